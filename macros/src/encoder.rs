@@ -0,0 +1,324 @@
+//! Encoding of items into statements building a set of tokens.
+
+use item::{Body, Else, Item};
+use proc_macro2::{Ident, LineColumn, Literal, Span, TokenStream};
+
+/// Encodes items into statements, inferring the layout from where the items were written.
+///
+/// Items written on a new line are pushed, and items which are indented further than the line
+/// before them are nested. Items on the same line are spaced if they were spaced in the input.
+pub struct Encoder {
+    /// Depth of the tokens which the encoder starts out building.
+    base: usize,
+    /// Statements of every open indentation level, innermost last.
+    streams: Vec<TokenStream>,
+    /// Columns of every open indentation level, innermost last.
+    indents: Vec<usize>,
+    /// Where the last item ended.
+    cursor: Option<LineColumn>,
+    /// Literal text which has not been appended yet.
+    text: String,
+    /// If spacing before the next item has already been taken care of.
+    spaced: bool,
+}
+
+impl Encoder {
+    /// Create a new encoder, building the tokens at the given depth.
+    pub fn new(base: usize) -> Encoder {
+        Encoder {
+            base,
+            streams: vec![TokenStream::new()],
+            indents: Vec::new(),
+            cursor: None,
+            text: String::new(),
+            spaced: false,
+        }
+    }
+
+    /// Encode the given items.
+    pub fn encode(&mut self, items: &[Item]) {
+        for (n, item) in items.iter().enumerate() {
+            self.item(item, items.get(n + 1));
+        }
+    }
+
+    /// Finish encoding, closing every open indentation level.
+    pub fn finish(mut self) -> TokenStream {
+        self.flush();
+
+        while self.streams.len() > 1 {
+            self.unindent();
+        }
+
+        self.streams.pop().unwrap_or_default()
+    }
+
+    fn item(&mut self, item: &Item, next: Option<&Item>) {
+        let spaced = self.layout(item.start());
+
+        // Spacing around repetitions and conditionals is only kept if they produce anything, by
+        // moving it into them. Spacing after them is preferred over spacing before them.
+        let mut around = Around::default();
+
+        match *item {
+            Item::For { .. } | Item::If { .. } => {
+                let end = item.end();
+
+                if next.is_some_and(|n| n.start().line == end.line && n.start().column > end.column)
+                {
+                    around.after = true;
+                    self.spaced = true;
+                } else if spaced {
+                    self.text.pop();
+                    around.before = true;
+                }
+            }
+            _ => {}
+        }
+
+        match *item {
+            Item::Text { ref text, .. } => {
+                self.text.push_str(text);
+            }
+            Item::Expr { ref expr, .. } => {
+                self.flush();
+                let t = self.tokens();
+                self.emit(quote!(#t.append(::std::clone::Clone::clone(&(#expr)));));
+            }
+            Item::For {
+                ref pat,
+                ref expr,
+                ref join,
+                ref body,
+                ..
+            } => {
+                self.flush();
+                let depth = self.depth();
+                let body = encode_body(body, depth, Around::default());
+
+                if join.is_none() && !around.before && !around.after {
+                    self.emit(quote!(for #pat in #expr { #body }));
+                } else {
+                    let t = self.tokens();
+                    let first = Ident::new("__first", Span::mixed_site());
+                    let mut stmts = TokenStream::new();
+
+                    if around.before {
+                        stmts.extend(quote!(if #first { #t.append(" "); }));
+                    }
+
+                    if let Some(ref join) = *join {
+                        let mut sep = Encoder::new(depth);
+                        sep.encode(join);
+                        let sep = sep.finish();
+                        stmts.extend(quote!(if !#first { #sep }));
+                    }
+
+                    let after = if around.after {
+                        quote!(if !#first { #t.append(" "); })
+                    } else {
+                        TokenStream::new()
+                    };
+
+                    self.emit(quote! {
+                        {
+                            let mut #first = true;
+
+                            for #pat in #expr {
+                                #stmts
+                                #first = false;
+                                #body
+                            }
+
+                            #after
+                        }
+                    });
+                }
+            }
+            Item::If { .. } => {
+                self.flush();
+                let stmt = encode_if(item, self.depth(), around);
+                self.emit(stmt);
+            }
+        }
+
+        self.cursor = Some(item.end());
+    }
+
+    /// Lay out an item starting at the given position, relative to the previous item.
+    ///
+    /// Returns `true` if the item is spaced from the previous item.
+    fn layout(&mut self, start: LineColumn) -> bool {
+        let spaced = ::std::mem::replace(&mut self.spaced, false);
+
+        let cursor = match self.cursor {
+            Some(cursor) => cursor,
+            None => {
+                self.indents.push(start.column);
+                return false;
+            }
+        };
+
+        if start.line > cursor.line {
+            self.flush();
+
+            while self.indents.len() > 1 && start.column < self.indent_column() {
+                self.unindent();
+            }
+
+            let t = self.tokens();
+
+            if start.line > cursor.line + 1 {
+                self.emit(quote!(#t.append(::rstgen::Element::LineSpacing);));
+            } else {
+                self.emit(quote!(#t.append(::rstgen::Element::PushSpacing);));
+            }
+
+            if start.column > self.indent_column() {
+                self.indents.push(start.column);
+                self.streams.push(TokenStream::new());
+            }
+        } else if start.column > cursor.column && !spaced {
+            self.text.push(' ');
+            return true;
+        }
+
+        false
+    }
+
+    /// Close the innermost indentation level, nesting its tokens.
+    fn unindent(&mut self) {
+        self.indents.pop();
+        let inner = tokens_ident(self.depth());
+        let stream = self.streams.pop().unwrap_or_default();
+        let t = self.tokens();
+
+        self.emit(quote! {
+            {
+                let mut #inner = ::rstgen::Tokens::new();
+                #stream
+                #t.nested(#inner);
+            }
+        });
+    }
+
+    /// Append pending literal text.
+    fn flush(&mut self) {
+        if !self.text.is_empty() {
+            let t = self.tokens();
+            let text = Literal::string(&self.text);
+            self.text.clear();
+            self.emit(quote!(#t.append(#text);));
+        }
+    }
+
+    fn emit(&mut self, stmt: TokenStream) {
+        if let Some(stream) = self.streams.last_mut() {
+            stream.extend(stmt);
+        }
+    }
+
+    fn indent_column(&self) -> usize {
+        self.indents.last().cloned().unwrap_or(0)
+    }
+
+    /// Depth of the tokens currently being built.
+    fn depth(&self) -> usize {
+        self.base + self.streams.len() - 1
+    }
+
+    /// Name of the tokens currently being built.
+    fn tokens(&self) -> Ident {
+        tokens_ident(self.depth())
+    }
+}
+
+/// Encode a whole invocation into an expression building a set of tokens.
+pub fn encode(items: &[Item]) -> TokenStream {
+    let mut encoder = Encoder::new(0);
+    encoder.encode(items);
+    let stream = encoder.finish();
+    let t = tokens_ident(0);
+
+    quote! {
+        {
+            #[allow(unused_mut)]
+            let mut #t = ::rstgen::Tokens::new();
+            #stream
+            #t
+        }
+    }
+}
+
+/// Spacing to add around a repetition or a conditional.
+#[derive(Clone, Copy, Default)]
+struct Around {
+    before: bool,
+    after: bool,
+}
+
+/// Encode the body of a repetition or a conditional.
+///
+/// A body starting on a new line is pushed, otherwise it is appended to the current line with the
+/// given spacing around it.
+fn encode_body(body: &Body, depth: usize, around: Around) -> TokenStream {
+    let mut encoder = Encoder::new(depth);
+
+    let first = match body.items.first() {
+        Some(first) => first,
+        None => return TokenStream::new(),
+    };
+
+    if first.start().line > body.open.line {
+        let t = tokens_ident(depth);
+        encoder.emit(quote!(#t.append(::rstgen::Element::PushSpacing);));
+        encoder.encode(&body.items);
+        return encoder.finish();
+    }
+
+    if around.before {
+        encoder.text.push(' ');
+    }
+
+    encoder.encode(&body.items);
+
+    if around.after {
+        encoder.text.push(' ');
+    }
+
+    encoder.finish()
+}
+
+/// Encode a conditional.
+fn encode_if(item: &Item, depth: usize, around: Around) -> TokenStream {
+    let (cond, then, otherwise) = match *item {
+        Item::If {
+            ref cond,
+            ref then,
+            ref otherwise,
+            ..
+        } => (cond, then, otherwise),
+        _ => return TokenStream::new(),
+    };
+
+    let then = encode_body(then, depth, around);
+
+    let otherwise = match *otherwise {
+        Some(Else::Body(ref body)) => {
+            let body = encode_body(body, depth, around);
+            quote!(else { #body })
+        }
+        Some(Else::If(ref item)) => {
+            let item = encode_if(item, depth, around);
+            quote!(else #item)
+        }
+        None => TokenStream::new(),
+    };
+
+    quote!(if #cond { #then } #otherwise)
+}
+
+/// Name of the tokens being built at the given depth.
+fn tokens_ident(depth: usize) -> Ident {
+    Ident::new(&format!("__t{}", depth), Span::mixed_site())
+}
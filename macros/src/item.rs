@@ -0,0 +1,374 @@
+//! Parsing of the input of `quote!` into items.
+
+use proc_macro2::{Delimiter, LineColumn, Span, TokenStream, TokenTree};
+
+/// Error raised while parsing, reported as a `compile_error!` at the given span.
+pub struct Error {
+    pub span: Span,
+    pub message: &'static str,
+}
+
+/// A single item of quoted code.
+pub enum Item {
+    /// Literal code of the target language.
+    Text {
+        text: String,
+        start: LineColumn,
+        end: LineColumn,
+    },
+    /// An interpolated expression, `#var` or `#(expr)`.
+    Expr {
+        expr: TokenStream,
+        start: LineColumn,
+        end: LineColumn,
+    },
+    /// A repetition, `#(for pat in expr join (sep) { body })`.
+    For {
+        pat: TokenStream,
+        expr: TokenStream,
+        join: Option<Vec<Item>>,
+        body: Body,
+        start: LineColumn,
+        end: LineColumn,
+    },
+    /// A conditional, `#(if cond { body } else { body })`.
+    If {
+        cond: TokenStream,
+        then: Body,
+        otherwise: Option<Else>,
+        start: LineColumn,
+        end: LineColumn,
+    },
+}
+
+impl Item {
+    /// Where the item starts.
+    pub fn start(&self) -> LineColumn {
+        match *self {
+            Item::Text { start, .. }
+            | Item::Expr { start, .. }
+            | Item::For { start, .. }
+            | Item::If { start, .. } => start,
+        }
+    }
+
+    /// Where the item ends.
+    pub fn end(&self) -> LineColumn {
+        match *self {
+            Item::Text { end, .. }
+            | Item::Expr { end, .. }
+            | Item::For { end, .. }
+            | Item::If { end, .. } => end,
+        }
+    }
+}
+
+/// The body of a repetition or a conditional.
+pub struct Body {
+    /// Where the opening brace of the body ends.
+    pub open: LineColumn,
+    pub items: Vec<Item>,
+}
+
+/// The alternative of a conditional.
+pub enum Else {
+    /// `else { body }`.
+    Body(Body),
+    /// `else if cond { body }`.
+    If(Box<Item>),
+}
+
+/// Parse the given stream into items.
+pub fn parse(input: TokenStream) -> Result<Vec<Item>, Error> {
+    let mut items = Vec::new();
+    parse_into(input, &mut items)?;
+    Ok(items)
+}
+
+fn parse_into(input: TokenStream, items: &mut Vec<Item>) -> Result<(), Error> {
+    let mut it = input.into_iter().peekable();
+
+    while let Some(tt) = it.next() {
+        match tt {
+            TokenTree::Punct(ref punct) if punct.as_char() == '#' => {
+                let start = punct.span().start();
+
+                match it.peek().cloned() {
+                    Some(TokenTree::Ident(ident)) => {
+                        it.next();
+
+                        items.push(Item::Expr {
+                            expr: TokenTree::Ident(ident.clone()).into(),
+                            start,
+                            end: ident.span().end(),
+                        });
+                    }
+                    Some(TokenTree::Group(ref group))
+                        if group.delimiter() == Delimiter::Parenthesis =>
+                    {
+                        it.next();
+                        items.push(parse_hash(group.stream(), start, group.span_close().end())?);
+                    }
+                    Some(TokenTree::Punct(ref escaped)) if escaped.as_char() == '#' => {
+                        it.next();
+
+                        items.push(Item::Text {
+                            text: String::from("#"),
+                            start,
+                            end: escaped.span().end(),
+                        });
+                    }
+                    _ => items.push(Item::Text {
+                        text: String::from("#"),
+                        start,
+                        end: punct.span().end(),
+                    }),
+                }
+            }
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Brace => ("{", "}"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::None => {
+                        parse_into(group.stream(), items)?;
+                        continue;
+                    }
+                };
+
+                items.push(Item::Text {
+                    text: String::from(open),
+                    start: group.span_open().start(),
+                    end: group.span_open().end(),
+                });
+
+                parse_into(group.stream(), items)?;
+
+                items.push(Item::Text {
+                    text: String::from(close),
+                    start: group.span_close().start(),
+                    end: group.span_close().end(),
+                });
+            }
+            tt => items.push(Item::Text {
+                text: tt.to_string(),
+                start: tt.span().start(),
+                end: tt.span().end(),
+            }),
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse the content of `#(...)`.
+fn parse_hash(input: TokenStream, start: LineColumn, end: LineColumn) -> Result<Item, Error> {
+    let tokens = input.clone().into_iter().collect::<Vec<_>>();
+
+    match tokens.first() {
+        Some(TokenTree::Ident(ref ident)) if ident == "for" => parse_for(&tokens[1..], start, end),
+        Some(TokenTree::Ident(ref ident)) if ident == "if" => parse_if(&tokens[1..], start, end),
+        _ => Ok(Item::Expr {
+            expr: input,
+            start,
+            end,
+        }),
+    }
+}
+
+/// Parse a repetition, after the leading `for`.
+fn parse_for(tokens: &[TokenTree], start: LineColumn, end: LineColumn) -> Result<Item, Error> {
+    let (body, rest) = match tokens.split_last() {
+        Some((TokenTree::Group(ref body), rest)) if body.delimiter() == Delimiter::Brace => {
+            (body, rest)
+        }
+        _ => {
+            return Err(Error {
+                span: span_of(tokens),
+                message: "expected repetition to end with a body, like `for x in xs { ... }`",
+            })
+        }
+    };
+
+    let in_ = rest
+        .iter()
+        .position(|tt| match *tt {
+            TokenTree::Ident(ref ident) => ident == "in",
+            _ => false,
+        })
+        .ok_or_else(|| Error {
+            span: span_of(tokens),
+            message: "expected `in` in repetition",
+        })?;
+
+    let pat = &rest[..in_];
+    let mut expr = &rest[in_ + 1..];
+    let mut join = None;
+
+    if expr.len() >= 2 {
+        let n = expr.len();
+
+        if let (TokenTree::Ident(ref ident), TokenTree::Group(ref group)) =
+            (&expr[n - 2], &expr[n - 1])
+        {
+            let method = n > 2 && is_path_punct(&expr[n - 3]);
+
+            if ident == "join" && group.delimiter() == Delimiter::Parenthesis && !method {
+                let mut items = parse(group.stream())?;
+                let close = group.span_close().start();
+
+                // Keep the spacing before the closing parenthesis, like in `join (, )`.
+                items.push(Item::Text {
+                    text: String::new(),
+                    start: close,
+                    end: close,
+                });
+
+                join = Some(items);
+                expr = &expr[..n - 2];
+            }
+        }
+    }
+
+    Ok(Item::For {
+        pat: pat.iter().cloned().collect(),
+        expr: expr.iter().cloned().collect(),
+        join,
+        body: parse_body(body.stream(), body.span_open().end())?,
+        start,
+        end,
+    })
+}
+
+/// Parse a conditional, after the leading `if`.
+fn parse_if(tokens: &[TokenTree], start: LineColumn, end: LineColumn) -> Result<Item, Error> {
+    let then = tokens
+        .iter()
+        .position(|tt| match *tt {
+            TokenTree::Group(ref group) => group.delimiter() == Delimiter::Brace,
+            _ => false,
+        })
+        .ok_or_else(|| Error {
+            span: span_of(tokens),
+            message: "expected conditional to have a body, like `if cond { ... }`",
+        })?;
+
+    let body = match tokens[then] {
+        TokenTree::Group(ref group) => parse_body(group.stream(), group.span_open().end())?,
+        _ => unreachable!(),
+    };
+
+    let rest = &tokens[then + 1..];
+
+    let otherwise = match rest.split_first() {
+        None => None,
+        Some((TokenTree::Ident(ref ident), rest)) if ident == "else" => match rest.split_first() {
+            Some((TokenTree::Group(ref group), &[])) if group.delimiter() == Delimiter::Brace => {
+                Some(Else::Body(parse_body(
+                    group.stream(),
+                    group.span_open().end(),
+                )?))
+            }
+            Some((TokenTree::Ident(ref ident), rest)) if ident == "if" => {
+                Some(Else::If(Box::new(parse_if(rest, start, end)?)))
+            }
+            _ => {
+                return Err(Error {
+                    span: span_of(rest),
+                    message: "expected `{ ... }` or `if` after `else`",
+                })
+            }
+        },
+        Some(_) => {
+            return Err(Error {
+                span: span_of(rest),
+                message: "expected `else` or the end of the conditional",
+            })
+        }
+    };
+
+    Ok(Item::If {
+        cond: tokens[..then].iter().cloned().collect(),
+        then: body,
+        otherwise,
+        start,
+        end,
+    })
+}
+
+fn parse_body(input: TokenStream, open: LineColumn) -> Result<Body, Error> {
+    Ok(Body {
+        open,
+        items: parse(input)?,
+    })
+}
+
+/// Check if the given token makes a following identifier part of a path or a method call.
+fn is_path_punct(tt: &TokenTree) -> bool {
+    match *tt {
+        TokenTree::Punct(ref punct) => punct.as_char() == '.' || punct.as_char() == ':',
+        _ => false,
+    }
+}
+
+/// Span to report errors about the given tokens at.
+fn span_of(tokens: &[TokenTree]) -> Span {
+    tokens
+        .first()
+        .map(|tt| tt.span())
+        .unwrap_or_else(Span::call_site)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Error};
+    use proc_macro2::TokenStream;
+
+    fn error(input: &str) -> Error {
+        let input = input.parse::<TokenStream>().unwrap();
+
+        match parse(input) {
+            Ok(_) => panic!("expected an error"),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn test_for_errors() {
+        let e = error("#(for x xs { a })");
+        assert_eq!("expected `in` in repetition", e.message);
+        assert_eq!(6, e.span.start().column);
+
+        let e = error("#(for x in xs)");
+        assert_eq!(
+            "expected repetition to end with a body, like `for x in xs { ... }`",
+            e.message
+        );
+    }
+
+    #[test]
+    fn test_if_errors() {
+        let e = error("#(if a { b } else c)");
+        assert_eq!("expected `{ ... }` or `if` after `else`", e.message);
+        assert_eq!(18, e.span.start().column);
+
+        let e = error("#(if a { b } else { c } d)");
+        assert_eq!("expected `{ ... }` or `if` after `else`", e.message);
+
+        let e = error("#(if a { b } c)");
+        assert_eq!("expected `else` or the end of the conditional", e.message);
+        assert_eq!(13, e.span.start().column);
+
+        let e = error("#(if a)");
+        assert_eq!(
+            "expected conditional to have a body, like `if cond { ... }`",
+            e.message
+        );
+    }
+
+    #[test]
+    fn test_nested_errors() {
+        let e = error("a #(for x in xs { #(if x { b } else) })");
+        assert_eq!("expected `{ ... }` or `if` after `else`", e.message);
+    }
+}
@@ -0,0 +1,46 @@
+//! Procedural macros for rstgen.
+#![deny(missing_docs)]
+
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+
+mod encoder;
+mod item;
+
+use proc_macro2::TokenStream;
+
+/// Quasi-quote code of any language into a set of tokens.
+///
+/// The structure of the tokens is inferred from the input itself: code written on a new line is
+/// pushed, code which is indented further than the line before it is nested, and empty lines are
+/// kept. Code on the same line is spaced as it was written.
+///
+/// The following is supported inside of the quoted code:
+///
+/// * `#var` - Interpolate a variable, which is cloned and converted into an element.
+/// * `#(expr)` - Interpolate the result of an expression.
+/// * `#(for pat in expr { ... })` - Repeat the body for every item. A body starting on a new line
+///   puts every repetition on a line of its own. A separator can be added between repetitions
+///   with `#(for pat in expr join (...) { ... })`.
+/// * `#(if cond { ... } else { ... })` - Include a body conditionally, with an optional `else`
+///   or `else if` alternative.
+/// * `##` - A literal `#`.
+///
+/// Since the input is tokenized like Rust, comments are dropped, and quotes and delimiters have
+/// to be balanced.
+#[proc_macro]
+pub fn quote(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = TokenStream::from(input);
+
+    let output = match item::parse(input) {
+        Ok(items) => encoder::encode(&items),
+        Err(e) => {
+            let message = e.message;
+            quote_spanned!(e.span=> compile_error!(#message))
+        }
+    };
+
+    output.into()
+}
@@ -0,0 +1,129 @@
+//! Language-agnostic comments.
+
+use {Cons, Formatter, Result};
+
+/// Syntax of a comment in a language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommentStyle {
+    /// Every line is prefixed with the given string, like `//` or `#`.
+    Line(&'static str),
+    /// The comment is delimited by an opening and closing line, with every line in between
+    /// prefixed with the given string, like `/*`, ` *`, and ` */`.
+    Block {
+        /// Line opening the comment.
+        open: &'static str,
+        /// Prefix of every line in the comment.
+        prefix: &'static str,
+        /// Line closing the comment.
+        close: &'static str,
+    },
+}
+
+impl CommentStyle {
+    /// Format the given lines as a comment.
+    ///
+    /// Lines containing line breaks are split, and any terminator of the comment is escaped.
+    pub fn format(&self, out: &mut Formatter, lines: &[Cons]) -> Result<()> {
+        if lines.is_empty() {
+            return Ok(());
+        }
+
+        let lines = lines.iter().flat_map(|line| line.lines());
+
+        out.new_line_unless_empty()?;
+
+        match *self {
+            CommentStyle::Line(prefix) => {
+                for (n, line) in lines.enumerate() {
+                    if n > 0 {
+                        out.new_line()?;
+                    }
+
+                    write_line(out, prefix, line)?;
+                }
+            }
+            CommentStyle::Block {
+                open,
+                prefix,
+                close,
+            } => {
+                let terminator = close.trim_start();
+
+                out.write_str(open)?;
+
+                for line in lines {
+                    out.new_line()?;
+                    write_line(out, prefix, &escape(line, terminator))?;
+                }
+
+                out.new_line()?;
+                out.write_str(close)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Write a single line of a comment.
+fn write_line(out: &mut Formatter, prefix: &str, line: &str) -> Result<()> {
+    out.write_str(prefix)?;
+
+    if !line.is_empty() {
+        if !prefix.is_empty() {
+            out.write_str(" ")?;
+        }
+
+        out.write_str(line)?;
+    }
+
+    Ok(())
+}
+
+/// Escape the terminator of a comment, by inserting a backslash before its last character.
+fn escape(line: &str, terminator: &str) -> String {
+    let last = match terminator.char_indices().last() {
+        Some((n, _)) => n,
+        None => return line.to_string(),
+    };
+
+    let escaped = format!("{}\\{}", &terminator[..last], &terminator[last..]);
+    line.replace(terminator, &escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use {Element, Tokens};
+
+    fn comment(element: Element<'static, ()>) -> String {
+        let mut toks = Tokens::new();
+        toks.push("before");
+        toks.push(element);
+        toks.push("after");
+        toks.to_string().unwrap()
+    }
+
+    #[test]
+    fn test_line_comment() {
+        assert_eq!(
+            "before\n// foo\n//\n// bar\n// baz\nafter",
+            comment(Element::LineComment(vec![
+                "foo\n\nbar".into(),
+                "baz".into()
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_block_comment() {
+        assert_eq!(
+            "before\n/*\n * foo *\\/ bar\n *\n * baz\n */\nafter",
+            comment(Element::BlockComment(vec!["foo */ bar\n\nbaz".into()]))
+        );
+    }
+
+    #[test]
+    fn test_empty_comment() {
+        assert_eq!("before\nafter", comment(Element::DocComment(vec![])));
+    }
+}
@@ -1,6 +1,8 @@
 //! Helper container for borrowed or owned values.
 
-use std::rc::Rc;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use shared::Rc;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Con<'a, T: 'a> {
@@ -20,3 +22,45 @@ impl<'a, T> AsRef<T> for Con<'a, T> {
         }
     }
 }
+
+impl<'a, T: Clone> Con<'a, T> {
+    /// Access the value mutably, cloning it if it is borrowed or shared.
+    pub fn make_mut(&mut self) -> &mut T {
+        if let Con::Borrowed(value) = *self {
+            *self = Con::Owned(value.clone());
+        }
+
+        match *self {
+            Con::Borrowed(_) => unreachable!(),
+            Con::Owned(ref mut value) => value,
+            Con::Rc(ref mut value) => Rc::make_mut(value),
+        }
+    }
+}
+
+/// Values are serialized as-is, and deserialized into owned values.
+#[cfg(feature = "serde")]
+impl<'a, T> Serialize for Con<'a, T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.as_ref().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, 'a, T> Deserialize<'de> for Con<'a, T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(Con::Owned)
+    }
+}
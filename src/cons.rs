@@ -1,9 +1,11 @@
 //! Helper trait to take ownership of strings.
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use shared::Rc;
 use std::borrow::Cow;
 use std::fmt;
 use std::ops::Deref;
-use std::rc::Rc;
 
 /// A managed string that permits immutable borrowing.
 #[derive(Debug, Clone, Hash, PartialOrd, Ord, PartialEq, Eq)]
@@ -11,6 +13,8 @@ pub enum Cons<'el> {
     /// A borrowed string.
     Borrowed(&'el str),
     /// A refcounted string.
+    ///
+    /// Build it with [`rstgen::Rc`](::Rc), which is `Arc` with the `sync` feature enabled.
     Rc(Rc<String>),
 }
 
@@ -67,3 +71,24 @@ impl<'el> fmt::Display for Cons<'el> {
         self.as_ref().fmt(fmt)
     }
 }
+
+/// Strings are serialized as-is, and deserialized into refcounted strings.
+#[cfg(feature = "serde")]
+impl<'el> Serialize for Cons<'el> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_ref())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, 'el> Deserialize<'de> for Cons<'el> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Cons::from(String::deserialize(deserializer)?))
+    }
+}
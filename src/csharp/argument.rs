@@ -3,11 +3,15 @@
 use super::modifier::Modifier;
 use cons::Cons;
 use csharp::Csharp;
+use ident;
 use into_tokens::IntoTokens;
+use naming::Identifier;
 use tokens::Tokens;
+use Custom;
 
 /// Model for C# Arguments to functions.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Argument<'el> {
     /// Modifiers for argument.
     pub modifiers: Vec<Modifier>,
@@ -24,13 +28,13 @@ impl<'el> Argument<'el> {
     pub fn new<T, N>(ty: T, name: N) -> Argument<'el>
     where
         T: Into<Csharp<'el>>,
-        N: Into<Cons<'el>>,
+        N: Into<Identifier<'el>>,
     {
         Argument {
             attributes: Tokens::new(),
             modifiers: vec![],
             ty: ty.into(),
-            name: name.into(),
+            name: ident::escape::<Csharp>(name.into().convert(Csharp::naming().field)),
         }
     }
 
@@ -12,6 +12,7 @@ use tokens::Tokens;
 
 /// Model for Csharp Classs.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Class<'el> {
     /// Class modifiers.
     pub modifiers: Vec<Modifier>,
@@ -145,7 +146,7 @@ impl<'el> IntoTokens<'el, Csharp<'el>> for Class<'el> {
 
         s.push("}");
 
-        s
+        s.with_context(format!("class {}", self.name))
     }
 }
 
@@ -2,15 +2,14 @@
 
 use super::argument::Argument;
 use super::modifier::Modifier;
-use con_::Con::Owned;
 use cons::Cons;
 use csharp::Csharp;
-use element::Element;
 use into_tokens::IntoTokens;
 use tokens::Tokens;
 
 /// Model for Csharp Constructors.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Constructor<'el> {
     /// Constructor modifiers.
     pub modifiers: Vec<Modifier>,
@@ -49,8 +48,6 @@ into_tokens_impl_from!((Cons<'el>, Constructor<'el>), Csharp<'el>);
 
 impl<'el> IntoTokens<'el, Csharp<'el>> for (Cons<'el>, Constructor<'el>) {
     fn into_tokens(self) -> Tokens<'el, Csharp<'el>> {
-        use self::Element::*;
-
         let (name, mut c) = self;
 
         let args: Vec<Tokens<Csharp>> = c.arguments.into_iter().map(|a| a.into_tokens()).collect();
@@ -61,14 +58,7 @@ impl<'el> IntoTokens<'el, Csharp<'el>> for (Cons<'el>, Constructor<'el>) {
         c.modifiers.sort();
         sig.extend(c.modifiers.into_iter().map(Into::into));
 
-        if !args.is_empty() {
-            let sep = toks![",", PushSpacing];
-            let args = args.join(sep);
-
-            sig.append(toks![name, "(", Nested(Owned(args)), ")",]);
-        } else {
-            sig.append(toks![name, "()"]);
-        }
+        sig.append(toks![name, args.join_group("(", ",", ")")]);
 
         if let Some(base) = c.base {
             sig.append(":");
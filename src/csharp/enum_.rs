@@ -9,6 +9,7 @@ use tokens::Tokens;
 
 /// Model for Csharp Enums.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Enum<'el> {
     /// Variants of the enum.
     pub variants: Tokens<'el, Csharp<'el>>,
@@ -113,7 +114,7 @@ mod tests {
 
         let s = t.to_string();
         let out = s.as_ref().map(|s| s.as_str());
-        assert_eq!(Ok("public enum Foo {\n  FOO(1),\n  BAR(2)\n}",), out);
+        assert_eq!(Ok("public enum Foo {\n    FOO(1),\n    BAR(2)\n}",), out);
     }
 
     #[test]
@@ -129,6 +130,9 @@ mod tests {
 
         let s = t.to_string();
         let out = s.as_ref().map(|s| s.as_str());
-        assert_eq!(Ok("public enum Foo : long {\n  FOO(1),\n  BAR(2)\n}",), out);
+        assert_eq!(
+            Ok("public enum Foo : long {\n    FOO(1),\n    BAR(2)\n}",),
+            out
+        );
     }
 }
@@ -2,10 +2,12 @@
 
 use con_::Con;
 use csharp::{BlockComment, Modifier};
-use {Cons, Csharp, Element, IntoTokens, Tokens};
+use naming::Identifier;
+use {ident, Cons, Csharp, Custom, Element, IntoTokens, Tokens};
 
 /// Model for Csharp Fields.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Field<'el> {
     /// Attributes of field.
     pub attributes: Tokens<'el, Csharp<'el>>,
@@ -26,7 +28,7 @@ impl<'el> Field<'el> {
     pub fn new<T, N>(ty: T, name: N) -> Field<'el>
     where
         T: Into<Csharp<'el>>,
-        N: Into<Cons<'el>>,
+        N: Into<Identifier<'el>>,
     {
         use self::Modifier::*;
 
@@ -36,7 +38,7 @@ impl<'el> Field<'el> {
             comments: vec![],
             block: None,
             ty: ty.into(),
-            name: name.into(),
+            name: ident::escape::<Csharp>(name.into().convert(Csharp::naming().field)),
         }
     }
 
@@ -136,4 +138,10 @@ mod tests {
         let t = Tokens::from(field());
         assert_eq!(Ok(String::from("private Int32 foo")), t.to_string());
     }
+
+    #[test]
+    fn test_keyword() {
+        let t = Tokens::from(Field::new(INT32, "class"));
+        assert_eq!(Ok(String::from("private Int32 @class")), t.to_string());
+    }
 }
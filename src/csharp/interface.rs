@@ -5,6 +5,7 @@ use {Cons, Csharp, Element, IntoTokens, Tokens};
 
 /// Model for Csharp Interfaces.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Interface<'el> {
     /// Interface modifiers.
     pub modifiers: Vec<Modifier>,
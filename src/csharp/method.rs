@@ -1,10 +1,12 @@
 //! Data structure for methods.
 
 use csharp::{Argument, BlockComment, Csharp, Modifier};
-use {Cons, IntoTokens, Tokens};
+use naming::Identifier;
+use {ident, Cons, Custom, IntoTokens, Tokens};
 
 /// Model for Csharp Methods.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Method<'el> {
     /// Method modifiers.
     pub modifiers: Vec<Modifier>,
@@ -28,7 +30,7 @@ impl<'el> Method<'el> {
     /// Build a new empty constructor.
     pub fn new<N>(name: N) -> Method<'el>
     where
-        N: Into<Cons<'el>>,
+        N: Into<Identifier<'el>>,
     {
         use self::Modifier::*;
 
@@ -40,7 +42,7 @@ impl<'el> Method<'el> {
             parameters: Tokens::new(),
             comments: Vec::new(),
             attributes: Tokens::new(),
-            name: name.into(),
+            name: ident::escape::<Csharp>(name.into().convert(Csharp::naming().method)),
         }
     }
 
@@ -62,6 +64,8 @@ into_tokens_impl_from!(Method<'el>, Csharp<'el>);
 
 impl<'el> IntoTokens<'el, Csharp<'el>> for Method<'el> {
     fn into_tokens(self) -> Tokens<'el, Csharp<'el>> {
+        let context = format!("method {}", self.name);
+
         let mut sig = Tokens::new();
 
         sig.extend(self.modifiers.into_tokens());
@@ -84,7 +88,7 @@ impl<'el> IntoTokens<'el, Csharp<'el>> for Method<'el> {
 
             let args: Tokens<Csharp> = args.into_tokens();
 
-            n.append(toks!["(", args.join(", "), ")"]);
+            n.append(args.join_group("(", ",", ")"));
 
             n
         });
@@ -104,13 +108,14 @@ impl<'el> IntoTokens<'el, Csharp<'el>> for Method<'el> {
             s.push("}");
         }
 
-        s
+        s.with_context(context)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use csharp::Method;
+    use naming;
     use tokens::Tokens;
 
     fn build_method() -> Method<'static> {
@@ -135,4 +140,10 @@ mod tests {
         let t = Tokens::from(build_method());
         assert_eq!(Ok(String::from("public void Foo<T>();")), t.to_string());
     }
+
+    #[test]
+    fn test_raw_name() {
+        let t = Tokens::from(Method::new(naming::raw("get_user_id")));
+        assert_eq!(Ok(String::from("public void GetUserId();")), t.to_string());
+    }
 }
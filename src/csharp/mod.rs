@@ -19,9 +19,17 @@ pub use self::interface::Interface;
 pub use self::method::Method;
 pub use self::modifier::Modifier;
 pub use self::utils::BlockComment;
-use std::collections::{BTreeSet, HashMap, HashSet};
-use std::fmt::{self, Write};
-use {Cons, Custom, Formatter, IntoTokens, Tokens};
+use imports::{Imports, Resolved};
+use naming::{Case, Naming};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt::Write;
+use std::path::PathBuf;
+use syntax::{Declaration, Exceptions, ForEach, Lambda, Syntax};
+use value::{self, Value};
+use {
+    CommentStyle, Cons, Custom, Formatter, FormatterConfig, Indentation, IntoOwned, IntoTokens,
+    Result, Tokens,
+};
 
 static SYSTEM: &'static str = "System";
 static SEP: &'static str = ".";
@@ -100,6 +108,7 @@ pub const UINT64: Csharp<'static> = Csharp::Simple {
 
 /// A class.
 #[derive(Debug, Clone, Hash, PartialOrd, Ord, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Type<'el> {
     /// namespace of the class.
     namespace: Cons<'el>,
@@ -115,6 +124,7 @@ pub struct Type<'el> {
 
 /// Csharp token specialization.
 #[derive(Debug, Clone, Hash, PartialOrd, Ord, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum Csharp<'el> {
     /// Simple type.
     Simple {
@@ -146,13 +156,13 @@ into_tokens_impl_from!(Csharp<'el>, Csharp<'el>);
 into_tokens_impl_from!(&'el Csharp<'el>, Csharp<'el>);
 
 /// Extra data for Csharp formatting.
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct Extra<'el> {
     /// namespace to use.
     pub namespace: Option<Cons<'el>>,
 
-    /// Names which have been imported (namespace + name).
-    imported_names: HashMap<String, String>,
+    /// Names which have been bound in the local namespace.
+    imports: Imports,
 }
 
 impl<'el> Extra<'el> {
@@ -163,6 +173,17 @@ impl<'el> Extra<'el> {
     {
         self.namespace = Some(namespace.into())
     }
+
+    /// Get the namespace the given simple name refers to, if it has been bound by a `using`
+    /// directive.
+    pub fn imported(&self, name: &str) -> Option<&str> {
+        self.imports.module(name)
+    }
+
+    /// Check if types from the given namespace are available without a `using` directive.
+    fn is_implicit(&self, namespace: &str) -> bool {
+        self.namespace.as_ref().map(|n| n.as_ref()) == Some(namespace)
+    }
 }
 
 impl<'el> Csharp<'el> {
@@ -189,15 +210,23 @@ impl<'el> Csharp<'el> {
         }
     }
 
-    fn inner_imports<'a>(ty: &'a Type<'a>, modules: &mut BTreeSet<(&'a str, &'a str)>) {
+    fn inner_imports<'a>(
+        ty: &'a Type<'a>,
+        modules: &mut BTreeSet<(&'a str, &'a str)>,
+        locals: &mut BTreeSet<&'a str>,
+    ) {
         for argument in &ty.arguments {
-            Self::type_imports(argument, modules);
+            Self::type_imports(argument, modules, locals);
         }
 
         modules.insert((ty.namespace.as_ref(), ty.name.as_ref()));
     }
 
-    fn type_imports<'a>(csharp: &'a Csharp<'a>, modules: &mut BTreeSet<(&'a str, &'a str)>) {
+    fn type_imports<'a>(
+        csharp: &'a Csharp<'a>,
+        modules: &mut BTreeSet<(&'a str, &'a str)>,
+        locals: &mut BTreeSet<&'a str>,
+    ) {
         use self::Csharp::*;
 
         match *csharp {
@@ -205,64 +234,89 @@ impl<'el> Csharp<'el> {
                 modules.insert((SYSTEM, alias));
             }
             Class(ref inner) => {
-                Self::inner_imports(inner, modules);
+                Self::inner_imports(inner, modules, locals);
             }
             Array(ref inner) => {
-                Self::type_imports(inner, modules);
+                Self::type_imports(inner, modules, locals);
             }
             Struct(ref inner) => {
-                Self::inner_imports(inner, modules);
+                Self::inner_imports(inner, modules, locals);
             }
             Enum(ref inner) => {
-                Self::inner_imports(inner, modules);
+                Self::inner_imports(inner, modules, locals);
             }
             Optional(ref value) => {
-                Self::type_imports(value, modules);
+                Self::type_imports(value, modules, locals);
+            }
+            Local { ref name } => {
+                locals.insert(name.as_ref());
             }
             _ => {}
         };
     }
 
-    fn imports<'a>(tokens: &'a Tokens<'a, Self>, extra: &mut Extra) -> Option<Tokens<'a, Self>> {
+    fn imports<'a>(tokens: &'a Tokens<Self>, extra: &mut Extra) -> Option<Tokens<'a, Self>> {
         let mut modules = BTreeSet::new();
-
-        let file_namespace = extra.namespace.as_ref().map(|p| p.as_ref());
+        let mut locals = BTreeSet::new();
 
         for custom in tokens.walk_custom() {
-            Self::type_imports(custom, &mut modules);
+            Self::type_imports(custom, &mut modules, &mut locals);
         }
 
         if modules.is_empty() {
             return None;
         }
 
+        // Local names, and names in the namespace of the file, take precedence over names from
+        // other namespaces.
+        for name in locals {
+            extra.imports.bind("", name);
+        }
+
+        for &(namespace, name) in &modules {
+            if extra.is_implicit(namespace) {
+                extra.imports.bind(namespace, name);
+            }
+        }
+
+        // A using directive imports every name of a namespace, so a name which is also
+        // referenced from another imported namespace is ambiguous, and qualified everywhere.
+        let mut first = BTreeMap::new();
+
+        for &(namespace, name) in &modules {
+            if extra.is_implicit(namespace) || extra.imports.resolve(namespace, name).is_some() {
+                continue;
+            }
+
+            first.entry(name).or_insert(namespace);
+        }
+
+        let used = first.values().cloned().collect::<BTreeSet<_>>();
+
+        for &(namespace, name) in &modules {
+            if first.get(name).is_some_and(|&n| n != namespace) && used.contains(namespace) {
+                extra.imports.reserve(name);
+            }
+        }
+
         let mut out = Tokens::new();
         let mut imported = HashSet::new();
 
         for (namespace, name) in modules {
-            if Some(namespace) == file_namespace {
+            if extra.is_implicit(namespace) || extra.imports.resolve(namespace, name).is_some() {
                 continue;
             }
 
-            match extra.imported_names.get(name) {
-                // already imported...
-                Some(existing) if existing == namespace => continue,
-                // already imported, as something else...
-                Some(_) => continue,
-                _ => {}
+            if extra.imports.bind(namespace, name) != Resolved::Name {
+                continue;
             }
 
-            if !imported.contains(namespace) {
+            if imported.insert(namespace) {
                 out.push(toks!("using ", namespace, ";"));
-                imported.insert(namespace.to_string());
             }
-
-            extra
-                .imported_names
-                .insert(name.to_string(), namespace.to_string());
         }
 
-        Some(out)
+        Some(out).filter(|out| !out.is_empty())
     }
 
     /// Add arguments to the given variable.
@@ -464,18 +518,18 @@ impl<'el> Csharp<'el> {
         out: &mut Formatter,
         extra: &mut <Self as Custom>::Extra,
         level: usize,
-    ) -> fmt::Result {
+    ) -> Result<()> {
         {
             let qualified = match inner.qualified {
                 true => true,
                 false => {
-                    let file_namespace = extra.namespace.as_ref().map(|p| p.as_ref());
-                    let imported = extra
-                        .imported_names
-                        .get(inner.name.as_ref())
-                        .map(String::as_str);
-                    let pkg = Some(inner.namespace.as_ref());
-                    imported != pkg && file_namespace != pkg
+                    let namespace = inner.namespace.as_ref();
+
+                    match extra.imports.resolve(namespace, inner.name.as_ref()) {
+                        Some(Resolved::Name) => false,
+                        Some(_) => true,
+                        None => !extra.is_implicit(namespace),
+                    }
                 }
             };
 
@@ -516,10 +570,184 @@ impl<'el> Csharp<'el> {
     }
 }
 
+/// Reserved keywords.
+static KEYWORDS: &[&str] = &[
+    "abstract",
+    "as",
+    "base",
+    "bool",
+    "break",
+    "byte",
+    "case",
+    "catch",
+    "char",
+    "checked",
+    "class",
+    "const",
+    "continue",
+    "decimal",
+    "default",
+    "delegate",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "event",
+    "explicit",
+    "extern",
+    "false",
+    "finally",
+    "fixed",
+    "float",
+    "for",
+    "foreach",
+    "goto",
+    "if",
+    "implicit",
+    "in",
+    "int",
+    "interface",
+    "internal",
+    "is",
+    "lock",
+    "long",
+    "namespace",
+    "new",
+    "null",
+    "object",
+    "operator",
+    "out",
+    "override",
+    "params",
+    "private",
+    "protected",
+    "public",
+    "readonly",
+    "ref",
+    "return",
+    "sbyte",
+    "sealed",
+    "short",
+    "sizeof",
+    "stackalloc",
+    "static",
+    "string",
+    "struct",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "uint",
+    "ulong",
+    "unchecked",
+    "unsafe",
+    "ushort",
+    "using",
+    "virtual",
+    "void",
+    "volatile",
+    "while",
+];
+
+impl<'el> IntoOwned for Type<'el> {
+    type Owned = Type<'static>;
+
+    fn into_owned(self) -> Type<'static> {
+        Type {
+            namespace: self.namespace.into_owned(),
+            name: self.name.into_owned(),
+            path: self.path.into_owned(),
+            arguments: self.arguments.into_owned(),
+            qualified: self.qualified,
+        }
+    }
+}
+
+impl<'el> IntoOwned for Csharp<'el> {
+    type Owned = Csharp<'static>;
+
+    fn into_owned(self) -> Csharp<'static> {
+        use self::Csharp::*;
+
+        match self {
+            Simple { name, alias } => Simple { name, alias },
+            Array(inner) => Array(inner.into_owned()),
+            Struct(ty) => Struct(ty.into_owned()),
+            Void => Void,
+            Class(ty) => Class(ty.into_owned()),
+            Enum(ty) => Enum(ty.into_owned()),
+            Local { name } => Local {
+                name: name.into_owned(),
+            },
+            Optional(inner) => Optional(inner.into_owned()),
+        }
+    }
+}
+
+impl<'el> IntoOwned for Extra<'el> {
+    type Owned = Extra<'static>;
+
+    fn into_owned(self) -> Extra<'static> {
+        Extra {
+            namespace: self.namespace.into_owned(),
+            imports: self.imports,
+        }
+    }
+}
+
+/// Simple types are deserialized into one of the known simple types.
+#[cfg(feature = "serde")]
+impl<'de, 'el> ::serde::Deserialize<'de> for Csharp<'el> {
+    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+    where
+        D: ::serde::Deserializer<'de>,
+    {
+        use serde::de::{Error, Unexpected};
+
+        #[derive(Deserialize)]
+        #[serde(rename = "Csharp")]
+        enum Data<'el> {
+            Simple { name: String, alias: String },
+            Array(Box<Csharp<'el>>),
+            Struct(Type<'el>),
+            Void,
+            Class(Type<'el>),
+            Enum(Type<'el>),
+            Local { name: Cons<'el> },
+            Optional(Box<Csharp<'el>>),
+        }
+
+        let simple = [
+            BOOLEAN, BYTE, SBYTE, DECIMAL, SINGLE, DOUBLE, INT16, UINT16, INT32, UINT32, INT64,
+            UINT64,
+        ];
+
+        Ok(match Data::deserialize(deserializer)? {
+            Data::Simple { name, alias } => simple
+                .iter()
+                .find(|s| match **s {
+                    Csharp::Simple { name: n, alias: a } => n == name && a == alias,
+                    _ => false,
+                })
+                .cloned()
+                .ok_or_else(|| D::Error::invalid_value(Unexpected::Str(&name), &"a simple type"))?,
+            Data::Array(inner) => Csharp::Array(inner),
+            Data::Struct(ty) => Csharp::Struct(ty),
+            Data::Void => Csharp::Void,
+            Data::Class(ty) => Csharp::Class(ty),
+            Data::Enum(ty) => Csharp::Enum(ty),
+            Data::Local { name } => Csharp::Local { name },
+            Data::Optional(inner) => Csharp::Optional(inner),
+        })
+    }
+}
+
 impl<'el> Custom for Csharp<'el> {
     type Extra = Extra<'el>;
 
-    fn format(&self, out: &mut Formatter, extra: &mut Self::Extra, level: usize) -> fmt::Result {
+    fn format(&self, out: &mut Formatter, extra: &mut Self::Extra, level: usize) -> Result<()> {
         use self::Csharp::*;
 
         match *self {
@@ -551,7 +779,7 @@ impl<'el> Custom for Csharp<'el> {
         Ok(())
     }
 
-    fn quote_string(out: &mut Formatter, input: &str) -> fmt::Result {
+    fn quote_string(out: &mut Formatter, input: &str) -> Result<()> {
         out.write_char('"')?;
 
         for c in input.chars() {
@@ -573,33 +801,144 @@ impl<'el> Custom for Csharp<'el> {
         Ok(())
     }
 
+    fn doc_comment() -> CommentStyle {
+        CommentStyle::Line("///")
+    }
+
+    fn literal<'a>(value: Value<'a, Self>) -> Tokens<'a, Self>
+    where
+        Self: 'a,
+    {
+        match value {
+            Value::Long(value) => value::raw(format!("{}L", value)),
+            Value::Float(value) if !value.is_finite() => value::non_finite(
+                value.into(),
+                "float.NaN",
+                "float.PositiveInfinity",
+                "float.NegativeInfinity",
+            ),
+            Value::Double(value) if !value.is_finite() => value::non_finite(
+                value,
+                "double.NaN",
+                "double.PositiveInfinity",
+                "double.NegativeInfinity",
+            ),
+            Value::Float(value) => value::raw(format!("{}f", value::float(value))),
+            Value::Array(ty, values) => {
+                toks!["new ", ty, "[] ", value::sequence("{ ", values, " }")]
+            }
+            // the type of an implicitly typed array can't be inferred without elements.
+            Value::List(ref values) if values.is_empty() => value::raw("new object[0]"),
+            Value::List(values) => toks!["new[] ", value::sequence("{ ", values, " }")],
+            Value::Map(entries) => {
+                let mut t = toks![
+                    "new ",
+                    using("System.Collections.Generic", "Dictionary"),
+                    "<object, object>",
+                ];
+
+                if entries.is_empty() {
+                    t.append("()");
+                    return t;
+                }
+
+                t.append(" { ");
+
+                for (n, (key, value)) in entries.into_iter().enumerate() {
+                    if n > 0 {
+                        t.append(", ");
+                    }
+
+                    t.append(value::map("{ ", vec![(key, value)], ", ", " }"));
+                }
+
+                t.append(" }");
+                t
+            }
+            value => value::literal(value),
+        }
+    }
+
+    fn syntax() -> Syntax {
+        Syntax {
+            declaration: Declaration::Keyword {
+                immutable: "var",
+                mutable: "var",
+            },
+            for_each: ForEach {
+                keyword: "foreach",
+                binding: "var ",
+                separator: " in ",
+            },
+            lambda: Lambda::Arrow("=>"),
+            exceptions: Some(Exceptions::Typed {
+                any: "System.Exception",
+            }),
+            ..Syntax::default()
+        }
+    }
+
+    fn naming() -> Naming {
+        Naming {
+            type_name: Case::Pascal,
+            field: Case::Camel,
+            method: Case::Pascal,
+            constant: Case::Pascal,
+        }
+    }
+
+    fn keywords() -> &'static [&'static str] {
+        KEYWORDS
+    }
+
+    fn escape_keyword(ident: &str) -> String {
+        format!("@{}", ident)
+    }
+
+    fn file_path(name: &str, extra: &Self::Extra) -> PathBuf {
+        let mut path = PathBuf::new();
+
+        if let Some(ref namespace) = extra.namespace {
+            path.extend(namespace.split('.'));
+        }
+
+        path.push(format!("{}.cs", name));
+        path
+    }
+
     fn write_file<'a>(
-        tokens: Tokens<'a, Self>,
+        tokens: &Tokens<'a, Self>,
         out: &mut Formatter,
         extra: &mut Self::Extra,
         level: usize,
-    ) -> fmt::Result {
+    ) -> Result<()> {
+        out.write_header::<Self>()?;
+
         let mut toks: Tokens<Self> = Tokens::new();
 
-        if let Some(imports) = Self::imports(&tokens, extra) {
+        if let Some(imports) = Self::imports(tokens, extra) {
             toks.push(imports);
         }
 
-        if let Some(ref namespace) = extra.namespace {
-            toks.push({
-                let mut t = Tokens::new();
+        let namespace = match extra.namespace {
+            Some(ref namespace) => namespace.clone(),
+            None => return tokens.format_with_preamble(&toks, out, extra, level),
+        };
 
-                t.push(toks!["namespace ", namespace.clone(), " {"]);
-                t.nested_ref(&tokens);
-                t.push("}");
+        let open: Tokens<Self> = toks!["namespace ", namespace, " {"];
+        open.format_with_preamble(&toks, out, extra, level)?;
 
-                t
-            });
-        } else {
-            toks.push_ref(&tokens);
-        }
+        out.indent();
+        out.new_line_unless_empty()?;
+        tokens.format(out, extra, level)?;
+        out.unindent();
+        out.new_line_unless_empty()?;
+        out.write_str("}")?;
+        Ok(())
+    }
 
-        toks.join_line_spacing().format(out, extra, level)
+    fn default_config() -> FormatterConfig {
+        FormatterConfig::default().indentation(Indentation::Space(4))
     }
 }
 
@@ -641,6 +980,48 @@ mod tests {
     use quoted::Quoted;
     use tokens::Tokens;
 
+    #[test]
+    fn test_literals() {
+        let mut toks: Tokens<Csharp> = Tokens::new();
+        toks.push(Value::Array(INT32, vec![1.into(), 2.into()]));
+        toks.push(Value::Map(vec![("a".into(), Value::Long(1))]));
+        toks.push(Value::Map(vec![]));
+        toks.push(Value::List(vec![]));
+        toks.push(Value::Float(f32::NAN));
+
+        assert_eq!(
+            Ok("using System;\nusing System.Collections.Generic;\n\nnew Int32[] { 1, 2 }\nnew Dictionary<object, object> { { \"a\", 1L } }\nnew Dictionary<object, object>()\nnew object[0]\nfloat.NaN\n"),
+            toks.to_file().as_ref().map(|s| s.as_str())
+        );
+    }
+
+    #[test]
+    fn test_file_path() {
+        let mut extra = Extra::default();
+        extra.namespace("Foo.Bar");
+
+        assert_eq!(
+            PathBuf::from("Foo/Bar/Baz.cs"),
+            Csharp::file_path("Baz", &extra)
+        );
+    }
+
+    #[test]
+    fn test_header() {
+        let mut extra = Extra::default();
+        extra.namespace("Foo");
+
+        let toks: Tokens<Csharp> = toks![using("System", "Console")];
+        let config = Csharp::default_config().header("Licensed under MIT.\nDo not edit.");
+
+        assert_eq!(
+            Ok("// Licensed under MIT.\n// Do not edit.\n\nusing System;\n\nnamespace Foo {\n    Console\n}\n"),
+            toks.to_file_with_config(extra, config)
+                .as_ref()
+                .map(|s| s.as_str())
+        );
+    }
+
     #[test]
     fn test_simple() {
         assert!(BOOLEAN.is_simple());
@@ -664,7 +1045,20 @@ mod tests {
         assert_eq!("\"hello \\n world\"", toks.to_string().unwrap().as_str());
     }
 
-    #[ignore]
+    #[test]
+    fn test_using_ambiguous() {
+        let bar_b = using("Foo.Bar", "B");
+        let baz_b = using("Foo.Baz", "B");
+        let baz_c = using("Foo.Baz", "C");
+
+        let toks = toks![bar_b, baz_b, baz_c].join_spacing();
+
+        assert_eq!(
+            Ok("using Foo.Baz;\n\nFoo.Bar.B Foo.Baz.B C\n"),
+            toks.to_file().as_ref().map(|s| s.as_str())
+        );
+    }
+
     #[test]
     fn test_using() {
         let a = using("Foo.Bar", "A");
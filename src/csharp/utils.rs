@@ -1,6 +1,7 @@
 use {Cons, Csharp, Element, IntoTokens, Tokens};
 
-/// Format a block comment, starting with `/**`, and ending in `*/`.
+/// Format a documentation comment, where each line is preceded by `///`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BlockComment<'el>(pub Vec<Cons<'el>>);
 
 impl<'el> IntoTokens<'el, Csharp<'el>> for BlockComment<'el> {
@@ -11,11 +12,7 @@ impl<'el> IntoTokens<'el, Csharp<'el>> for BlockComment<'el> {
             return t;
         }
 
-        for line in self.0 {
-            t.push("/// ");
-            t.append(line);
-        }
-
+        t.push(Element::DocComment(self.0));
         t.push(Element::PushSpacing);
 
         t
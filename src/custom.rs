@@ -1,8 +1,13 @@
 //! Trait used for custom element.
 
-use super::formatter::Formatter;
+use super::comment::CommentStyle;
+use super::error::Result;
+use super::formatter::{Formatter, FormatterConfig};
+use super::naming::Naming;
+use super::syntax::Syntax;
 use super::tokens::Tokens;
-use std::fmt;
+use super::value::{self, Value};
+use std::path::PathBuf;
 
 /// Trait that must be implemented by custom elements.
 pub trait Custom
@@ -10,27 +15,105 @@ where
     Self: Sized,
 {
     /// Extra data associated with building a formatting element.
-    type Extra;
+    type Extra: Clone;
 
     /// Format the custom element.
-    fn format(&self, _out: &mut Formatter, _extra: &mut Self::Extra, _level: usize) -> fmt::Result {
+    fn format(&self, _out: &mut Formatter, _extra: &mut Self::Extra, _level: usize) -> Result<()> {
         Ok(())
     }
 
     /// Performing quoting according to convention set by custom element.
-    fn quote_string(out: &mut Formatter, input: &str) -> fmt::Result {
-        out.write_str(input)
+    fn quote_string(out: &mut Formatter, input: &str) -> Result<()> {
+        out.write_str(input)?;
+        Ok(())
     }
 
     /// Write a file according to convention by custom element.
     fn write_file<'el>(
-        tokens: Tokens<'el, Self>,
+        tokens: &Tokens<'el, Self>,
         out: &mut Formatter,
         extra: &mut Self::Extra,
         level: usize,
-    ) -> fmt::Result {
+    ) -> Result<()> {
+        out.write_header::<Self>()?;
         tokens.format(out, extra, level)
     }
+
+    /// Convert a literal value into tokens, using the literal syntax of the language.
+    fn literal<'a>(value: Value<'a, Self>) -> Tokens<'a, Self>
+    where
+        Self: 'a,
+    {
+        value::literal(value)
+    }
+
+    /// Syntax of expressions and statements in the language.
+    fn syntax() -> Syntax {
+        Syntax::default()
+    }
+
+    /// Naming conventions of the language.
+    fn naming() -> Naming {
+        Naming::default()
+    }
+
+    /// Reserved keywords of the language, which can't be used as identifiers.
+    fn keywords() -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Check if the given character can start an identifier.
+    fn is_identifier_start(c: char) -> bool {
+        c == '_' || c.is_alphabetic()
+    }
+
+    /// Check if the given character can be part of an identifier, after its first character.
+    fn is_identifier_part(c: char) -> bool {
+        c == '_' || c.is_alphanumeric()
+    }
+
+    /// Escape an identifier which is a reserved keyword.
+    ///
+    /// By default, this renames the identifier by adding a `_` suffix.
+    fn escape_keyword(ident: &str) -> String {
+        format!("{}_", ident)
+    }
+
+    /// Prefix of a line comment, used for marker comments like the ones surrounding user regions.
+    fn line_comment() -> &'static str {
+        "//"
+    }
+
+    /// Syntax of a block comment.
+    fn block_comment() -> CommentStyle {
+        CommentStyle::Block {
+            open: "/*",
+            prefix: " *",
+            close: " */",
+        }
+    }
+
+    /// Syntax of a documentation comment.
+    fn doc_comment() -> CommentStyle {
+        CommentStyle::Block {
+            open: "/**",
+            prefix: " *",
+            close: " */",
+        }
+    }
+
+    /// Path of the file declaring the given name, relative to the root of a project.
+    ///
+    /// Languages override this to follow their conventions, like placing Java classes in
+    /// directories matching their package.
+    fn file_path(name: &str, _extra: &Self::Extra) -> PathBuf {
+        PathBuf::from(name)
+    }
+
+    /// Default formatter configuration according to convention by custom element.
+    fn default_config() -> FormatterConfig {
+        FormatterConfig::default()
+    }
 }
 
 /// Dummy implementation for unit.
@@ -9,9 +9,15 @@ pub use self::utils::DocComment;
 use super::cons::Cons;
 use super::custom::Custom;
 use super::formatter::Formatter;
+use super::into_owned::IntoOwned;
 use super::into_tokens::IntoTokens;
 use super::tokens::Tokens;
-use std::fmt::{self, Write};
+use naming::{Case, Naming};
+use std::fmt::Write;
+use std::path::PathBuf;
+use syntax::{Construct, Declaration, Exceptions, ForEach, Lambda, Syntax};
+use value::{self, Value};
+use {CommentStyle, Result};
 
 static SEP: &'static str = ".";
 /// dart:core package.
@@ -28,6 +34,7 @@ pub const BOOL: Dart<'static> = Dart::BuiltIn { name: "bool" };
 
 /// All information about a single type.
 #[derive(Default, Debug, Clone, Hash, PartialOrd, Ord, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Type<'el> {
     /// Path to import.
     path: Option<Cons<'el>>,
@@ -41,6 +48,7 @@ pub struct Type<'el> {
 
 /// Dart token specialization.
 #[derive(Debug, Clone, Hash, PartialOrd, Ord, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum Dart<'el> {
     /// built-in type.
     BuiltIn {
@@ -59,17 +67,14 @@ into_tokens_impl_from!(Dart<'el>, Dart<'el>);
 into_tokens_impl_from!(&'el Dart<'el>, Dart<'el>);
 
 /// Extra data for Dart formatting.
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct Extra {}
 
 impl Extra {}
 
 impl<'el> Dart<'el> {
     /// Resolve all imports.
-    fn imports<'a, 'b: 'a>(
-        input: &'b Tokens<'a, Dart<'el>>,
-        _: &mut Extra,
-    ) -> Tokens<'a, Dart<'el>> {
+    fn imports<'a>(input: &'a Tokens<Dart<'el>>, _: &mut Extra) -> Tokens<'a, Dart<'el>> {
         use quoted::Quoted;
         use std::collections::BTreeSet;
 
@@ -195,10 +200,84 @@ impl<'el> Dart<'el> {
     }
 }
 
+/// Reserved keywords.
+static KEYWORDS: &[&str] = &[
+    "assert", "break", "case", "catch", "class", "const", "continue", "default", "do", "else",
+    "enum", "extends", "false", "final", "finally", "for", "if", "in", "is", "new", "null",
+    "rethrow", "return", "super", "switch", "this", "throw", "true", "try", "var", "void", "while",
+    "with",
+];
+
+impl<'el> IntoOwned for Type<'el> {
+    type Owned = Type<'static>;
+
+    fn into_owned(self) -> Type<'static> {
+        Type {
+            path: self.path.into_owned(),
+            alias: self.alias.into_owned(),
+            name: self.name.into_owned(),
+            arguments: self.arguments.into_owned(),
+        }
+    }
+}
+
+impl<'el> IntoOwned for Dart<'el> {
+    type Owned = Dart<'static>;
+
+    fn into_owned(self) -> Dart<'static> {
+        use self::Dart::*;
+
+        match self {
+            BuiltIn { name } => BuiltIn { name },
+            Void => Void,
+            Dynamic => Dynamic,
+            Type(ty) => Type(ty.into_owned()),
+        }
+    }
+}
+
+/// Built-in types are deserialized into one of the known built-in types.
+#[cfg(feature = "serde")]
+impl<'de, 'el> ::serde::Deserialize<'de> for Dart<'el> {
+    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+    where
+        D: ::serde::Deserializer<'de>,
+    {
+        use serde::de::{Error, Unexpected};
+
+        #[derive(Deserialize)]
+        #[serde(rename = "Dart")]
+        enum Data<'el> {
+            BuiltIn { name: String },
+            Void,
+            Dynamic,
+            Type(Type<'el>),
+        }
+
+        let built_in = [INT, DOUBLE, BOOL];
+
+        Ok(match Data::deserialize(deserializer)? {
+            Data::BuiltIn { name } => built_in
+                .iter()
+                .find(|b| match **b {
+                    Dart::BuiltIn { name: n } => n == name,
+                    _ => false,
+                })
+                .cloned()
+                .ok_or_else(|| {
+                    D::Error::invalid_value(Unexpected::Str(&name), &"a built-in type")
+                })?,
+            Data::Void => Dart::Void,
+            Data::Dynamic => Dart::Dynamic,
+            Data::Type(ty) => Dart::Type(ty),
+        })
+    }
+}
+
 impl<'el> Custom for Dart<'el> {
     type Extra = Extra;
 
-    fn format(&self, out: &mut Formatter, extra: &mut Self::Extra, level: usize) -> fmt::Result {
+    fn format(&self, out: &mut Formatter, extra: &mut Self::Extra, level: usize) -> Result<()> {
         use self::Dart::*;
 
         match *self {
@@ -238,7 +317,7 @@ impl<'el> Custom for Dart<'el> {
         Ok(())
     }
 
-    fn quote_string(out: &mut Formatter, input: &str) -> fmt::Result {
+    fn quote_string(out: &mut Formatter, input: &str) -> Result<()> {
         out.write_char('"')?;
 
         for c in input.chars() {
@@ -260,17 +339,88 @@ impl<'el> Custom for Dart<'el> {
         Ok(())
     }
 
+    fn doc_comment() -> CommentStyle {
+        CommentStyle::Line("///")
+    }
+
+    fn literal<'a>(value: Value<'a, Self>) -> Tokens<'a, Self>
+    where
+        Self: 'a,
+    {
+        match value {
+            Value::Char(value) => value::literal(Value::String(value.to_string().into())),
+            Value::Float(value) if !value.is_finite() => value::non_finite(
+                value.into(),
+                "double.nan",
+                "double.infinity",
+                "double.negativeInfinity",
+            ),
+            Value::Double(value) if !value.is_finite() => value::non_finite(
+                value,
+                "double.nan",
+                "double.infinity",
+                "double.negativeInfinity",
+            ),
+            Value::Tuple(values) => value::tuple(values),
+            value => value::literal(value),
+        }
+    }
+
+    fn syntax() -> Syntax {
+        Syntax {
+            declaration: Declaration::Keyword {
+                immutable: "final",
+                mutable: "var",
+            },
+            for_each: ForEach {
+                keyword: "for",
+                binding: "final ",
+                separator: " in ",
+            },
+            lambda: Lambda::Arrow("=>"),
+            construct: Construct::Call,
+            exceptions: Some(Exceptions::On),
+            ..Syntax::default()
+        }
+    }
+
+    fn naming() -> Naming {
+        Naming {
+            type_name: Case::Pascal,
+            field: Case::Camel,
+            method: Case::Camel,
+            constant: Case::Camel,
+        }
+    }
+
+    fn keywords() -> &'static [&'static str] {
+        KEYWORDS
+    }
+
+    fn is_identifier_start(c: char) -> bool {
+        c == '_' || c == '$' || c.is_alphabetic()
+    }
+
+    fn is_identifier_part(c: char) -> bool {
+        c == '_' || c == '$' || c.is_alphanumeric()
+    }
+
+    fn file_path(name: &str, _extra: &Self::Extra) -> PathBuf {
+        PathBuf::from(format!("{}.dart", name))
+    }
+
     fn write_file<'a>(
-        tokens: Tokens<'a, Self>,
+        tokens: &Tokens<'a, Self>,
         out: &mut Formatter,
         extra: &mut Self::Extra,
         level: usize,
-    ) -> fmt::Result {
+    ) -> Result<()> {
+        out.write_header::<Self>()?;
+
         let mut toks: Tokens<Self> = Tokens::new();
 
-        toks.push_unless_empty(Self::imports(&tokens, extra));
-        toks.push_ref(&tokens);
-        toks.join_line_spacing().format(out, extra, level)
+        toks.push_unless_empty(Self::imports(tokens, extra));
+        tokens.format_with_preamble(&toks, out, extra, level)
     }
 }
 
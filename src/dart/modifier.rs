@@ -5,6 +5,7 @@ use {Custom, Element, IntoTokens, Tokens};
 
 /// A Java modifier.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Modifier {
     /// async
     Async,
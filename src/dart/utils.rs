@@ -1,22 +1,12 @@
-use {Cons, Dart, Element, Tokens};
+use {Cons, Dart, Element};
 
 /// Format a doc comment where each line is preceeded by `///`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DocComment<'el>(pub Vec<Cons<'el>>);
 
 impl<'el> From<DocComment<'el>> for Element<'el, Dart<'el>> {
     fn from(value: DocComment<'el>) -> Element<'el, Dart<'el>> {
-        let mut t = Tokens::new();
-
-        if value.0.is_empty() {
-            return Element::None;
-        }
-
-        for line in value.0 {
-            t.push("/// ");
-            t.append(line);
-        }
-
-        Element::from(t)
+        Element::DocComment(value.0)
     }
 }
 
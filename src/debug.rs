@@ -0,0 +1,204 @@
+//! Helpers to debug the layout of tokens.
+//!
+//! [`Tokens::debug_tree`] shows the structure of a set of tokens, with every element on a line of
+//! its own, indented by depth. [`Tokens::to_visible_string`] renders the tokens with whitespace
+//! made visible.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rstgen::{Java, Tokens};
+//!
+//! let mut toks: Tokens<Java> = Tokens::new();
+//! toks.push("class Foo {");
+//! toks.nested("int a;");
+//! toks.push("}");
+//!
+//! assert_eq!(
+//!     "Push\n  Literal \"class Foo {\"\nNested\n  Literal \"int a;\"\nPush\n  Literal \"}\"\n",
+//!     toks.debug_tree().unwrap()
+//! );
+//!
+//! assert_eq!("class·Foo·{⏎\n··int·a;⏎\n}", toks.to_visible_string().unwrap());
+//! ```
+
+use std::fmt::Write;
+use {Custom, Element, Formatter, Result, Tokens};
+
+/// Make whitespace in the given output visible.
+///
+/// Spaces are shown as `·`, tabs as `→`, carriage returns as `␍`, and the end of every line as
+/// `⏎`.
+pub fn visible(output: &str) -> String {
+    let mut out = String::with_capacity(output.len());
+
+    for c in output.chars() {
+        match c {
+            ' ' => out.push('·'),
+            '\t' => out.push('→'),
+            '\r' => out.push('␍'),
+            '\n' => out.push_str("⏎\n"),
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// Write the tree of the given tokens, indented by the given depth.
+pub(crate) fn tree<C: Custom>(
+    tokens: &Tokens<C>,
+    out: &mut String,
+    extra: &mut C::Extra,
+    depth: usize,
+) -> Result<()> {
+    for element in tokens.elements() {
+        element_tree(element, out, extra, depth)?;
+    }
+
+    Ok(())
+}
+
+/// Write the tree of a single element, indented by the given depth.
+fn element_tree<C: Custom>(
+    element: &Element<C>,
+    out: &mut String,
+    extra: &mut C::Extra,
+    depth: usize,
+) -> Result<()> {
+    use self::Element::*;
+
+    for _ in 0..depth {
+        out.push_str("  ");
+    }
+
+    match *element {
+        Rc(ref element) => {
+            out.push_str("Rc\n");
+            element_tree(element, out, extra, depth + 1)?;
+        }
+        Borrowed(element) => {
+            out.push_str("Borrowed\n");
+            element_tree(element, out, extra, depth + 1)?;
+        }
+        Append(ref tokens) => {
+            out.push_str("Append\n");
+            tree(tokens.as_ref(), out, extra, depth + 1)?;
+        }
+        Push(ref tokens) => {
+            out.push_str("Push\n");
+            tree(tokens.as_ref(), out, extra, depth + 1)?;
+        }
+        Nested(ref tokens) => {
+            out.push_str("Nested\n");
+            tree(tokens.as_ref(), out, extra, depth + 1)?;
+        }
+        Group(ref tokens) => {
+            out.push_str("Group\n");
+            tree(tokens.as_ref(), out, extra, depth + 1)?;
+        }
+        Indent(ref tokens) => {
+            out.push_str("Indent\n");
+            tree(tokens.as_ref(), out, extra, depth + 1)?;
+        }
+        Align(columns, ref tokens) => {
+            writeln!(out, "Align {}", columns)?;
+            tree(tokens.as_ref(), out, extra, depth + 1)?;
+        }
+        Context(ref context, ref tokens) => {
+            writeln!(out, "Context {:?}", context.as_ref())?;
+            tree(tokens.as_ref(), out, extra, depth + 1)?;
+        }
+        Region(ref name, ref tokens) => {
+            writeln!(out, "Region {:?}", name.as_ref())?;
+            tree(tokens.as_ref(), out, extra, depth + 1)?;
+        }
+        Literal(ref literal) => {
+            writeln!(out, "Literal {:?}", literal.as_ref())?;
+        }
+        Quoted(ref literal) => {
+            writeln!(out, "Quoted {:?}", literal.as_ref())?;
+        }
+        Custom(ref custom) => {
+            writeln!(out, "Custom {}", summary(custom.as_ref(), extra)?)?;
+        }
+        Registered(ref custom) => {
+            writeln!(out, "Registered {}", summary(custom.as_ref(), extra)?)?;
+        }
+        LineComment(ref lines) => comment(out, "LineComment", lines)?,
+        BlockComment(ref lines) => comment(out, "BlockComment", lines)?,
+        DocComment(ref lines) => comment(out, "DocComment", lines)?,
+        Slot(ref slot) => {
+            writeln!(out, "Slot {:?}", slot.name())?;
+
+            for entry in slot.entries() {
+                tree(entry, out, extra, depth + 1)?;
+            }
+        }
+        Lazy(_) => out.push_str("Lazy\n"),
+        None => out.push_str("None\n"),
+        PushSpacing => out.push_str("PushSpacing\n"),
+        Line => out.push_str("Line\n"),
+        Spacing => out.push_str("Spacing\n"),
+        LineSpacing => out.push_str("LineSpacing\n"),
+        SoftLine => out.push_str("SoftLine\n"),
+        SoftSpacing => out.push_str("SoftSpacing\n"),
+    }
+
+    Ok(())
+}
+
+/// Summarize a custom element by rendering it on its own.
+fn summary<C: Custom>(custom: &C, extra: &mut C::Extra) -> Result<String> {
+    let mut output = String::new();
+    custom.format(&mut Formatter::new(&mut output), extra, 0)?;
+    Ok(output)
+}
+
+/// Write a comment with the given lines.
+fn comment<S: AsRef<str>>(out: &mut String, kind: &str, lines: &[S]) -> Result<()> {
+    let lines = lines.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+    writeln!(out, "{} {:?}", kind, lines)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::visible;
+    use java::{imported, Java};
+    use {Element, Slot, Tokens};
+
+    #[test]
+    fn test_tree() {
+        let helpers = Slot::new("helpers");
+        helpers.push("int b;").unwrap();
+
+        let mut toks: Tokens<Java> = Tokens::new();
+        toks.push(toks![imported("java.util", "List"), " a;"]);
+        toks.append(Element::LineSpacing);
+        toks.register(imported("java.io", "File"));
+        toks.slot(&helpers);
+
+        assert_eq!(
+            concat!(
+                "Push\n",
+                "  Custom java.util.List\n",
+                "  Literal \" a;\"\n",
+                "LineSpacing\n",
+                "Registered java.io.File\n",
+                "Push\n",
+                "  Slot \"helpers\"\n",
+                "    Literal \"int b;\"\n",
+            ),
+            toks.debug_tree().unwrap()
+        );
+
+        // The slot is not frozen by showing its tree.
+        assert!(!helpers.is_frozen());
+    }
+
+    #[test]
+    fn test_visible() {
+        assert_eq!("a·b⏎\n→c␍⏎\n", visible("a b\n\tc\r\n"));
+    }
+}
@@ -1,18 +1,24 @@
 //! A single element
 
 use super::con_::Con;
-use std::fmt;
-use std::fmt::{Debug, Display, Pointer};
-use {Cons, Custom, Formatter, Tokens};
+use region;
+use slot::SlotRef;
+use {CommentStyle, Cons, Custom, Formatter, Lazy, Result, Tokens};
 
-use std::rc::Rc;
+use shared::Rc;
 
 /// A single element in a set of tokens.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Element<'el, C: 'el> {
     /// A refcounted member.
+    ///
+    /// Build it with [`rstgen::Rc`](::Rc), which is `Arc` with the `sync` feature enabled.
     Rc(Rc<Element<'el, C>>),
     /// A borrowed element.
+    ///
+    /// Serialized like a refcounted element, since borrowed elements can't be deserialized.
+    #[cfg_attr(feature = "serde", serde(rename = "Rc", skip_deserializing))]
     Borrowed(&'el Element<'el, C>),
     /// Append the given set of tokens.
     Append(Con<'el, Tokens<'el, C>>),
@@ -38,11 +44,43 @@ pub enum Element<'el, C: 'el> {
     Spacing,
     /// New line if needed.
     LineSpacing,
+    /// A group of tokens which is laid out flat if it fits on the current line, otherwise all
+    /// soft line breaks directly inside of it are broken.
+    Group(Con<'el, Tokens<'el, C>>),
+    /// Append the given set of tokens, indenting any lines broken inside of it.
+    Indent(Con<'el, Tokens<'el, C>>),
+    /// Append the given set of tokens, aligning any lines broken inside of it with the column it
+    /// starts at, plus the given number of columns.
+    Align(usize, Con<'el, Tokens<'el, C>>),
+    /// Soft line break, which renders as nothing when laid out flat.
+    SoftLine,
+    /// Soft line break, which renders as single-space spacing when laid out flat.
+    SoftSpacing,
+    /// Append the given set of tokens, attaching the given context to any errors raised while
+    /// formatting them.
+    Context(Cons<'el>, Con<'el, Tokens<'el, C>>),
+    /// A named user region surrounded by marker comments, with the given default content.
+    ///
+    /// If the formatter carries over the content of the region, it is used instead.
+    Region(Cons<'el>, Con<'el, Tokens<'el, C>>),
+    /// A line comment, using the syntax of [`Custom::line_comment`].
+    LineComment(Vec<Cons<'el>>),
+    /// A block comment, using the syntax of [`Custom::block_comment`].
+    BlockComment(Vec<Cons<'el>>),
+    /// A documentation comment, using the syntax of [`Custom::doc_comment`].
+    DocComment(Vec<Cons<'el>>),
+    /// A named slot, rendering the tokens it has been filled with.
+    Slot(SlotRef<'el, C>),
+    /// A lazy element, rendering the tokens it produces while being formatted.
+    ///
+    /// Lazy elements can't be serialized, since they are closures.
+    #[cfg_attr(feature = "serde", serde(skip_deserializing))]
+    Lazy(Lazy<'el, C>),
 }
 
 impl<'el, C: Custom> Element<'el, C> {
     /// Format the given element.
-    pub fn format(&self, out: &mut Formatter, extra: &mut C::Extra, level: usize) -> fmt::Result {
+    pub fn format(&self, out: &mut Formatter, extra: &mut C::Extra, level: usize) -> Result<()> {
         use self::Element::*;
 
         match *self {
@@ -61,7 +99,10 @@ impl<'el, C: Custom> Element<'el, C> {
                 out.indent();
                 out.new_line_unless_empty()?;
 
+                // nested tokens are always followed by a line break.
+                let previous = out.set_trailing(0);
                 tokens.as_ref().format(out, extra, level)?;
+                out.set_trailing(previous);
 
                 out.unindent();
                 out.new_line_unless_empty()?;
@@ -93,12 +134,113 @@ impl<'el, C: Custom> Element<'el, C> {
             Spacing => {
                 out.write_str(" ")?;
             }
+            // layout below
+            Group(ref tokens) => {
+                // probe against a copy of the extra data, so that formatting only affects it once.
+                let flat = out.is_flat()
+                    || out
+                        .fits(|probe| tokens.as_ref().format(probe, &mut extra.clone(), level))?;
+                let previous = out.set_flat(flat);
+                tokens.as_ref().format(out, extra, level)?;
+                out.set_flat(previous);
+            }
+            Indent(ref tokens) => {
+                out.indent();
+                tokens.as_ref().format(out, extra, level)?;
+                out.unindent();
+            }
+            Align(columns, ref tokens) => {
+                out.align(columns);
+                tokens.as_ref().format(out, extra, level)?;
+                out.unalign();
+            }
+            SoftLine => {
+                if !out.is_flat() {
+                    out.new_line()?;
+                }
+            }
+            SoftSpacing => {
+                if out.is_flat() {
+                    out.write_str(" ")?;
+                } else {
+                    out.new_line()?;
+                }
+            }
+            Context(ref context, ref tokens) => {
+                tokens
+                    .as_ref()
+                    .format(out, extra, level)
+                    .map_err(|e| e.with_context(context.as_ref()))?;
+            }
+            Region(ref name, ref tokens) => {
+                let comment = C::line_comment();
+
+                out.new_line_unless_empty()?;
+                out.write_str(&region::begin_marker(comment, name))?;
+                out.new_line()?;
+
+                if let Some(lines) = out.regions_mut().take(name) {
+                    for line in lines {
+                        out.write_str(&line)?;
+                        out.new_line()?;
+                    }
+                } else {
+                    let previous = out.set_trailing(0);
+                    tokens.as_ref().format(out, extra, level)?;
+                    out.set_trailing(previous);
+                    out.new_line_unless_empty()?;
+                }
+
+                out.write_str(&region::end_marker(comment, name))?;
+            }
+            // comments below
+            LineComment(ref lines) => {
+                CommentStyle::Line(C::line_comment()).format(out, lines)?;
+            }
+            BlockComment(ref lines) => {
+                C::block_comment().format(out, lines)?;
+            }
+            DocComment(ref lines) => {
+                C::doc_comment().format(out, lines)?;
+            }
+            Slot(ref slot) => {
+                slot.format(out, extra, level)?;
+            }
+            Lazy(ref lazy) => {
+                lazy.format(out, extra, level)?;
+            }
         }
 
         Ok(())
     }
 }
 
+impl<'el, C> Element<'el, C> {
+    /// Check if the element contains a group, which needs to know what follows it to be laid
+    /// out.
+    pub(crate) fn has_group(&self) -> bool {
+        use self::Element::*;
+
+        match *self {
+            Group(_) | Lazy(_) => true,
+            Rc(ref element) => element.has_group(),
+            Borrowed(element) => element.has_group(),
+            Append(ref tokens)
+            | Push(ref tokens)
+            | Nested(ref tokens)
+            | Indent(ref tokens)
+            | Align(_, ref tokens)
+            | Context(_, ref tokens)
+            | Region(_, ref tokens) => tokens.as_ref().elements().iter().any(Element::has_group),
+            Slot(ref slot) => slot
+                .entries()
+                .iter()
+                .any(|entry| entry.elements().iter().any(Element::has_group)),
+            _ => false,
+        }
+    }
+}
+
 impl<'el, C: Custom> From<C> for Element<'el, C> {
     fn from(value: C) -> Self {
         Element::Custom(Con::Owned(value))
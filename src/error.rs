@@ -0,0 +1,214 @@
+//! Errors raised while generating code.
+
+use std::error;
+use std::fmt;
+use std::io;
+use std::result;
+
+/// Result type used when generating code.
+pub type Result<T> = result::Result<T, Error>;
+
+/// The kind of an error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Failed to write to the output.
+    Write,
+    /// An I/O operation failed, like creating a directory or writing a file.
+    Io(io::ErrorKind, String),
+    /// An identifier which is not valid in the target language.
+    InvalidIdentifier(String),
+    /// An import which could not be resolved.
+    UnresolvedImport(String),
+    /// A path which is not relative to the root of a project.
+    InvalidPath(String),
+    /// A construct which is not supported by the target language.
+    Unsupported(String),
+    /// A slot which was filled after it was rendered.
+    SlotFrozen(String),
+    /// An invalid template, or a template referencing a name which is not bound, at the given
+    /// line.
+    Template(usize, String),
+    /// Rendered output which does not match the snapshot with the given name, with a diff from
+    /// the snapshot to the output.
+    Snapshot(String, String),
+    /// A snapshot with the given name which does not exist, and is not being updated.
+    MissingSnapshot(String),
+    /// A custom error, raised by a language specialization.
+    Custom(String),
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        use self::ErrorKind::*;
+
+        match *self {
+            Write => fmt.write_str("failed to write output"),
+            Io(_, ref message) => fmt.write_str(message),
+            InvalidIdentifier(ref ident) => write!(fmt, "invalid identifier `{}`", ident),
+            UnresolvedImport(ref import) => write!(fmt, "unresolved import `{}`", import),
+            InvalidPath(ref path) => write!(fmt, "invalid path `{}`", path),
+            Unsupported(ref what) => write!(fmt, "{} are not supported by the language", what),
+            SlotFrozen(ref name) => write!(fmt, "slot `{}` was filled after it was rendered", name),
+            Template(line, ref message) => write!(fmt, "template line {}: {}", line, message),
+            Snapshot(ref name, ref diff) => {
+                write!(fmt, "output does not match snapshot `{}`:\n{}", name, diff)
+            }
+            MissingSnapshot(ref name) => write!(
+                fmt,
+                "snapshot `{}` does not exist, set `RSTGEN_UPDATE_SNAPSHOTS=1` to write it",
+                name
+            ),
+            Custom(ref message) => fmt.write_str(message),
+        }
+    }
+}
+
+/// Error raised while generating code.
+///
+/// Errors carry a path of context, like the file, class, and method being generated when the
+/// error was raised.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    /// The kind of the error.
+    kind: ErrorKind,
+    /// Context of the error, from the outermost to the innermost.
+    context: Vec<String>,
+}
+
+impl Error {
+    /// Construct a new error of the given kind.
+    pub fn new(kind: ErrorKind) -> Error {
+        Error {
+            kind,
+            context: Vec::new(),
+        }
+    }
+
+    /// Construct an error for an invalid identifier.
+    pub fn invalid_identifier<I: Into<String>>(ident: I) -> Error {
+        Error::new(ErrorKind::InvalidIdentifier(ident.into()))
+    }
+
+    /// Construct an error for an import which could not be resolved.
+    pub fn unresolved_import<I: Into<String>>(import: I) -> Error {
+        Error::new(ErrorKind::UnresolvedImport(import.into()))
+    }
+
+    /// Construct a new invalid path error.
+    pub fn invalid_path<P: Into<String>>(path: P) -> Error {
+        Error::new(ErrorKind::InvalidPath(path.into()))
+    }
+
+    /// Construct an error for a construct which is not supported by the target language.
+    pub fn unsupported<W: Into<String>>(what: W) -> Error {
+        Error::new(ErrorKind::Unsupported(what.into()))
+    }
+
+    /// Construct an error for a slot which was filled after it was rendered.
+    pub fn slot_frozen<N: Into<String>>(name: N) -> Error {
+        Error::new(ErrorKind::SlotFrozen(name.into()))
+    }
+
+    /// Construct an error for a template, at the given line.
+    pub fn template<M: Into<String>>(line: usize, message: M) -> Error {
+        Error::new(ErrorKind::Template(line, message.into()))
+    }
+
+    /// Construct an error for output which does not match a snapshot.
+    pub fn snapshot<N: Into<String>, D: Into<String>>(name: N, diff: D) -> Error {
+        Error::new(ErrorKind::Snapshot(name.into(), diff.into()))
+    }
+
+    /// Construct an error for a snapshot which does not exist.
+    pub fn missing_snapshot<N: Into<String>>(name: N) -> Error {
+        Error::new(ErrorKind::MissingSnapshot(name.into()))
+    }
+
+    /// Construct a custom error with the given message.
+    pub fn custom<M: Into<String>>(message: M) -> Error {
+        Error::new(ErrorKind::Custom(message.into()))
+    }
+
+    /// Add an outer context to the error.
+    pub fn with_context<C: Into<String>>(mut self, context: C) -> Error {
+        self.context.insert(0, context.into());
+        self
+    }
+
+    /// Get the kind of the error.
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// Get the context of the error, from the outermost to the innermost.
+    pub fn context(&self) -> &[String] {
+        &self.context
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        for context in &self.context {
+            write!(fmt, "{}: ", context)?;
+        }
+
+        self.kind.fmt(fmt)
+    }
+}
+
+impl error::Error for Error {}
+
+impl From<fmt::Error> for Error {
+    fn from(_: fmt::Error) -> Self {
+        Error::new(ErrorKind::Write)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::new(ErrorKind::Io(error.kind(), error.to_string()))
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Error::new(kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, ErrorKind};
+    use {Custom, Formatter, Result, Tokens};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct Failing;
+
+    impl Custom for Failing {
+        type Extra = ();
+
+        fn format(&self, _: &mut Formatter, _: &mut (), _: usize) -> Result<()> {
+            Err(Error::custom("not supported"))
+        }
+    }
+
+    #[test]
+    fn test_context() {
+        let mut method: Tokens<Failing> = Tokens::new();
+        method.append(Failing);
+
+        let mut class = Tokens::new();
+        class.nested(method.with_context("method foo"));
+
+        let mut file = Tokens::new();
+        file.push(class.with_context("class Foo"));
+
+        let error = file.to_file().unwrap_err();
+
+        assert_eq!(&ErrorKind::Custom("not supported".into()), error.kind());
+        assert_eq!(
+            "class Foo: method foo: not supported",
+            error.to_string().as_str()
+        );
+    }
+}
@@ -1,6 +1,9 @@
+use error::Result;
+use region::Regions;
+use source_map::{Origin, Recorder, SourceMap};
 use std::fmt;
 use std::io;
-use std::iter;
+use {CommentStyle, Custom};
 
 /// Facade for writing formatted strings to io::Write types.
 pub struct IoFmt<'write, W: 'write>(pub &'write mut W);
@@ -14,43 +17,298 @@ where
     }
 }
 
+/// Unit of indentation used for each nested level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Indentation {
+    /// Indent with the given number of spaces per level.
+    Space(usize),
+    /// Indent with a single tab per level.
+    Tab,
+}
+
+impl Indentation {
+    /// Number of columns occupied by a single level of indentation.
+    ///
+    /// Tabs are considered to be four columns wide.
+    pub fn width(&self) -> usize {
+        match *self {
+            Indentation::Space(width) => width,
+            Indentation::Tab => 4,
+        }
+    }
+}
+
+/// Line ending written for every new line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineEnding {
+    /// Unix line endings (`\n`).
+    Lf,
+    /// Windows line endings (`\r\n`).
+    CrLf,
+}
+
+impl LineEnding {
+    /// Get the line ending as a string.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+/// Configuration for how a formatter lays out its output.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FormatterConfig {
+    /// Indentation used for each nested level.
+    pub indentation: Indentation,
+    /// Line ending to use.
+    pub line_ending: LineEnding,
+    /// If files should be terminated with a line ending.
+    pub trailing_newline: bool,
+    /// Maximum width of a line that groups are laid out to fit within.
+    pub max_width: usize,
+    /// Header written at the top of every file, like a license or a "do not edit" banner.
+    ///
+    /// The header is rendered using the line comment syntax of the language.
+    pub header: Option<String>,
+}
+
+impl FormatterConfig {
+    /// Change the indentation.
+    pub fn indentation(self, indentation: Indentation) -> FormatterConfig {
+        FormatterConfig {
+            indentation,
+            ..self
+        }
+    }
+
+    /// Change the line ending.
+    pub fn line_ending(self, line_ending: LineEnding) -> FormatterConfig {
+        FormatterConfig {
+            line_ending,
+            ..self
+        }
+    }
+
+    /// Change if files should be terminated with a line ending.
+    pub fn trailing_newline(self, trailing_newline: bool) -> FormatterConfig {
+        FormatterConfig {
+            trailing_newline,
+            ..self
+        }
+    }
+
+    /// Change the maximum width of a line.
+    pub fn max_width(self, max_width: usize) -> FormatterConfig {
+        FormatterConfig { max_width, ..self }
+    }
+
+    /// Change the header written at the top of every file.
+    pub fn header<H: Into<String>>(self, header: H) -> FormatterConfig {
+        FormatterConfig {
+            header: Some(header.into()),
+            ..self
+        }
+    }
+}
+
+impl Default for FormatterConfig {
+    fn default() -> Self {
+        FormatterConfig {
+            indentation: Indentation::Space(2),
+            line_ending: LineEnding::Lf,
+            trailing_newline: true,
+            max_width: 100,
+            header: None,
+        }
+    }
+}
+
 /// Formatter implementation for write types.
 pub struct Formatter<'write> {
-    write: &'write mut fmt::Write,
+    write: &'write mut dyn fmt::Write,
     /// if last line was empty.
     current_line_empty: bool,
     /// Current indentation level.
     indent: usize,
-    /// Holds the current indentation level as a string.
-    buffer: String,
+    /// Indentation level and extra columns of every alignment, innermost last.
+    aligns: Vec<(usize, usize)>,
+    /// Current line being written.
+    line: usize,
+    /// Current column on the line being written.
+    column: usize,
+    /// If soft line breaks should be laid out flat.
+    flat: bool,
+    /// Holds a single level of indentation as a string.
+    unit: String,
+    /// Configuration of the formatter.
+    config: FormatterConfig,
+    /// Content of user regions carried over from a previously generated file.
+    regions: Regions,
+    /// Source map being recorded, if any.
+    source_map: Option<Recorder>,
+    /// Width of the output following the tokens being formatted, up to the next line break.
+    trailing: usize,
+    /// If this formatter stops at the first line break, while measuring output.
+    measuring: bool,
 }
 
 impl<'write> Formatter<'write> {
     /// Create a new write formatter.
-    pub fn new(write: &mut fmt::Write) -> Formatter {
+    pub fn new(write: &mut dyn fmt::Write) -> Formatter<'_> {
+        Formatter::with_config(write, FormatterConfig::default())
+    }
+
+    /// Create a new write formatter with the given configuration.
+    pub fn with_config(write: &mut dyn fmt::Write, config: FormatterConfig) -> Formatter<'_> {
+        let unit = match config.indentation {
+            Indentation::Space(width) => " ".repeat(width),
+            Indentation::Tab => String::from("\t"),
+        };
+
         Formatter {
-            write: write,
+            write,
             current_line_empty: true,
             indent: 0usize,
-            buffer: String::from("  "),
+            aligns: Vec::new(),
+            line: 0usize,
+            column: 0usize,
+            flat: false,
+            unit,
+            config,
+            regions: Regions::new(),
+            source_map: None,
+            trailing: 0,
+            measuring: false,
+        }
+    }
+
+    /// Access the configuration of the formatter.
+    pub fn config(&self) -> &FormatterConfig {
+        &self.config
+    }
+
+    /// Write the configured file header as line comments of the given language, followed by an
+    /// empty line.
+    ///
+    /// Does nothing if no header is configured.
+    pub fn write_header<C: Custom>(&mut self) -> Result<()> {
+        if let Some(header) = self.config.header.clone() {
+            CommentStyle::Line(C::line_comment()).format(self, &[header.into()])?;
+            self.new_line()?;
+            self.new_line()?;
+        }
+
+        Ok(())
+    }
+
+    /// Access the user regions of the formatter.
+    pub fn regions(&self) -> &Regions {
+        &self.regions
+    }
+
+    /// Mutably access the user regions of the formatter.
+    pub fn regions_mut(&mut self) -> &mut Regions {
+        &mut self.regions
+    }
+
+    /// Start recording a source map of the origins of the tokens being written.
+    pub fn record_source_map(&mut self) {
+        self.source_map = Some(Recorder::default());
+    }
+
+    /// Take the recorded source map, if recording was started.
+    pub fn take_source_map(&mut self) -> Option<SourceMap> {
+        self.source_map.take().map(Recorder::finish)
+    }
+
+    /// Enter tokens with the given origin, see [`source_map`](::source_map).
+    pub(crate) fn enter_origin(&mut self, origin: &Origin) {
+        if let Some(ref mut source_map) = self.source_map {
+            source_map.enter(origin);
+        }
+    }
+
+    /// Exit the innermost origin.
+    pub(crate) fn exit_origin(&mut self) {
+        if let Some(ref mut source_map) = self.source_map {
+            source_map.exit();
         }
     }
 
     fn check_indent(&mut self) -> fmt::Result {
-        if self.current_line_empty && self.indent > 0 {
-            self.write.write_str(&self.buffer[0..self.indent * 2])?;
+        if self.current_line_empty && (self.indent > 0 || !self.aligns.is_empty()) {
+            let mut prefix = String::new();
+            let mut level = 0;
+
+            for &(at, columns) in &self.aligns {
+                while level < at.min(self.indent) {
+                    prefix.push_str(&self.unit);
+                    level += 1;
+                }
+
+                for _ in 0..columns {
+                    prefix.push(' ');
+                }
+            }
+
+            for _ in level..self.indent {
+                prefix.push_str(&self.unit);
+            }
+
+            self.write_raw(&prefix)?;
+
+            self.column = self.prefix_width();
             self.current_line_empty = false;
         }
 
         Ok(())
     }
 
+    /// Width of the indentation and alignment written at the start of every line.
+    fn prefix_width(&self) -> usize {
+        let aligned = self
+            .aligns
+            .iter()
+            .map(|&(_, columns)| columns)
+            .sum::<usize>();
+        self.indent * self.config.indentation.width() + aligned
+    }
+
+    /// Write the given string to the underlying writer, as is.
+    fn write_raw(&mut self, s: &str) -> fmt::Result {
+        if let Some(ref mut source_map) = self.source_map {
+            source_map.write(s);
+        }
+
+        self.write.write_str(s)
+    }
+
     /// Write the given string.
     pub fn write_str(&mut self, s: &str) -> fmt::Result {
-        if s.len() > 0 {
+        if self.measuring {
+            if let Some(n) = s.find('\n') {
+                self.write_str(&s[..n])?;
+                return Err(fmt::Error);
+            }
+        }
+
+        if !s.is_empty() {
             self.check_indent()?;
-            self.write.write_str(s)?;
+
+            if let Some(ref mut source_map) = self.source_map {
+                source_map.record(self.line);
+            }
+
+            self.write_raw(s)?;
             self.current_line_empty = false;
+            self.line += s.matches('\n').count();
+
+            match s.rfind('\n') {
+                Some(n) => self.column = s[n + 1..].chars().count(),
+                None => self.column += s.chars().count(),
+            }
         }
 
         Ok(())
@@ -58,8 +316,14 @@ impl<'write> Formatter<'write> {
 
     /// Push a new line.
     pub fn new_line(&mut self) -> fmt::Result {
-        self.write.write_char('\n')?;
+        if self.measuring {
+            return Err(fmt::Error);
+        }
+
+        self.write_raw(self.config.line_ending.as_str())?;
         self.current_line_empty = true;
+        self.line += 1;
+        self.column = 0;
         Ok(())
     }
 
@@ -75,30 +339,166 @@ impl<'write> Formatter<'write> {
     /// Increase indentation level.
     pub fn indent(&mut self) {
         self.indent += 1;
-
-        // check that buffer contains the current indentation.
-        if self.buffer.len() < self.indent * 2 {
-            // double the buffer
-            for c in iter::repeat(' ').take(self.buffer.len()) {
-                self.buffer.push(c);
-            }
-        }
     }
 
     /// Decrease indentation level.
     pub fn unindent(&mut self) {
         self.indent = self.indent.saturating_sub(1);
     }
+
+    /// Align lines broken from now on with the current column, plus the given number of columns.
+    pub fn align(&mut self, columns: usize) {
+        let prefix = self.prefix_width();
+
+        let start = if self.current_line_empty {
+            prefix
+        } else {
+            self.column
+        };
+
+        self.aligns
+            .push((self.indent, start.saturating_sub(prefix) + columns));
+    }
+
+    /// Remove the innermost alignment.
+    pub fn unalign(&mut self) {
+        self.aligns.pop();
+    }
+
+    /// Current line being written, starting at zero.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Current column of the line being written.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Check if soft line breaks are currently laid out flat.
+    pub fn is_flat(&self) -> bool {
+        self.flat
+    }
+
+    /// Change if soft line breaks should be laid out flat, returning the previous setting.
+    pub fn set_flat(&mut self, flat: bool) -> bool {
+        ::std::mem::replace(&mut self.flat, flat)
+    }
+
+    /// Change the width of the output following the tokens being formatted, up to the next line
+    /// break, returning the previous width.
+    pub(crate) fn set_trailing(&mut self, trailing: usize) -> usize {
+        ::std::mem::replace(&mut self.trailing, trailing)
+    }
+
+    /// Width of the output following the tokens being formatted, up to the next line break.
+    pub(crate) fn trailing(&self) -> usize {
+        self.trailing
+    }
+
+    /// Measure the width of the output of the given function up to its first line break, and
+    /// check if it has one.
+    ///
+    /// Like with [`fits`](Formatter::fits), the output is discarded. Errors are taken to be line
+    /// breaks, since they are raised again once the output is formatted.
+    pub(crate) fn measure<F>(&self, format: F) -> (usize, bool)
+    where
+        F: FnOnce(&mut Formatter) -> Result<()>,
+    {
+        let mut buffer = String::new();
+
+        let broken = {
+            let mut probe = Formatter::with_config(&mut buffer, self.config.clone());
+            probe.flat = self.flat;
+            probe.measuring = true;
+            probe.current_line_empty = false;
+            format(&mut probe).is_err()
+        };
+
+        (buffer.chars().count(), broken)
+    }
+
+    /// Check if the output of the given function fits on the current line when laid out flat,
+    /// together with the output following it up to the next line break.
+    ///
+    /// The function is called with a probing formatter, the output of which is discarded. Any other
+    /// state the function modifies should be a copy, since the output is formatted again once the
+    /// layout is decided.
+    pub fn fits<F>(&self, format: F) -> Result<bool>
+    where
+        F: FnOnce(&mut Formatter) -> Result<()>,
+    {
+        let mut buffer = String::new();
+
+        {
+            let mut probe = Formatter::with_config(&mut buffer, self.config.clone());
+            probe.flat = true;
+            // probe as if in the middle of a line, so that hard line breaks are always visible.
+            probe.current_line_empty = false;
+            format(&mut probe)?;
+        }
+
+        if buffer.contains('\n') {
+            return Ok(false);
+        }
+
+        let start = if self.current_line_empty {
+            self.prefix_width()
+        } else {
+            self.column
+        };
+
+        Ok(start + buffer.chars().count() + self.trailing <= self.config.max_width)
+    }
 }
 
 impl<'write> fmt::Write for Formatter<'write> {
     fn write_str(&mut self, s: &str) -> fmt::Result {
-        if s.len() > 0 {
-            self.check_indent()?;
-            self.write.write_str(s)?;
-            self.current_line_empty = false;
-        }
+        Formatter::write_str(self, s)
+    }
+}
 
-        Ok(())
+#[cfg(test)]
+mod tests {
+    use super::{FormatterConfig, Indentation, LineEnding};
+    use {Tokens, WriteTokens};
+
+    fn tokens() -> Tokens<'static, ()> {
+        let mut toks = Tokens::new();
+        toks.push("fn foo() {");
+        toks.nested("bar();");
+        toks.push("}");
+        toks
+    }
+
+    #[test]
+    fn test_tabs() {
+        let config = FormatterConfig::default().indentation(Indentation::Tab);
+        let out = tokens().to_string_with_config((), config);
+        assert_eq!(
+            Ok("fn foo() {\n\tbar();\n}"),
+            out.as_ref().map(|s| s.as_str())
+        );
+    }
+
+    #[test]
+    fn test_crlf() {
+        let config = FormatterConfig::default()
+            .indentation(Indentation::Space(4))
+            .line_ending(LineEnding::CrLf);
+        let out = tokens().to_file_with_config((), config);
+        assert_eq!(
+            Ok("fn foo() {\r\n    bar();\r\n}\r\n"),
+            out.as_ref().map(|s| s.as_str())
+        );
+    }
+
+    #[test]
+    fn test_no_trailing_newline() {
+        let config = FormatterConfig::default().trailing_newline(false);
+        let mut out = String::new();
+        out.write_file_with_config(&tokens(), &mut (), config)
+            .unwrap();
+        assert_eq!("fn foo() {\n  bar();\n}", out);
     }
 }
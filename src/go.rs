@@ -1,13 +1,22 @@
 //! Specialization for Go code generation.
 
+use imports::{Conflict, Imports, Resolved};
+use naming::{Case, Naming};
 use std::collections::BTreeSet;
-use std::fmt::{self, Write};
-use {Cons, Custom, Formatter, Quoted, Tokens};
+use std::fmt::Write;
+use std::path::PathBuf;
+use syntax::{Construct, Declaration, ForEach, Lambda, Syntax};
+use value::{self, Value};
+use {
+    CommentStyle, Cons, Custom, Formatter, FormatterConfig, Indentation, IntoOwned, Quoted, Result,
+    Tokens,
+};
 
 const SEP: &str = ".";
 
 /// Name of an imported type.
 #[derive(Debug, Clone, Hash, PartialOrd, Ord, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Name<'el> {
     /// Module of the imported name.
     module: Option<Cons<'el>>,
@@ -17,6 +26,7 @@ pub struct Name<'el> {
 
 /// Go token specialization.
 #[derive(Debug, Clone, Hash, PartialOrd, Ord, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Go<'el> {
     /// A regular type.
     Type {
@@ -62,7 +72,7 @@ impl<'el> Go<'el> {
         };
     }
 
-    fn imports<'a>(tokens: &'a Tokens<'a, Self>) -> Option<Tokens<'a, Self>> {
+    fn imports<'a>(tokens: &'a Tokens<Self>, extra: &mut Extra) -> Option<Tokens<'a, Self>> {
         let mut modules = BTreeSet::new();
 
         for custom in tokens.walk_custom() {
@@ -79,6 +89,12 @@ impl<'el> Go<'el> {
             let mut s = Tokens::new();
 
             s.append("import ");
+
+            if let Resolved::Alias(alias) = extra.imports.bind(module, package_name(module)) {
+                s.append(alias.to_string());
+                s.append(" ");
+            }
+
             s.append(module.quoted());
 
             out.push(s);
@@ -88,10 +104,27 @@ impl<'el> Go<'el> {
     }
 }
 
+/// Tokens for a floating point number which is not finite, using the `math` package.
+fn non_finite<'a, 'el: 'a>(value: f64) -> Tokens<'a, Go<'el>> {
+    value::non_finite(
+        value,
+        toks![imported("math", "NaN"), "()"],
+        toks![imported("math", "Inf"), "(1)"],
+        toks![imported("math", "Inf"), "(-1)"],
+    )
+}
+
+/// Name of the package imported from the given module, which is the last component of its path.
+fn package_name(module: &str) -> &str {
+    module.rsplit('/').next().unwrap_or(module)
+}
+
 /// Extra data for Go.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Extra {
     package: String,
+    /// Packages which have been bound in the file.
+    imports: Imports,
 }
 
 impl Extra {
@@ -99,6 +132,80 @@ impl Extra {
     pub fn from_package<S: AsRef<str>>(package: S) -> Self {
         Self {
             package: package.as_ref().to_string(),
+            imports: Imports::new(Conflict::Alias),
+        }
+    }
+
+    /// Package of the file.
+    pub fn package(&self) -> &str {
+        &self.package
+    }
+
+    /// Get the module the given package name or alias refers to, if it has been bound by an
+    /// import.
+    pub fn imported(&self, name: &str) -> Option<&str> {
+        self.imports.module(name)
+    }
+}
+
+/// Reserved keywords.
+static KEYWORDS: &[&str] = &[
+    "break",
+    "case",
+    "chan",
+    "const",
+    "continue",
+    "default",
+    "defer",
+    "else",
+    "fallthrough",
+    "for",
+    "func",
+    "go",
+    "goto",
+    "if",
+    "import",
+    "interface",
+    "map",
+    "package",
+    "range",
+    "return",
+    "select",
+    "struct",
+    "switch",
+    "type",
+    "var",
+];
+
+impl<'el> IntoOwned for Name<'el> {
+    type Owned = Name<'static>;
+
+    fn into_owned(self) -> Name<'static> {
+        Name {
+            module: self.module.into_owned(),
+            name: self.name.into_owned(),
+        }
+    }
+}
+
+impl<'el> IntoOwned for Go<'el> {
+    type Owned = Go<'static>;
+
+    fn into_owned(self) -> Go<'static> {
+        use self::Go::*;
+
+        match self {
+            Type { name } => Type {
+                name: name.into_owned(),
+            },
+            Map { key, value } => Map {
+                key: key.into_owned(),
+                value: value.into_owned(),
+            },
+            Array { inner } => Array {
+                inner: inner.into_owned(),
+            },
+            Interface => Interface,
         }
     }
 }
@@ -106,7 +213,7 @@ impl Extra {
 impl<'el> Custom for Go<'el> {
     type Extra = Extra;
 
-    fn format(&self, out: &mut Formatter, extra: &mut Self::Extra, level: usize) -> fmt::Result {
+    fn format(&self, out: &mut Formatter, extra: &mut Self::Extra, level: usize) -> Result<()> {
         use self::Go::*;
 
         match *self {
@@ -119,8 +226,14 @@ impl<'el> Custom for Go<'el> {
                     },
                 ..
             } => {
-                if let Some(module) = module.as_ref().and_then(|m| m.as_ref().split("/").last()) {
-                    out.write_str(module)?;
+                if let Some(module) = module.as_ref() {
+                    let package = package_name(module);
+
+                    match extra.imports.resolve(module, package) {
+                        Some(Resolved::Alias(alias)) => out.write_str(alias)?,
+                        _ => out.write_str(package)?,
+                    }
+
                     out.write_str(SEP)?;
                 }
 
@@ -147,7 +260,7 @@ impl<'el> Custom for Go<'el> {
         Ok(())
     }
 
-    fn quote_string(out: &mut Formatter, input: &str) -> fmt::Result {
+    fn quote_string(out: &mut Formatter, input: &str) -> Result<()> {
         out.write_char('"')?;
 
         for c in input.chars() {
@@ -166,25 +279,91 @@ impl<'el> Custom for Go<'el> {
         Ok(())
     }
 
+    fn doc_comment() -> CommentStyle {
+        CommentStyle::Line("//")
+    }
+
+    fn literal<'a>(value: Value<'a, Self>) -> Tokens<'a, Self>
+    where
+        Self: 'a,
+    {
+        match value {
+            Value::Null => value::raw("nil"),
+            Value::Float(value) if !value.is_finite() => {
+                toks!["float32(", non_finite(value.into()), ")"]
+            }
+            Value::Double(value) if !value.is_finite() => non_finite(value),
+            Value::Array(ty, values) => toks!["[]", ty, value::sequence("{", values, "}")],
+            Value::List(values) | Value::Tuple(values) => {
+                value::sequence("[]interface{}{", values, "}")
+            }
+            Value::Map(entries) => value::map("map[interface{}]interface{}{", entries, ": ", "}"),
+            value => value::literal(value),
+        }
+    }
+
+    fn syntax() -> Syntax {
+        Syntax {
+            semicolons: false,
+            parenthesized: false,
+            declaration: Declaration::Operator(":="),
+            while_loop: "for",
+            for_each: ForEach {
+                keyword: "for",
+                binding: "_, ",
+                separator: " := range ",
+            },
+            lambda: Lambda::Function,
+            construct: Construct::Composite,
+            exceptions: None,
+            ..Syntax::default()
+        }
+    }
+
+    fn naming() -> Naming {
+        Naming {
+            type_name: Case::Pascal,
+            field: Case::Pascal,
+            method: Case::Pascal,
+            constant: Case::Pascal,
+        }
+    }
+
+    fn keywords() -> &'static [&'static str] {
+        KEYWORDS
+    }
+
+    fn file_path(name: &str, extra: &Self::Extra) -> PathBuf {
+        let mut path = PathBuf::new();
+        path.extend(extra.package.split('/').filter(|p| !p.is_empty()));
+        path.push(format!("{}.go", name));
+        path
+    }
+
     fn write_file<'a>(
-        tokens: Tokens<'a, Self>,
+        tokens: &Tokens<'a, Self>,
         out: &mut Formatter,
         extra: &mut Self::Extra,
         level: usize,
-    ) -> fmt::Result {
+    ) -> Result<()> {
+        out.write_header::<Self>()?;
+
         let mut toks: Tokens<Self> = Tokens::new();
 
         toks.push_into(|t| {
             t.append("package ");
-            t.append(extra.package.to_string());
+            t.append(package_name(&extra.package).to_string());
         });
 
-        if let Some(imports) = Self::imports(&tokens) {
+        if let Some(imports) = Self::imports(tokens, extra) {
             toks.push(imports);
         }
 
-        toks.push_ref(&tokens);
-        toks.join_line_spacing().format(out, extra, level)
+        tokens.format_with_preamble(&toks, out, extra, level)
+    }
+
+    fn default_config() -> FormatterConfig {
+        FormatterConfig::default().indentation(Indentation::Tab)
     }
 }
 
@@ -245,7 +424,24 @@ pub fn interface<'a>() -> Go<'a> {
 #[cfg(test)]
 mod tests {
     use super::{array, imported, interface, map, Extra, Go};
-    use {Quoted, Tokens};
+    use std::path::PathBuf;
+    use {Custom, Quoted, Tokens, Value};
+
+    #[test]
+    fn test_file_path() {
+        assert_eq!(
+            PathBuf::from("foo/bar/baz.go"),
+            Go::file_path("baz", &Extra::from_package("foo/bar"))
+        );
+
+        let toks: Tokens<Go> = toks!("func baz() {}");
+        assert_eq!(
+            Ok("package bar\n\nfunc baz() {}\n"),
+            toks.to_file_with(Extra::from_package("foo/bar"))
+                .as_ref()
+                .map(|s| s.as_str())
+        );
+    }
 
     #[test]
     fn test_string() {
@@ -270,6 +466,54 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_conflicting_imports() {
+        let mut toks: Tokens<Go> = Tokens::new();
+        toks.push(toks!(imported("github.com/b/log", "Logger")));
+        toks.push(toks!(imported("github.com/a/log", "Logger")));
+        toks.push(toks!(imported("github.com/b/log", "Level")));
+
+        assert_eq!(
+            Ok(concat!(
+                "package foo\n\n",
+                "import \"github.com/a/log\"\n",
+                "import log2 \"github.com/b/log\"\n\n",
+                "log2.Logger\nlog.Logger\nlog2.Level\n",
+            )),
+            toks.to_file_with(Extra::from_package("foo"))
+                .as_ref()
+                .map(|s| s.as_str())
+        );
+    }
+
+    #[test]
+    fn test_header() {
+        let toks: Tokens<Go> = toks!("var x = 1");
+        let config = Go::default_config().header("Code generated by rstgen. DO NOT EDIT.");
+
+        assert_eq!(
+            Ok("// Code generated by rstgen. DO NOT EDIT.\n\npackage foo\n\nvar x = 1\n"),
+            toks.to_file_with_config(Extra::from_package("foo"), config)
+                .as_ref()
+                .map(|s| s.as_str())
+        );
+    }
+
+    #[test]
+    fn test_indentation() {
+        let mut toks: Tokens<Go> = Tokens::new();
+        toks.push("func foo() {");
+        toks.nested("return");
+        toks.push("}");
+
+        assert_eq!(
+            Ok("package foo\n\nfunc foo() {\n\treturn\n}\n"),
+            toks.to_file_with(Extra::from_package("foo"))
+                .as_ref()
+                .map(|s| s.as_str())
+        );
+    }
+
     #[test]
     fn test_map() {
         let keyed = map(imported("foo", "Debug"), interface());
@@ -299,4 +543,20 @@ mod tests {
                 .map(|s| s.as_str())
         );
     }
+
+    #[test]
+    fn test_non_finite() {
+        let toks: Tokens<Go> = toks![
+            Value::Double(f64::NAN),
+            " ",
+            Value::Float(f32::NEG_INFINITY),
+        ];
+
+        assert_eq!(
+            Ok("package foo\n\nimport \"math\"\n\nmath.NaN() float32(math.Inf(-1))\n"),
+            toks.to_file_with(Extra::from_package("foo"))
+                .as_ref()
+                .map(|s| s.as_str())
+        );
+    }
 }
@@ -0,0 +1,120 @@
+//! Identifiers, validated and escaped according to the conventions of a language.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rstgen::{ident, Csharp, Rust};
+//!
+//! assert_eq!("r#type", ident::escape::<Rust>("type".into()).as_ref());
+//! assert_eq!("@class", ident::escape::<Csharp>("class".into()).as_ref());
+//! assert_eq!("name", ident::escape::<Rust>("name".into()).as_ref());
+//! ```
+
+use {Cons, Custom, Error, Result};
+
+/// Check if the given identifier is a reserved keyword in the language.
+pub fn is_keyword<C: Custom>(ident: &str) -> bool {
+    C::keywords().contains(&ident)
+}
+
+/// Validate that the given string is syntactically an identifier in the language.
+///
+/// Which characters identifiers may consist of is decided by
+/// [`Custom::is_identifier_start`] and [`Custom::is_identifier_part`]. Keywords are valid
+/// identifiers, since they can be escaped.
+pub fn validate<C: Custom>(ident: &str) -> Result<()> {
+    if !is_valid::<C>(ident) {
+        return Err(Error::invalid_identifier(ident));
+    }
+
+    Ok(())
+}
+
+/// Escape the given identifier if it is a reserved keyword in the language.
+///
+/// Characters which can't be part of an identifier are replaced with `_`, and an identifier
+/// which can't start with its first character, like a digit, is prefixed with `_`.
+pub fn escape<'el, C: Custom>(ident: Cons<'el>) -> Cons<'el> {
+    let ident = if is_valid::<C>(&ident) {
+        ident
+    } else {
+        Cons::from(replace_invalid::<C>(&ident))
+    };
+
+    if is_keyword::<C>(&ident) {
+        return Cons::from(C::escape_keyword(&ident));
+    }
+
+    ident
+}
+
+/// Validate and escape the given identifier.
+pub fn checked<'el, C: Custom>(ident: Cons<'el>) -> Result<Cons<'el>> {
+    validate::<C>(&ident)?;
+    Ok(escape::<C>(ident))
+}
+
+fn is_valid<C: Custom>(ident: &str) -> bool {
+    let mut chars = ident.chars();
+
+    match chars.next() {
+        Some(c) => C::is_identifier_start(c) && chars.all(C::is_identifier_part),
+        None => false,
+    }
+}
+
+fn replace_invalid<C: Custom>(ident: &str) -> String {
+    let mut out = String::new();
+
+    // characters which aren't part of an identifier are replaced with `_` below anyway.
+    match ident.chars().next() {
+        Some(c) if C::is_identifier_start(c) || !C::is_identifier_part(c) => {}
+        _ => out.push('_'),
+    }
+
+    out.extend(
+        ident
+            .chars()
+            .map(|c| if C::is_identifier_part(c) { c } else { '_' }),
+    );
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{checked, escape, validate};
+    use swift::Swift;
+    use {Cons, ErrorKind, Go, Java, Python};
+
+    #[test]
+    fn test_escape() {
+        assert_eq!("class_", escape::<Java>("class".into()).as_ref());
+        assert_eq!("`default`", escape::<Swift>("default".into()).as_ref());
+        assert_eq!("type_", escape::<Go>("type".into()).as_ref());
+        assert_eq!("in_", escape::<Python>("in".into()).as_ref());
+        assert_eq!("value", escape::<Python>("value".into()).as_ref());
+    }
+
+    #[test]
+    fn test_escape_invalid() {
+        assert_eq!("_1foo", escape::<Go>("1foo".into()).as_ref());
+        assert_eq!(
+            "content_type",
+            escape::<Python>("content-type".into()).as_ref()
+        );
+        assert_eq!("$scope", escape::<Java>("$scope".into()).as_ref());
+        assert_eq!("_scope", escape::<Python>("$scope".into()).as_ref());
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate::<Java>("_foo1").is_ok());
+        assert!(validate::<Java>("$foo").is_ok());
+        assert!(validate::<Python>("$foo").is_err());
+        assert!(validate::<Java>("").is_err());
+
+        let error = checked::<Java>(Cons::from("1foo")).unwrap_err();
+        assert_eq!(&ErrorKind::InvalidIdentifier("1foo".into()), error.kind());
+    }
+}
@@ -0,0 +1,189 @@
+//! Resolution of imported names, shared by the language specializations.
+//!
+//! Every name referenced in a file is bound to the simple name it is referenced by, in the order
+//! the names are bound. A name which collides with a name from another module which was bound
+//! before it is either referenced by its qualified name, or bound to an alias, depending on the
+//! rules of the language.
+//!
+//! Languages bind names while collecting the imports of a file, in a deterministic order, and look
+//! them up again while formatting, so that every reference to a name is rendered the same way.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rstgen::imports::{Conflict, Imports, Resolved};
+//!
+//! let mut imports = Imports::new(Conflict::Alias);
+//!
+//! assert_eq!(Resolved::Name, imports.bind("github.com/a/log", "log"));
+//! assert_eq!(Resolved::Alias("log2"), imports.bind("github.com/b/log", "log"));
+//!
+//! assert_eq!(Some(Resolved::Name), imports.resolve("github.com/a/log", "log"));
+//! assert_eq!(Some(Resolved::Alias("log2")), imports.resolve("github.com/b/log", "log"));
+//! assert_eq!(None, imports.resolve("github.com/c/fmt", "fmt"));
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+/// How a name which collides with a name bound before it is referenced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Conflict {
+    /// Reference the name by its qualified name.
+    #[default]
+    Qualify,
+    /// Bind the name to an alias, made unique by appending a number to it.
+    Alias,
+}
+
+/// How a name is referenced in a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Resolved<'a> {
+    /// By its simple name.
+    Name,
+    /// By the given alias.
+    Alias(&'a str),
+    /// By its qualified name.
+    Qualified,
+}
+
+/// Names bound in a file, and the modules they are bound to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Imports {
+    /// How collisions are resolved.
+    conflict: Conflict,
+    /// Simple names and aliases, and the modules they are bound to.
+    bound: HashMap<String, String>,
+    /// Aliases, by module and name.
+    aliases: HashMap<(String, String), String>,
+    /// Simple names which are ambiguous, and never bound.
+    reserved: HashSet<String>,
+}
+
+impl Imports {
+    /// Create a new set of imports, resolving collisions in the given way.
+    pub fn new(conflict: Conflict) -> Imports {
+        Imports {
+            conflict,
+            ..Imports::default()
+        }
+    }
+
+    /// Bind the given name from the given module.
+    ///
+    /// Names which are bound again resolve the same way they did the first time.
+    pub fn bind(&mut self, module: &str, name: &str) -> Resolved<'_> {
+        if self.bound.get(name).map(String::as_str) == Some(module) {
+            return Resolved::Name;
+        }
+
+        let key = (module.to_string(), name.to_string());
+
+        if self.aliases.contains_key(&key) {
+            return self.resolve(module, name).unwrap_or(Resolved::Qualified);
+        }
+
+        if !self.bound.contains_key(name) && !self.reserved.contains(name) {
+            self.bound.insert(name.to_string(), module.to_string());
+            return Resolved::Name;
+        }
+
+        match self.conflict {
+            Conflict::Qualify => Resolved::Qualified,
+            Conflict::Alias => {
+                let alias = (2..)
+                    .map(|n| format!("{}{}", name, n))
+                    .find(|alias| !self.bound.contains_key(alias) && !self.reserved.contains(alias))
+                    .unwrap_or_default();
+
+                self.bound.insert(alias.clone(), module.to_string());
+                self.aliases.insert(key, alias);
+                self.resolve(module, name).unwrap_or(Resolved::Qualified)
+            }
+        }
+    }
+
+    /// Resolve how the given name from the given module is referenced.
+    ///
+    /// Returns `None` if nothing is bound to the name, like when formatting tokens without
+    /// collecting their imports.
+    pub fn resolve(&self, module: &str, name: &str) -> Option<Resolved<'_>> {
+        if let Some(alias) = self.aliases.get(&(module.to_string(), name.to_string())) {
+            return Some(Resolved::Alias(alias));
+        }
+
+        match self.bound.get(name) {
+            Some(bound) if bound == module => Some(Resolved::Name),
+            Some(_) => Some(Resolved::Qualified),
+            None if self.reserved.contains(name) => Some(Resolved::Qualified),
+            None => None,
+        }
+    }
+
+    /// Reserve the given simple name, which is ambiguous between modules, so that it's not bound
+    /// to any module bound after this.
+    ///
+    /// Returns `false` if the name is already bound.
+    pub fn reserve(&mut self, name: &str) -> bool {
+        if self.bound.contains_key(name) {
+            return false;
+        }
+
+        self.reserved.insert(name.to_string());
+        true
+    }
+
+    /// Get the module the given simple name or alias is bound to.
+    pub fn module(&self, name: &str) -> Option<&str> {
+        self.bound.get(name).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Conflict, Imports, Resolved};
+
+    #[test]
+    fn test_qualify() {
+        let mut imports = Imports::new(Conflict::Qualify);
+
+        assert_eq!(Resolved::Name, imports.bind("java.io", "File"));
+        assert_eq!(Resolved::Qualified, imports.bind("com.example", "File"));
+        assert_eq!(Resolved::Name, imports.bind("java.io", "File"));
+
+        assert_eq!(
+            Some(Resolved::Qualified),
+            imports.resolve("com.example", "File")
+        );
+        assert_eq!(Some("java.io"), imports.module("File"));
+    }
+
+    #[test]
+    fn test_alias() {
+        let mut imports = Imports::new(Conflict::Alias);
+
+        assert_eq!(Resolved::Name, imports.bind("a/log2", "log2"));
+        assert_eq!(Resolved::Name, imports.bind("a/log", "log"));
+        assert_eq!(Resolved::Alias("log3"), imports.bind("b/log", "log"));
+        assert_eq!(Resolved::Alias("log3"), imports.bind("b/log", "log"));
+        assert_eq!(Some("b/log"), imports.module("log3"));
+    }
+
+    #[test]
+    fn test_reserve() {
+        let mut imports = Imports::new(Conflict::Qualify);
+
+        assert!(imports.reserve("Debug"));
+        assert_eq!(Resolved::Qualified, imports.bind("Foo", "Debug"));
+        assert_eq!(Resolved::Qualified, imports.bind("Bar", "Debug"));
+        assert_eq!(Some(Resolved::Qualified), imports.resolve("Foo", "Debug"));
+        assert_eq!(None, imports.module("Debug"));
+
+        assert_eq!(Resolved::Name, imports.bind("", "Data"));
+        assert!(!imports.reserve("Data"));
+        assert_eq!(Some(Resolved::Name), imports.resolve("", "Data"));
+
+        let mut imports = Imports::new(Conflict::Alias);
+        imports.reserve("log");
+        assert_eq!(Resolved::Alias("log2"), imports.bind("a/log", "log"));
+    }
+}
@@ -0,0 +1,232 @@
+//! Converter trait for things that can be converted into owned data.
+
+use con_::Con;
+use shared::Rc;
+use source_map::Origin;
+use {Cons, Element, Tokens};
+
+/// Helper trait to convert something into a value which doesn't borrow anything.
+///
+/// This makes it possible to store tokens in long-lived structures, like caches, or to send them
+/// to other threads.
+///
+/// ## Example
+///
+/// ```rust
+/// use rstgen::{IntoOwned, Java, Tokens};
+///
+/// fn generate(name: &str) -> Tokens<'static, Java<'static>> {
+///     let mut toks: Tokens<Java> = Tokens::new();
+///     toks.append("class ");
+///     toks.append(name);
+///     toks.append(" {}");
+///     toks.into_owned()
+/// }
+///
+/// let toks = generate(&String::from("Foo"));
+/// assert_eq!("class Foo {}", toks.to_string().unwrap());
+/// ```
+pub trait IntoOwned {
+    /// The owned type.
+    type Owned: 'static;
+
+    /// Convert into the owned type.
+    fn into_owned(self) -> Self::Owned;
+}
+
+impl<'el> IntoOwned for Cons<'el> {
+    type Owned = Cons<'static>;
+
+    fn into_owned(self) -> Cons<'static> {
+        match self {
+            Cons::Borrowed(value) => Cons::Rc(Rc::new(value.to_string())),
+            Cons::Rc(value) => Cons::Rc(value),
+        }
+    }
+}
+
+impl<'el, T> IntoOwned for Con<'el, T>
+where
+    T: IntoOwned + Clone,
+{
+    type Owned = Con<'static, T::Owned>;
+
+    fn into_owned(self) -> Con<'static, T::Owned> {
+        match self {
+            Con::Borrowed(value) => Con::Owned(value.clone().into_owned()),
+            Con::Owned(value) => Con::Owned(value.into_owned()),
+            Con::Rc(value) => Con::Rc(Rc::new(unwrap_or_clone(value).into_owned())),
+        }
+    }
+}
+
+/// Slots are copied by the conversion, since other handles to them can't be converted. The
+/// original slot is frozen, so adding tokens to it afterwards fails instead of leaving them out
+/// of the copy.
+///
+/// Lazy elements can't be carried over, since their closures are specific to the language they
+/// were created for. They are replaced with lazy elements which fail to format.
+impl<'el, C> IntoOwned for Element<'el, C>
+where
+    C: IntoOwned + Clone,
+{
+    type Owned = Element<'static, C::Owned>;
+
+    fn into_owned(self) -> Element<'static, C::Owned> {
+        use self::Element::*;
+
+        match self {
+            Rc(element) => Rc(::shared::Rc::new(unwrap_or_clone(element).into_owned())),
+            Borrowed(element) => element.clone().into_owned(),
+            Append(tokens) => Append(tokens.into_owned()),
+            Push(tokens) => Push(tokens.into_owned()),
+            Nested(tokens) => Nested(tokens.into_owned()),
+            Literal(literal) => Literal(literal.into_owned()),
+            Quoted(literal) => Quoted(literal.into_owned()),
+            Custom(custom) => Custom(custom.into_owned()),
+            Registered(custom) => Registered(custom.into_owned()),
+            None => None,
+            PushSpacing => PushSpacing,
+            Line => Line,
+            Spacing => Spacing,
+            LineSpacing => LineSpacing,
+            Group(tokens) => Group(tokens.into_owned()),
+            Indent(tokens) => Indent(tokens.into_owned()),
+            Align(columns, tokens) => Align(columns, tokens.into_owned()),
+            SoftLine => SoftLine,
+            SoftSpacing => SoftSpacing,
+            Context(context, tokens) => Context(context.into_owned(), tokens.into_owned()),
+            Region(name, tokens) => Region(name.into_owned(), tokens.into_owned()),
+            LineComment(lines) => LineComment(lines.into_owned()),
+            BlockComment(lines) => BlockComment(lines.into_owned()),
+            DocComment(lines) => DocComment(lines.into_owned()),
+            Slot(slot) => Slot(slot.map(|entry| entry.clone().into_owned())),
+            Lazy(_) => Lazy(::lazy::Lazy::unavailable()),
+        }
+    }
+}
+
+/// See the conversion of [`Element`] for how slots and lazy elements are handled.
+impl<'el, C> IntoOwned for Tokens<'el, C>
+where
+    C: IntoOwned + Clone,
+{
+    type Owned = Tokens<'static, C::Owned>;
+
+    fn into_owned(self) -> Tokens<'static, C::Owned> {
+        let origin = self.origin().cloned();
+        let tokens: Tokens<'static, C::Owned> =
+            self.into_iter().map(IntoOwned::into_owned).collect();
+
+        match origin {
+            Some(origin) => tokens.with_origin(origin.into_owned()),
+            None => tokens,
+        }
+    }
+}
+
+impl<'el> IntoOwned for Origin<'el> {
+    type Owned = Origin<'static>;
+
+    fn into_owned(self) -> Origin<'static> {
+        match self {
+            Origin::Location(location) => Origin::Location(location),
+            Origin::Label(label) => Origin::Label(label.into_owned()),
+        }
+    }
+}
+
+impl IntoOwned for () {
+    type Owned = ();
+
+    fn into_owned(self) {}
+}
+
+impl<T: IntoOwned> IntoOwned for Option<T> {
+    type Owned = Option<T::Owned>;
+
+    fn into_owned(self) -> Option<T::Owned> {
+        self.map(IntoOwned::into_owned)
+    }
+}
+
+impl<T: IntoOwned> IntoOwned for Vec<T> {
+    type Owned = Vec<T::Owned>;
+
+    fn into_owned(self) -> Vec<T::Owned> {
+        self.into_iter().map(IntoOwned::into_owned).collect()
+    }
+}
+
+impl<T: IntoOwned> IntoOwned for Box<T> {
+    type Owned = Box<T::Owned>;
+
+    fn into_owned(self) -> Box<T::Owned> {
+        Box::new((*self).into_owned())
+    }
+}
+
+/// Take the value out of the given pointer, cloning it if it is shared.
+fn unwrap_or_clone<T: Clone>(value: Rc<T>) -> T {
+    Rc::try_unwrap(value).unwrap_or_else(|value| (*value).clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IntoOwned;
+    use java::{self, Java};
+    use {Element, ErrorKind, Lazy, Slot, Tokens};
+
+    #[test]
+    fn test_into_owned() {
+        fn generate(package: &str) -> Tokens<'static, Java<'static>> {
+            let list = java::imported(package, "List");
+            let name = String::from("Foo");
+
+            let mut toks: Tokens<Java> = Tokens::new();
+            toks.push(toks!["class ", name.as_str(), " {"]);
+            toks.nested(toks![&list, " a;"]);
+            toks.push("}");
+            toks.into_owned()
+        }
+
+        let toks = generate(&String::from("java.util"));
+
+        assert_eq!(
+            "import java.util.List;\n\nclass Foo {\n  List a;\n}\n",
+            toks.to_file().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_slot() {
+        let helpers = Slot::new("helpers");
+
+        let mut toks: Tokens<Java> = Tokens::new();
+        toks.slot(&helpers);
+        helpers.push("int a;").unwrap();
+
+        let toks = toks.into_owned();
+        assert!(helpers.is_frozen());
+
+        let error = helpers.push("int b;").unwrap_err();
+        assert_eq!(&ErrorKind::SlotFrozen("helpers".into()), error.kind());
+        assert_eq!("int a;", toks.to_string().unwrap());
+    }
+
+    #[test]
+    fn test_lazy() {
+        let lazy = Lazy::new(|_, _, _| Ok(toks!["a"]));
+
+        let mut toks: Tokens<Java> = Tokens::new();
+        toks.append(Element::from(lazy));
+
+        assert_eq!("a", toks.to_string().unwrap());
+
+        let error = toks.into_owned().to_string().unwrap_err();
+        assert_eq!(
+            &ErrorKind::Custom("lazy elements can't be converted into owned tokens".into()),
+            error.kind()
+        );
+    }
+}
@@ -2,12 +2,16 @@
 
 use super::modifier::Modifier;
 use cons::Cons;
+use ident;
 use into_tokens::IntoTokens;
 use java::Java;
+use naming::Identifier;
 use tokens::Tokens;
+use Custom;
 
 /// Model for Java Arguments to functions.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Argument<'el> {
     /// Modifiers for argument.
     pub modifiers: Vec<Modifier>,
@@ -24,13 +28,13 @@ impl<'el> Argument<'el> {
     pub fn new<T, N>(ty: T, name: N) -> Argument<'el>
     where
         T: Into<Java<'el>>,
-        N: Into<Cons<'el>>,
+        N: Into<Identifier<'el>>,
     {
         Argument {
             annotations: Tokens::new(),
             modifiers: vec![Modifier::Final],
             ty: ty.into(),
-            name: name.into(),
+            name: ident::escape::<Java>(name.into().convert(Java::naming().field)),
         }
     }
 
@@ -12,6 +12,7 @@ use tokens::Tokens;
 
 /// Model for Java Classs.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Class<'el> {
     /// Class modifiers.
     pub modifiers: Vec<Modifier>,
@@ -147,7 +148,7 @@ impl<'el> IntoTokens<'el, Java<'el>> for Class<'el> {
 
         s.push("}");
 
-        s
+        s.with_context(format!("class {}", self.name))
     }
 }
 
@@ -169,4 +170,23 @@ mod tests {
         let out = s.as_ref().map(|s| s.as_str());
         assert_eq!(Ok("public class Foo<T> implements Super {\n}"), out);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use java::{imported, Field, Method, INTEGER};
+        use serde_json;
+
+        let mut c = Class::new("Foo");
+        c.extends = Some(imported("com.example", "Base"));
+        c.fields.push(Field::new(INTEGER, "count"));
+        c.methods.push(Method::new("run"));
+
+        let json = serde_json::to_string(&c).unwrap();
+        let d: Class = serde_json::from_str(&json).unwrap();
+
+        let c: Tokens<Java> = c.into();
+        let d: Tokens<Java> = d.into();
+        assert_eq!(c.to_file().unwrap(), d.to_file().unwrap());
+    }
 }
@@ -12,6 +12,7 @@ use tokens::Tokens;
 
 /// Model for Java Enums.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Enum<'el> {
     /// Variants of the enum.
     pub variants: Tokens<'el, Java<'el>>,
@@ -2,10 +2,12 @@
 
 use con_::Con;
 use java::{BlockComment, Modifier};
-use {Cons, Element, IntoTokens, Java, Tokens};
+use naming::Identifier;
+use {ident, Cons, Custom, Element, IntoTokens, Java, Tokens};
 
 /// Model for Java Fields.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Field<'el> {
     /// Annotations of field.
     pub annotations: Tokens<'el, Java<'el>>,
@@ -26,7 +28,7 @@ impl<'el> Field<'el> {
     pub fn new<T, N>(ty: T, name: N) -> Field<'el>
     where
         T: Into<Java<'el>>,
-        N: Into<Cons<'el>>,
+        N: Into<Identifier<'el>>,
     {
         use self::Modifier::*;
 
@@ -35,7 +37,27 @@ impl<'el> Field<'el> {
             modifiers: vec![Private, Final],
             comments: vec![],
             ty: ty.into(),
-            name: name.into(),
+            name: ident::escape::<Java>(name.into().convert(Java::naming().field)),
+            initializer: None,
+        }
+    }
+
+    /// Create a new `private static final` constant.
+    ///
+    /// Raw names are converted to the naming convention of constants, like `MAX_SIZE`.
+    pub fn constant<T, N>(ty: T, name: N) -> Field<'el>
+    where
+        T: Into<Java<'el>>,
+        N: Into<Identifier<'el>>,
+    {
+        use self::Modifier::*;
+
+        Field {
+            annotations: Tokens::new(),
+            modifiers: vec![Private, Static, Final],
+            comments: vec![],
+            ty: ty.into(),
+            name: ident::escape::<Java>(name.into().convert(Java::naming().constant)),
             initializer: None,
         }
     }
@@ -109,6 +131,7 @@ impl<'el> From<Field<'el>> for Element<'el, Java<'el>> {
 #[cfg(test)]
 mod tests {
     use java::{Field, INTEGER};
+    use naming;
     use tokens::Tokens;
 
     fn field() -> Field<'static> {
@@ -128,6 +151,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_keyword() {
+        let t = Tokens::from(Field::new(INTEGER, "default"));
+        assert_eq!(
+            Ok(String::from("private final int default_")),
+            t.to_string()
+        );
+    }
+
+    #[test]
+    fn test_invalid_name() {
+        let t = Tokens::from(Field::new(INTEGER, "$content-type"));
+        assert_eq!(
+            Ok(String::from("private final int $content_type")),
+            t.to_string()
+        );
+    }
+
+    #[test]
+    fn test_constant() {
+        let t = Tokens::from(Field::constant(INTEGER, naming::raw("max_size")));
+        assert_eq!(
+            Ok(String::from("private static final int MAX_SIZE")),
+            t.to_string()
+        );
+    }
+
+    #[test]
+    fn test_raw_name() {
+        let t = Tokens::from(Field::new(INTEGER, naming::raw("user_id")));
+        assert_eq!(Ok(String::from("private final int userId")), t.to_string());
+    }
+
     #[test]
     fn test_no_comments() {
         let t = Tokens::from(field());
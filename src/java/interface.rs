@@ -9,6 +9,7 @@ use tokens::Tokens;
 
 /// Model for Java Interfaces.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Interface<'el> {
     /// Interface modifiers.
     pub modifiers: Vec<Modifier>,
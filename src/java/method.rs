@@ -1,10 +1,12 @@
 //! Data structure for methods.
 
 use java::{Argument, BlockComment, Java, Modifier, VOID};
-use {Cons, IntoTokens, Tokens};
+use naming::Identifier;
+use {ident, Cons, Custom, IntoTokens, Tokens};
 
 /// Model for Java Methods.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Method<'el> {
     /// Method modifiers.
     pub modifiers: Vec<Modifier>,
@@ -30,7 +32,7 @@ impl<'el> Method<'el> {
     /// Build a new empty constructor.
     pub fn new<N>(name: N) -> Method<'el>
     where
-        N: Into<Cons<'el>>,
+        N: Into<Identifier<'el>>,
     {
         use self::Modifier::*;
 
@@ -43,7 +45,7 @@ impl<'el> Method<'el> {
             comments: Vec::new(),
             throws: None,
             annotations: Tokens::new(),
-            name: name.into(),
+            name: ident::escape::<Java>(name.into().convert(Java::naming().method)),
         }
     }
 
@@ -65,6 +67,8 @@ into_tokens_impl_from!(Method<'el>, Java<'el>);
 
 impl<'el> IntoTokens<'el, Java<'el>> for Method<'el> {
     fn into_tokens(self) -> Tokens<'el, Java<'el>> {
+        let context = format!("method {}", self.name);
+
         let mut sig = Tokens::new();
 
         sig.extend(self.modifiers.into_tokens());
@@ -88,7 +92,7 @@ impl<'el> IntoTokens<'el, Java<'el>> for Method<'el> {
 
             let args: Tokens<Java> = args.into_tokens();
 
-            n.append(toks!["(", args.join(", "), ")"]);
+            n.append(args.join_group("(", ",", ")"));
 
             n
         });
@@ -113,13 +117,14 @@ impl<'el> IntoTokens<'el, Java<'el>> for Method<'el> {
             s.push("}");
         }
 
-        s
+        s.with_context(context)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Method;
+    use java::{Argument, INTEGER};
     use tokens::Tokens;
 
     fn build_method() -> Method<'static> {
@@ -158,4 +163,60 @@ mod tests {
             t.to_string()
         );
     }
+
+    #[test]
+    fn test_arguments() {
+        let mut m = build_method();
+        m.arguments.push(Argument::new(INTEGER, "a"));
+        m.arguments.push(Argument::new(INTEGER, "b"));
+
+        let t = Tokens::from(m);
+        assert_eq!(
+            Ok(String::from(
+                "public <T> void foo(final int a, final int b);"
+            )),
+            t.to_string()
+        );
+    }
+
+    #[test]
+    fn test_arguments_throws() {
+        let mut m = build_method();
+
+        for name in &["firstArgument", "secondArgument", "thirdArgument"] {
+            m.arguments.push(Argument::new(INTEGER, *name));
+        }
+
+        m.throws = Some("IllegalArgumentException, IllegalStateException".into());
+
+        let t = Tokens::from(m);
+        assert_eq!(
+            Ok(String::from(
+                "public <T> void foo(\n  final int firstArgument,\n  final int secondArgument,\n  final int thirdArgument\n) throws IllegalArgumentException, IllegalStateException;",
+            )),
+            t.to_string()
+        );
+    }
+
+    #[test]
+    fn test_long_arguments() {
+        let mut m = build_method();
+
+        for name in &[
+            "firstArgument",
+            "secondArgument",
+            "thirdArgument",
+            "fourthArgument",
+        ] {
+            m.arguments.push(Argument::new(INTEGER, *name));
+        }
+
+        let t = Tokens::from(m);
+        assert_eq!(
+            Ok(String::from(
+                "public <T> void foo(\n  final int firstArgument,\n  final int secondArgument,\n  final int thirdArgument,\n  final int fourthArgument\n);",
+            )),
+            t.to_string()
+        );
+    }
 }
@@ -23,10 +23,15 @@ pub use self::utils::BlockComment;
 use super::cons::Cons;
 use super::custom::Custom;
 use super::formatter::Formatter;
+use super::imports::{Conflict, Imports, Resolved};
+use super::into_owned::IntoOwned;
 use super::into_tokens::IntoTokens;
 use super::tokens::Tokens;
-use std::collections::{BTreeSet, HashMap};
-use std::fmt::{self, Write};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::path::PathBuf;
+use value::{self, Value};
+use {Error, Result};
 
 static JAVA_LANG: &'static str = "java.lang";
 static SEP: &'static str = ".";
@@ -87,6 +92,7 @@ pub const VOID: Java<'static> = Java::Primitive {
 
 /// A class.
 #[derive(Debug, Clone, Hash, PartialOrd, Ord, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Type<'el> {
     /// Package of the class.
     package: Cons<'el>,
@@ -100,6 +106,7 @@ pub struct Type<'el> {
 
 /// An optional type.
 #[derive(Debug, Clone, Hash, PartialOrd, Ord, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Optional<'el> {
     /// The type that is optional.
     pub value: Box<Java<'el>>,
@@ -109,6 +116,7 @@ pub struct Optional<'el> {
 
 /// Java token specialization.
 #[derive(Debug, Clone, Hash, PartialOrd, Ord, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum Java<'el> {
     /// Primitive type.
     Primitive {
@@ -132,13 +140,13 @@ into_tokens_impl_from!(Java<'el>, Java<'el>);
 into_tokens_impl_from!(&'el Java<'el>, Java<'el>);
 
 /// Extra data for Java formatting.
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct Extra<'el> {
     /// Package to use.
     pub package: Option<Cons<'el>>,
 
-    /// Types which has been imported into the local namespace.
-    imported: HashMap<String, String>,
+    /// Types which have been bound in the local namespace.
+    imports: Imports,
 }
 
 impl<'el> Extra<'el> {
@@ -149,10 +157,21 @@ impl<'el> Extra<'el> {
     {
         Extra {
             package: Some(package.into()),
-            imported: HashMap::new(),
+            imports: Imports::new(Conflict::Qualify),
         }
     }
 
+    /// Get the package the given simple name refers to, if it has been bound by an import, the
+    /// package of the file, or `java.lang`.
+    pub fn imported(&self, name: &str) -> Option<&str> {
+        self.imports.module(name)
+    }
+
+    /// Check if types from the given package are available without importing them.
+    fn is_implicit(&self, package: &str) -> bool {
+        package == JAVA_LANG || self.package.as_ref().map_or("", |p| p.as_ref()) == package
+    }
+
     /// Set the package name to build.
     pub fn package<P>(&mut self, package: P)
     where
@@ -185,54 +204,76 @@ impl<'el> Java<'el> {
         }
     }
 
-    fn type_imports<'a>(java: &'a Java<'a>, modules: &mut BTreeSet<(&'a str, &'a str)>) {
+    fn type_imports<'a>(
+        java: &'a Java<'a>,
+        modules: &mut BTreeSet<(&'a str, &'a str)>,
+        locals: &mut BTreeSet<&'a str>,
+    ) {
         use self::Java::*;
 
         match *java {
             Class(ref class) => {
                 for argument in &class.arguments {
-                    Self::type_imports(argument, modules);
+                    Self::type_imports(argument, modules, locals);
                 }
 
                 modules.insert((class.package.as_ref(), class.name.as_ref()));
             }
+            Local { ref name } => {
+                locals.insert(name.as_ref());
+            }
+            Optional(self::Optional { ref field, .. }) => {
+                Self::type_imports(field, modules, locals);
+            }
             _ => {}
         };
     }
 
-    fn imports<'a>(tokens: &'a Tokens<'a, Self>, extra: &mut Extra) -> Option<Tokens<'a, Self>> {
+    fn imports<'a>(
+        tokens: &'a Tokens<Self>,
+        extra: &mut Extra,
+    ) -> Result<Option<Tokens<'a, Self>>> {
         let mut modules = BTreeSet::new();
-
-        let file_package = extra.package.as_ref().map(|p| p.as_ref());
+        let mut locals = BTreeSet::new();
 
         for custom in tokens.walk_custom() {
-            Self::type_imports(custom, &mut modules);
+            Self::type_imports(custom, &mut modules, &mut locals);
         }
 
         if modules.is_empty() {
-            return None;
+            return Ok(None);
+        }
+
+        // Local names, and names which are available without importing them, take precedence
+        // over imported names.
+        for name in locals {
+            extra.imports.bind("", name);
+        }
+
+        for &(package, name) in &modules {
+            if extra.is_implicit(package) {
+                extra.imports.bind(package, name);
+            }
         }
 
         let mut out = Tokens::new();
 
         for (package, name) in modules {
-            if extra.imported.contains_key(name) {
+            if extra.is_implicit(package) || extra.imports.resolve(package, name).is_some() {
                 continue;
             }
 
-            if package == JAVA_LANG {
-                continue;
+            // classes in the default package can't be imported.
+            if package.is_empty() {
+                return Err(Error::unresolved_import(name));
             }
 
-            if Some(package) == file_package {
-                continue;
+            if extra.imports.bind(package, name) == Resolved::Name {
+                out.push(toks!("import ", package, SEP, name, ";"));
             }
-
-            out.push(toks!("import ", package, SEP, name, ";"));
-            extra.imported.insert(name.to_string(), package.to_string());
         }
 
-        Some(out)
+        Ok(Some(out).filter(|out| !out.is_empty()))
     }
 
     /// Add arguments to the given variable.
@@ -398,10 +439,162 @@ impl<'el> Java<'el> {
     }
 }
 
+/// Reserved keywords.
+static KEYWORDS: &[&str] = &[
+    "abstract",
+    "assert",
+    "boolean",
+    "break",
+    "byte",
+    "case",
+    "catch",
+    "char",
+    "class",
+    "const",
+    "continue",
+    "default",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "extends",
+    "final",
+    "finally",
+    "float",
+    "for",
+    "goto",
+    "if",
+    "implements",
+    "import",
+    "instanceof",
+    "int",
+    "interface",
+    "long",
+    "native",
+    "new",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "short",
+    "static",
+    "strictfp",
+    "super",
+    "switch",
+    "synchronized",
+    "this",
+    "throw",
+    "throws",
+    "transient",
+    "try",
+    "void",
+    "volatile",
+    "while",
+    "true",
+    "false",
+    "null",
+];
+
+impl<'el> IntoOwned for Type<'el> {
+    type Owned = Type<'static>;
+
+    fn into_owned(self) -> Type<'static> {
+        Type {
+            package: self.package.into_owned(),
+            name: self.name.into_owned(),
+            path: self.path.into_owned(),
+            arguments: self.arguments.into_owned(),
+        }
+    }
+}
+
+impl<'el> IntoOwned for Optional<'el> {
+    type Owned = Optional<'static>;
+
+    fn into_owned(self) -> Optional<'static> {
+        Optional {
+            value: self.value.into_owned(),
+            field: self.field.into_owned(),
+        }
+    }
+}
+
+impl<'el> IntoOwned for Java<'el> {
+    type Owned = Java<'static>;
+
+    fn into_owned(self) -> Java<'static> {
+        use self::Java::*;
+
+        match self {
+            Primitive { boxed, primitive } => Primitive { boxed, primitive },
+            Class(class) => Class(class.into_owned()),
+            Local { name } => Local {
+                name: name.into_owned(),
+            },
+            Optional(optional) => Optional(optional.into_owned()),
+        }
+    }
+}
+
+impl<'el> IntoOwned for Extra<'el> {
+    type Owned = Extra<'static>;
+
+    fn into_owned(self) -> Extra<'static> {
+        Extra {
+            package: self.package.into_owned(),
+            imports: self.imports,
+        }
+    }
+}
+
+/// Primitive types are deserialized into one of the known primitive types.
+#[cfg(feature = "serde")]
+impl<'de, 'el> ::serde::Deserialize<'de> for Java<'el> {
+    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+    where
+        D: ::serde::Deserializer<'de>,
+    {
+        use serde::de::{Error, Unexpected};
+
+        #[derive(Deserialize)]
+        #[serde(rename = "Java")]
+        enum Data<'el> {
+            Primitive { boxed: String, primitive: String },
+            Class(Type<'el>),
+            Local { name: Cons<'el> },
+            Optional(Optional<'el>),
+        }
+
+        let primitives = [
+            SHORT, INTEGER, LONG, FLOAT, DOUBLE, CHAR, BOOLEAN, BYTE, VOID,
+        ];
+
+        Ok(match Data::deserialize(deserializer)? {
+            Data::Primitive { boxed, primitive } => primitives
+                .iter()
+                .find(|p| match **p {
+                    Java::Primitive {
+                        boxed: b,
+                        primitive: p,
+                    } => b == boxed && p == primitive,
+                    _ => false,
+                })
+                .cloned()
+                .ok_or_else(|| {
+                    D::Error::invalid_value(Unexpected::Str(&primitive), &"a primitive type")
+                })?,
+            Data::Class(class) => Java::Class(class),
+            Data::Local { name } => Java::Local { name },
+            Data::Optional(optional) => Java::Optional(optional),
+        })
+    }
+}
+
 impl<'el> Custom for Java<'el> {
     type Extra = Extra<'el>;
 
-    fn format(&self, out: &mut Formatter, extra: &mut Self::Extra, level: usize) -> fmt::Result {
+    fn format(&self, out: &mut Formatter, extra: &mut Self::Extra, level: usize) -> Result<()> {
         use self::Java::*;
 
         match *self {
@@ -418,12 +611,16 @@ impl<'el> Custom for Java<'el> {
             }
             Class(ref cls) => {
                 {
-                    let file_package = extra.package.as_ref().map(|p| p.as_ref());
-                    let imported = extra.imported.get(cls.name.as_ref()).map(String::as_str);
-                    let pkg = Some(cls.package.as_ref());
+                    let package = cls.package.as_ref();
 
-                    if cls.package.as_ref() != JAVA_LANG && imported != pkg && file_package != pkg {
-                        out.write_str(cls.package.as_ref())?;
+                    let qualified = match extra.imports.resolve(package, cls.name.as_ref()) {
+                        Some(Resolved::Name) => false,
+                        Some(_) => true,
+                        None => !extra.is_implicit(package),
+                    };
+
+                    if qualified {
+                        out.write_str(package)?;
                         out.write_str(SEP)?;
                     }
                 }
@@ -466,7 +663,7 @@ impl<'el> Custom for Java<'el> {
         Ok(())
     }
 
-    fn quote_string(out: &mut Formatter, input: &str) -> fmt::Result {
+    fn quote_string(out: &mut Formatter, input: &str) -> Result<()> {
         out.write_char('"')?;
 
         for c in input.chars() {
@@ -488,24 +685,109 @@ impl<'el> Custom for Java<'el> {
         Ok(())
     }
 
+    fn literal<'a>(value: Value<'a, Self>) -> Tokens<'a, Self>
+    where
+        Self: 'a,
+    {
+        match value {
+            Value::Long(value) => value::raw(format!("{}L", value)),
+            Value::Float(value) if !value.is_finite() => value::non_finite(
+                value.into(),
+                "Float.NaN",
+                "Float.POSITIVE_INFINITY",
+                "Float.NEGATIVE_INFINITY",
+            ),
+            Value::Double(value) if !value.is_finite() => value::non_finite(
+                value,
+                "Double.NaN",
+                "Double.POSITIVE_INFINITY",
+                "Double.NEGATIVE_INFINITY",
+            ),
+            Value::Float(value) => value::raw(format!("{}f", value::float(value))),
+            Value::Array(ty, values) => {
+                toks!["new ", ty, "[]", value::sequence("{", values, "}")]
+            }
+            Value::List(values) => toks![
+                imported("java.util", "List"),
+                value::sequence(".of(", values, ")"),
+            ],
+            // Map.of only takes up to 10 entries.
+            Value::Map(entries) if entries.len() > 10 => {
+                let map = imported("java.util", "Map");
+                let mut t = toks![map.clone(), ".ofEntries("];
+
+                for (n, (key, value)) in entries.into_iter().enumerate() {
+                    if n > 0 {
+                        t.append(", ");
+                    }
+
+                    t.append(toks![
+                        map.clone(),
+                        value::sequence(".entry(", vec![key, value], ")")
+                    ]);
+                }
+
+                t.append(")");
+                t
+            }
+            Value::Map(entries) => {
+                let values = entries
+                    .into_iter()
+                    .flat_map(|(key, value)| vec![key, value])
+                    .collect();
+
+                toks![
+                    imported("java.util", "Map"),
+                    value::sequence(".of(", values, ")"),
+                ]
+            }
+            Value::Tuple(values) => toks!["new Object[]", value::sequence("{", values, "}")],
+            value => value::literal(value),
+        }
+    }
+
+    fn keywords() -> &'static [&'static str] {
+        KEYWORDS
+    }
+
+    fn is_identifier_start(c: char) -> bool {
+        c == '_' || c == '$' || c.is_alphabetic()
+    }
+
+    fn is_identifier_part(c: char) -> bool {
+        c == '_' || c == '$' || c.is_alphanumeric()
+    }
+
+    fn file_path(name: &str, extra: &Self::Extra) -> PathBuf {
+        let mut path = PathBuf::new();
+
+        if let Some(ref package) = extra.package {
+            path.extend(package.split('.'));
+        }
+
+        path.push(format!("{}.java", name));
+        path
+    }
+
     fn write_file<'a>(
-        tokens: Tokens<'a, Self>,
+        tokens: &Tokens<'a, Self>,
         out: &mut Formatter,
         extra: &mut Self::Extra,
         level: usize,
-    ) -> fmt::Result {
+    ) -> Result<()> {
+        out.write_header::<Self>()?;
+
         let mut toks: Tokens<Self> = Tokens::new();
 
         if let Some(ref package) = extra.package {
             toks.push(toks!["package ", package.clone(), ";"]);
         }
 
-        if let Some(imports) = Self::imports(&tokens, extra) {
+        if let Some(imports) = Self::imports(tokens, extra)? {
             toks.push(imports);
         }
 
-        toks.push_ref(&tokens);
-        toks.join_line_spacing().format(out, extra, level)
+        tokens.format_with_preamble(&toks, out, extra, level)
     }
 }
 
@@ -539,6 +821,32 @@ mod tests {
     use quoted::Quoted;
     use tokens::Tokens;
 
+    #[test]
+    fn test_literals() {
+        let mut toks: Tokens<Java> = Tokens::new();
+        toks.push(toks![
+            Value::Long(1),
+            " ",
+            Value::Float(1.0),
+            " ",
+            Value::Null
+        ]);
+        toks.push(Value::Array(INTEGER, vec![1.into(), 2.into()]));
+        toks.push(Value::List(vec!["a".into()]));
+        toks.push(Value::Map(vec![("a".into(), 1.into())]));
+        toks.push(Value::Map((0..11).map(|n| (n.into(), n.into())).collect()));
+        toks.push(toks![
+            Value::Double(f64::NAN),
+            " ",
+            Value::Float(f32::INFINITY)
+        ]);
+
+        assert_eq!(
+            Ok("import java.util.List;\nimport java.util.Map;\n\n1L 1.0f null\nnew int[]{1, 2}\nList.of(\"a\")\nMap.of(\"a\", 1)\nMap.ofEntries(Map.entry(0, 0), Map.entry(1, 1), Map.entry(2, 2), Map.entry(3, 3), Map.entry(4, 4), Map.entry(5, 5), Map.entry(6, 6), Map.entry(7, 7), Map.entry(8, 8), Map.entry(9, 9), Map.entry(10, 10))\nDouble.NaN Float.POSITIVE_INFINITY\n"),
+            toks.to_file().as_ref().map(|s| s.as_str())
+        );
+    }
+
     #[test]
     fn test_primitive() {
         assert!(SHORT.is_primitive());
@@ -574,4 +882,33 @@ mod tests {
             toks.to_file().as_ref().map(|s| s.as_str())
         );
     }
+
+    #[test]
+    fn test_imported_conflicts_with_package() {
+        let list = imported("java.util", "List");
+        let local_list = imported("com.example", "List");
+        let string = imported("java.lang", "String");
+        let other_string = imported("com.other", "String");
+
+        let toks = toks!(list, local_list, string, other_string).join_spacing();
+
+        assert_eq!(
+            Ok("package com.example;\n\njava.util.List List String com.other.String\n"),
+            toks.to_file_with(Extra::new("com.example"))
+                .as_ref()
+                .map(|s| s.as_str())
+        );
+    }
+
+    #[test]
+    fn test_default_package() {
+        use ErrorKind;
+
+        let toks = toks!(imported("", "Foo"), " a;");
+
+        assert_eq!(Ok("Foo a;\n"), toks.to_file().as_ref().map(|s| s.as_str()));
+
+        let error = toks.to_file_with(Extra::new("com.example")).unwrap_err();
+        assert_eq!(&ErrorKind::UnresolvedImport("Foo".into()), error.kind());
+    }
 }
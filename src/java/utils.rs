@@ -1,6 +1,7 @@
 use {Cons, Element, IntoTokens, Java, Tokens};
 
 /// Format a block comment, starting with `/**`, and ending in `*/`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BlockComment<'el>(pub Vec<Cons<'el>>);
 
 impl<'el> IntoTokens<'el, Java<'el>> for BlockComment<'el> {
@@ -11,14 +12,7 @@ impl<'el> IntoTokens<'el, Java<'el>> for BlockComment<'el> {
             return t;
         }
 
-        t.push("/**");
-
-        for line in self.0 {
-            t.push(" * ");
-            t.append(line);
-        }
-
-        t.push(" */");
+        t.push(Element::DocComment(self.0));
         t.push(Element::PushSpacing);
 
         t
@@ -2,13 +2,17 @@
 
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{self, Write};
-use {Cons, Custom, Formatter, IntoTokens, Quoted, Tokens};
+use std::path::PathBuf;
+use syntax::{Declaration, Exceptions, ForEach, Lambda, Syntax};
+use value::{self, Value};
+use {Cons, Custom, Formatter, IntoOwned, IntoTokens, Quoted, Result, Tokens};
 
 static SEP: &'static str = ".";
 static PATH_SEP: &'static str = "/";
 
 /// JavaScript token specialization.
 #[derive(Debug, Clone, Hash, PartialOrd, Ord, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct JavaScript<'el> {
     /// Module of the imported name.
     module: Option<Cons<'el>>,
@@ -47,7 +51,7 @@ impl<'el> JavaScript<'el> {
         format!("{}.js", parts.join(PATH_SEP))
     }
 
-    fn imports(tokens: &'el Tokens<'el, Self>) -> Option<Tokens<'el, Self>> {
+    fn imports<'a>(tokens: &'a Tokens<Self>) -> Option<Tokens<'a, Self>> {
         let mut sets = BTreeMap::new();
         let mut wildcard = BTreeSet::new();
 
@@ -99,14 +103,77 @@ impl<'el> JavaScript<'el> {
     }
 }
 
+/// Reserved keywords.
+static KEYWORDS: &[&str] = &[
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+impl<'el> IntoOwned for JavaScript<'el> {
+    type Owned = JavaScript<'static>;
+
+    fn into_owned(self) -> JavaScript<'static> {
+        JavaScript {
+            module: self.module.into_owned(),
+            name: self.name.into_owned(),
+            alias: self.alias.into_owned(),
+        }
+    }
+}
+
 impl<'el> Custom for JavaScript<'el> {
     type Extra = ();
 
-    fn format(&self, out: &mut Formatter, _extra: &mut Self::Extra, _level: usize) -> fmt::Result {
-        write!(out, "{}", self)
+    fn format(&self, out: &mut Formatter, _extra: &mut Self::Extra, _level: usize) -> Result<()> {
+        write!(out, "{}", self)?;
+        Ok(())
     }
 
-    fn quote_string(out: &mut Formatter, input: &str) -> fmt::Result {
+    fn quote_string(out: &mut Formatter, input: &str) -> Result<()> {
         out.write_char('"')?;
 
         for c in input.chars() {
@@ -128,20 +195,67 @@ impl<'el> Custom for JavaScript<'el> {
         Ok(())
     }
 
+    fn literal<'a>(value: Value<'a, Self>) -> Tokens<'a, Self>
+    where
+        Self: 'a,
+    {
+        match value {
+            Value::Char(value) => value::literal(Value::String(value.to_string().into())),
+            Value::Tuple(values) => value::sequence("[", values, "]"),
+            value => value::literal(value),
+        }
+    }
+
+    fn syntax() -> Syntax {
+        Syntax {
+            declaration: Declaration::Keyword {
+                immutable: "const",
+                mutable: "let",
+            },
+            equals: "===",
+            not_equals: "!==",
+            for_each: ForEach {
+                keyword: "for",
+                binding: "const ",
+                separator: " of ",
+            },
+            lambda: Lambda::Arrow("=>"),
+            exceptions: Some(Exceptions::Untyped),
+            ..Syntax::default()
+        }
+    }
+
+    fn keywords() -> &'static [&'static str] {
+        KEYWORDS
+    }
+
+    fn is_identifier_start(c: char) -> bool {
+        c == '_' || c == '$' || c.is_alphabetic()
+    }
+
+    fn is_identifier_part(c: char) -> bool {
+        c == '_' || c == '$' || c.is_alphanumeric()
+    }
+
+    fn file_path(name: &str, _extra: &Self::Extra) -> PathBuf {
+        PathBuf::from(format!("{}.js", name))
+    }
+
     fn write_file<'a>(
-        tokens: Tokens<'a, JavaScript<'a>>,
+        tokens: &Tokens<'a, Self>,
         out: &mut Formatter,
         extra: &mut Self::Extra,
         level: usize,
-    ) -> fmt::Result {
-        let mut toks = Tokens::new();
+    ) -> Result<()> {
+        out.write_header::<Self>()?;
+
+        let mut toks: Tokens<Self> = Tokens::new();
 
-        if let Some(imports) = JavaScript::imports(&tokens) {
+        if let Some(imports) = JavaScript::imports(tokens) {
             toks.push(imports);
         }
 
-        toks.push_ref(&tokens);
-        toks.join_line_spacing().format(out, extra, level)
+        tokens.format_with_preamble(&toks, out, extra, level)
     }
 }
 
@@ -0,0 +1,299 @@
+//! Lazy elements, which produce tokens while they are being formatted.
+//!
+//! Lazy elements have access to the formatter and the extra data of the language, which makes it
+//! possible to generate code depending on state only known while rendering, like the names which
+//! have been imported.
+//!
+//! The produced tokens are formatted like any other tokens of the language, but are not visited
+//! when collecting imports, since imports are collected before anything is formatted.
+//!
+//! Inside of a group, the closure is called again for every probe of the layout, with a copy of
+//! the extra data. Only the output of the final call is kept.
+//!
+//! ## Example
+//!
+//! ```rust
+//! # #[macro_use] extern crate rstgen;
+//! use rstgen::java::{imported, Extra};
+//! use rstgen::{Java, Lazy, Tokens};
+//!
+//! # fn main() {
+//! // Qualify `java.awt.List` only if `List` has been imported from another package.
+//! let list = Lazy::new(|_, extra: &mut Extra, _| {
+//!     let name = match extra.imported("List") {
+//!         Some(package) if package != "java.awt" => "java.awt.List",
+//!         _ => "List",
+//!     };
+//!
+//!     Ok(toks![name])
+//! });
+//!
+//! let mut toks: Tokens<Java> = Tokens::new();
+//! toks.push(toks![imported("java.util", "List"), " a;"]);
+//! toks.push(toks![list, " b;"]);
+//!
+//! assert_eq!(
+//!     "import java.util.List;\n\nList a;\njava.awt.List b;\n",
+//!     toks.to_file().unwrap()
+//! );
+//! # }
+//! ```
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer};
+use shared::{MaybeSync, Rc};
+use std::fmt;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+use {Custom, Element, Error, Formatter, Result, Tokens};
+
+/// Producer of tokens for a lazy element, implemented for closures.
+trait LazyTokens<'el, C: 'el>: MaybeSync {
+    /// Produce the tokens to format.
+    fn tokens(
+        &self,
+        out: &mut Formatter,
+        extra: &mut C::Extra,
+        level: usize,
+    ) -> Result<Tokens<'el, C>>
+    where
+        C: Custom;
+}
+
+impl<'el, C: 'el, F> LazyTokens<'el, C> for F
+where
+    C: Custom,
+    F: Fn(&mut Formatter, &mut C::Extra, usize) -> Result<Tokens<'el, C>> + MaybeSync,
+{
+    fn tokens(
+        &self,
+        out: &mut Formatter,
+        extra: &mut C::Extra,
+        level: usize,
+    ) -> Result<Tokens<'el, C>> {
+        self(out, extra, level)
+    }
+}
+
+/// Producer of a lazy element whose closure couldn't be carried over, which fails to produce
+/// tokens.
+struct Unavailable;
+
+impl<'el, C: 'el> LazyTokens<'el, C> for Unavailable {
+    fn tokens(&self, _: &mut Formatter, _: &mut C::Extra, _: usize) -> Result<Tokens<'el, C>>
+    where
+        C: Custom,
+    {
+        Err(Error::custom(
+            "lazy elements can't be converted into owned tokens",
+        ))
+    }
+}
+
+type Producer<'el, C> = Box<dyn LazyTokens<'el, C> + 'el>;
+
+/// A lazy element, producing tokens while it is being formatted.
+///
+/// Like a [`SlotRef`](::slot::SlotRef), lazy elements are covariant over the lifetime and
+/// language of the tokens they produce, so that they don't keep tokens from being shortened.
+pub struct Lazy<'el, C: 'el> {
+    /// The `Producer<'el, C>` of the element, from a strong reference owned by this element.
+    ptr: NonNull<()>,
+    marker: PhantomData<Tokens<'el, C>>,
+}
+
+impl<'el, C: 'el> Lazy<'el, C> {
+    /// Create a lazy element from the given closure, which is called with the formatter, the
+    /// extra data of the file, and the current level.
+    ///
+    /// With the `sync` feature enabled, the closure must be `Send + Sync`.
+    pub fn new<F>(lazy: F) -> Lazy<'el, C>
+    where
+        C: Custom,
+        F: Fn(&mut Formatter, &mut C::Extra, usize) -> Result<Tokens<'el, C>> + MaybeSync + 'el,
+    {
+        Lazy::from_producer(Box::new(lazy))
+    }
+
+    /// Create a lazy element which fails to format, in place of one whose closure can't be
+    /// carried over, like when converting tokens into owned tokens.
+    pub(crate) fn unavailable() -> Lazy<'el, C> {
+        Lazy::from_producer(Box::new(Unavailable))
+    }
+
+    fn from_producer(producer: Producer<'el, C>) -> Lazy<'el, C> {
+        let inner = Rc::into_raw(Rc::new(producer));
+
+        Lazy {
+            // Pointers to reference counted values are never null.
+            ptr: unsafe { NonNull::new_unchecked(inner as *mut ()) },
+            marker: PhantomData,
+        }
+    }
+
+    fn producer(&self) -> &Producer<'el, C> {
+        // The producer is kept alive by the strong reference owned by this element.
+        unsafe { &*(self.ptr.as_ptr() as *const Producer<'el, C>) }
+    }
+
+    /// Produce the tokens of the element and format them.
+    pub fn format(&self, out: &mut Formatter, extra: &mut C::Extra, level: usize) -> Result<()>
+    where
+        C: Custom,
+    {
+        self.producer()
+            .tokens(out, extra, level)?
+            .format(out, extra, level)
+    }
+}
+
+impl<'el, C> Clone for Lazy<'el, C> {
+    fn clone(&self) -> Self {
+        unsafe { Rc::increment_strong_count(self.ptr.as_ptr() as *const Producer<'el, C>) };
+
+        Lazy {
+            ptr: self.ptr,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'el, C> Drop for Lazy<'el, C> {
+    fn drop(&mut self) {
+        unsafe { drop(Rc::from_raw(self.ptr.as_ptr() as *const Producer<'el, C>)) };
+    }
+}
+
+/// Producers can only be shared between threads with the `sync` feature, which requires them to
+/// be `Send + Sync`.
+#[cfg(feature = "sync")]
+unsafe impl<'el, C: Send + Sync> Send for Lazy<'el, C> {}
+
+#[cfg(feature = "sync")]
+unsafe impl<'el, C: Send + Sync> Sync for Lazy<'el, C> {}
+
+impl<'el, C> fmt::Debug for Lazy<'el, C> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("Lazy")
+    }
+}
+
+/// Lazy elements are equal if they share the same producer.
+impl<'el, C> PartialEq for Lazy<'el, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr == other.ptr
+    }
+}
+
+impl<'el, C> Eq for Lazy<'el, C> {}
+
+impl<'el, C> From<Lazy<'el, C>> for Element<'el, C> {
+    fn from(value: Lazy<'el, C>) -> Self {
+        Element::Lazy(value)
+    }
+}
+
+/// Lazy elements can't be serialized, and fail to serialize.
+#[cfg(feature = "serde")]
+impl<'el, C> Serialize for Lazy<'el, C> {
+    fn serialize<S>(&self, _: S) -> ::std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use serde::ser::Error;
+        Err(S::Error::custom("lazy elements can't be serialized"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lazy;
+    use java::{imported, local};
+    use {Element, Error, ErrorKind, Java, Tokens};
+
+    #[test]
+    fn test_qualify_conflicting() {
+        // Qualify `java.awt.List` only if `List` has been imported from another package.
+        let list = Lazy::new(|_, extra: &mut ::java::Extra, _| {
+            let name = match extra.imported("List") {
+                Some(package) if package != "java.awt" => "java.awt.List",
+                _ => "List",
+            };
+
+            Ok(toks![name])
+        });
+
+        let mut toks: Tokens<Java> = Tokens::new();
+        toks.push(toks![imported("java.util", "List"), " a;"]);
+        toks.push(toks![Element::from(list), " b;"]);
+
+        assert_eq!(
+            "import java.util.List;\n\nList a;\njava.awt.List b;\n",
+            toks.to_file().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_language() {
+        // Produced tokens are formatted with the quoting, comments, and types of the language.
+        let mut toks: Tokens<Java> = Tokens::new();
+        toks.lazy(|_, _, _| {
+            let mut out = Tokens::new();
+            out.append(local("Foo"));
+            out.append(Element::Quoted("a\"b".into()));
+            out.push(Element::LineComment(vec!["c".into()]));
+            Ok(out)
+        });
+
+        assert_eq!("Foo\"a\\\"b\"\n// c", toks.to_string().unwrap());
+    }
+
+    #[test]
+    fn test_error() {
+        let mut toks: Tokens<Java> = Tokens::new();
+        toks.lazy(|_, _, _| Err(Error::custom("not known")));
+
+        let error = toks.to_string().unwrap_err();
+        assert_eq!(&ErrorKind::Custom("not known".into()), error.kind());
+    }
+
+    #[test]
+    fn test_group() {
+        // Output written directly to the formatter is kept inside of a group.
+        let mut args: Tokens<Java> = Tokens::new();
+        args.lazy(|out, _, _| {
+            out.write_str("x")?;
+            Ok(Tokens::new())
+        });
+
+        let mut toks: Tokens<Java> = Tokens::new();
+        toks.append("f");
+        toks.append(args.join_group("(", ",", ")"));
+
+        assert_eq!("f(x)", toks.to_string().unwrap());
+    }
+
+    #[test]
+    fn test_covariant() {
+        fn shorten<'a>(toks: Tokens<'static, Java<'static>>) -> Tokens<'a, Java<'a>> {
+            toks
+        }
+
+        let mut toks: Tokens<Java> = Tokens::new();
+        toks.lazy(|_, _, _| Ok(toks!["a"]));
+
+        assert_eq!("a", shorten(toks).to_string().unwrap());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use serde_json;
+
+        let mut toks: Tokens<Java> = Tokens::new();
+        toks.lazy(|_, _, _| Ok(Tokens::new()));
+
+        let error = serde_json::to_string(&toks).unwrap_err();
+        assert_eq!("lazy elements can't be serialized", error.to_string());
+    }
+}
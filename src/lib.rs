@@ -1,44 +1,127 @@
 //! ## Simple and flexible code generator (rsgen)
 #![deny(missing_docs)]
 
+#[cfg(feature = "quote")]
+extern crate rstgen_macros;
+// Allows code generated by `quote!` to refer to the crate by name.
+extern crate self as rstgen;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
+
 #[macro_use]
 mod macros;
+mod comment;
 mod con_;
 mod cons;
 pub mod csharp;
 mod custom;
 pub mod dart;
+pub mod debug;
 mod element;
+mod error;
 mod formatter;
 pub mod go;
+pub mod ident;
+pub mod imports;
+mod into_owned;
 mod into_tokens;
 pub mod java;
 pub mod js;
+pub mod lazy;
+pub mod naming;
+pub mod project;
 pub mod python;
 mod quoted;
+mod region;
 pub mod rust;
+pub mod shared;
+mod slot;
+pub mod source_map;
 pub mod swift;
+pub mod syntax;
+pub mod template;
+#[cfg(feature = "testing")]
+pub mod testing;
 mod tokens;
+pub mod value;
+pub mod visit;
 mod write_tokens;
 
+pub use self::comment::CommentStyle;
 pub use self::cons::Cons;
 pub use self::csharp::Csharp;
 pub use self::custom::Custom;
 pub use self::dart::Dart;
 pub use self::element::Element;
-pub use self::formatter::{Formatter, IoFmt};
+pub use self::error::{Error, ErrorKind, Result};
+pub use self::formatter::{Formatter, FormatterConfig, Indentation, IoFmt, LineEnding};
 pub use self::go::Go;
+pub use self::into_owned::IntoOwned;
 pub use self::into_tokens::IntoTokens;
 pub use self::java::Java;
 pub use self::js::JavaScript;
+pub use self::lazy::Lazy;
+pub use self::project::{OrphanedRegion, Project, ProjectFile, WriteReport};
 pub use self::python::Python;
 pub use self::quoted::Quoted;
+pub use self::region::Regions;
 pub use self::rust::Rust;
+pub use self::shared::Rc;
+pub use self::slot::{Slot, SlotRef};
 pub use self::tokens::Tokens;
+pub use self::value::Value;
 pub use self::write_tokens::WriteTokens;
+/// Requires the `quote` feature.
+///
+/// ## Example
+///
+/// ```rust
+/// use rstgen::{quote, Java, Tokens};
+///
+/// let fields = vec!["a", "b"];
+///
+/// let toks: Tokens<Java> = quote! {
+///     class Foo {
+///         #(for field in &fields {
+///             int #(*field);
+///         })
+///
+///         Foo(#(for field in &fields join (, ) { int #(*field) })) {}
+///     }
+/// };
+///
+/// assert_eq!(
+///     "class Foo {\n  int a;\n  int b;\n\n  Foo(int a, int b) {}\n}",
+///     toks.to_string().unwrap()
+/// );
+/// ```
+///
+/// Malformed repetitions and conditionals are reported as compile errors:
+///
+/// ```compile_fail
+/// use rstgen::{quote, Java, Tokens};
+///
+/// let fields = vec!["a", "b"];
+/// let toks: Tokens<Java> = quote!(#(for field &fields { #(*field) }));
+/// ```
+///
+/// ```compile_fail
+/// use rstgen::{quote, Java, Tokens};
+///
+/// let toks: Tokens<Java> = quote!(#(if true { a } else b));
+/// ```
+#[cfg(feature = "quote")]
+pub use rstgen_macros::quote;
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "quote")]
+    use java::{imported, Java};
+    #[cfg(feature = "quote")]
+    use quote;
     use rust::Rust;
     use tokens::Tokens;
 
@@ -52,4 +135,72 @@ mod tests {
         let output = toks.to_string().unwrap();
         assert_eq!("fn foo() -> u32 {\n  return 42;\n}", output.as_str());
     }
+
+    #[cfg(feature = "quote")]
+    #[test]
+    fn test_quote() {
+        let list = imported("java.util", "List");
+        let name = "Foo";
+
+        let toks: Tokens<Java> = quote! {
+            public class #name {
+                private #list<String> items;
+
+                public #name() {
+                    this.items = new ArrayList<>();
+                }
+            }
+        };
+
+        assert_eq!(
+            concat!(
+                "import java.util.List;\n",
+                "\n",
+                "public class Foo {\n",
+                "  private List<String> items;\n",
+                "\n",
+                "  public Foo() {\n",
+                "    this.items = new ArrayList<>();\n",
+                "  }\n",
+                "}\n",
+            ),
+            toks.to_file().unwrap()
+        );
+    }
+
+    #[cfg(feature = "quote")]
+    #[test]
+    fn test_quote_control_flow() {
+        let methods = [("a", true), ("b", false)];
+
+        let toks: Tokens<Rust> = quote! {
+            impl Foo {
+                #(for (name, public) in methods.iter().cloned() {
+                    #(if public { pub }) fn #name(#(for n in 0..2 join (, ) { _: u#(n.to_string()) })) {
+                        #(if public {
+                            println!(##public);
+                        } else if name == "c" {
+                            unreachable!();
+                        } else {
+                            println!(##private);
+                        })
+                    }
+                })
+            }
+        };
+
+        assert_eq!(
+            concat!(
+                "impl Foo {\n",
+                "  pub fn a(_: u0, _: u1) {\n",
+                "    println!(#public);\n",
+                "  }\n",
+                "  fn b(_: u0, _: u1) {\n",
+                "    println!(#private);\n",
+                "  }\n",
+                "}",
+            ),
+            toks.to_string().unwrap()
+        );
+    }
 }
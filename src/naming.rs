@@ -0,0 +1,232 @@
+//! Naming conventions, and conversion of names between them.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rstgen::naming::Case;
+//!
+//! assert_eq!("userId", Case::Camel.convert("user_id"));
+//! assert_eq!("HttpServer", Case::Pascal.convert("HTTPServer"));
+//! assert_eq!("MAX_SIZE", Case::ScreamingSnake.convert("maxSize"));
+//! ```
+
+use shared::Rc;
+use std::borrow::Cow;
+use Cons;
+
+/// Casing of a name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Case {
+    /// `snake_case`.
+    Snake,
+    /// `camelCase`.
+    Camel,
+    /// `PascalCase`.
+    Pascal,
+    /// `SCREAMING_SNAKE_CASE`.
+    ScreamingSnake,
+    /// `kebab-case`.
+    Kebab,
+}
+
+impl Case {
+    /// Convert the given name to this case.
+    ///
+    /// The name is split into words on separators and changes of case, where a run of capital
+    /// letters is treated as a single acronym, like `HTTP` in `HTTPServer`.
+    pub fn convert(&self, name: &str) -> String {
+        let words = words(name);
+
+        match *self {
+            Case::Snake => join(&words, "_", str::to_lowercase),
+            Case::Kebab => join(&words, "-", str::to_lowercase),
+            Case::ScreamingSnake => join(&words, "_", str::to_uppercase),
+            Case::Pascal => join(&words, "", capitalize),
+            Case::Camel => {
+                let mut out = String::new();
+
+                for (n, word) in words.iter().enumerate() {
+                    if n == 0 {
+                        out.push_str(&word.to_lowercase());
+                    } else {
+                        out.push_str(&capitalize(word));
+                    }
+                }
+
+                out
+            }
+        }
+    }
+}
+
+/// Naming conventions of a language.
+///
+/// Arguments and local variables follow the same convention as fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Naming {
+    /// Case of type names.
+    pub type_name: Case,
+    /// Case of fields, arguments, and variables.
+    pub field: Case,
+    /// Case of methods and functions.
+    pub method: Case,
+    /// Case of constants.
+    pub constant: Case,
+}
+
+impl Default for Naming {
+    fn default() -> Self {
+        Naming {
+            type_name: Case::Pascal,
+            field: Case::Camel,
+            method: Case::Camel,
+            constant: Case::ScreamingSnake,
+        }
+    }
+}
+
+/// A name given to a model, which is either used verbatim or converted to the naming convention
+/// of the language.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Identifier<'el> {
+    /// A name which is used as-is.
+    Verbatim(Cons<'el>),
+    /// A name which is converted to the naming convention of the language.
+    Raw(Cons<'el>),
+}
+
+impl<'el> Identifier<'el> {
+    /// Get the name, converting it to the given case if it is raw.
+    pub fn convert(self, case: Case) -> Cons<'el> {
+        match self {
+            Identifier::Verbatim(name) => name,
+            Identifier::Raw(name) => Cons::from(case.convert(&name)),
+        }
+    }
+}
+
+impl<'el> From<Cons<'el>> for Identifier<'el> {
+    fn from(value: Cons<'el>) -> Self {
+        Identifier::Verbatim(value)
+    }
+}
+
+impl<'el> From<&'el str> for Identifier<'el> {
+    fn from(value: &'el str) -> Self {
+        Identifier::Verbatim(value.into())
+    }
+}
+
+impl<'el> From<String> for Identifier<'el> {
+    fn from(value: String) -> Self {
+        Identifier::Verbatim(value.into())
+    }
+}
+
+impl<'el> From<Rc<String>> for Identifier<'el> {
+    fn from(value: Rc<String>) -> Self {
+        Identifier::Verbatim(value.into())
+    }
+}
+
+impl<'el> From<Cow<'el, str>> for Identifier<'el> {
+    fn from(value: Cow<'el, str>) -> Self {
+        Identifier::Verbatim(value.into())
+    }
+}
+
+/// Construct a raw name, which is converted to the naming convention of the language.
+pub fn raw<'el, N: Into<Cons<'el>>>(name: N) -> Identifier<'el> {
+    Identifier::Raw(name.into())
+}
+
+/// Split a name into words.
+fn words(name: &str) -> Vec<&str> {
+    let chars = name.char_indices().collect::<Vec<_>>();
+
+    let mut words = Vec::new();
+    let mut start = None;
+
+    for (i, &(n, c)) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if let Some(s) = start.take() {
+                words.push(&name[s..n]);
+            }
+
+            continue;
+        }
+
+        let s = match start {
+            Some(s) => s,
+            None => {
+                start = Some(n);
+                continue;
+            }
+        };
+
+        let prev = chars[i - 1].1;
+        let next = chars.get(i + 1).map(|&(_, c)| c);
+
+        let boundary = c.is_uppercase()
+            && (prev.is_lowercase()
+                || prev.is_numeric()
+                || (prev.is_uppercase() && next.map(char::is_lowercase).unwrap_or(false)));
+
+        if boundary {
+            words.push(&name[s..n]);
+            start = Some(n);
+        }
+    }
+
+    if let Some(s) = start {
+        words.push(&name[s..]);
+    }
+
+    words
+}
+
+fn join<F>(words: &[&str], separator: &str, f: F) -> String
+where
+    F: Fn(&str) -> String,
+{
+    words
+        .iter()
+        .map(|w| f(w))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{words, Case};
+
+    #[test]
+    fn test_words() {
+        assert_eq!(vec!["user", "id"], words("user_id"));
+        assert_eq!(vec!["HTTP", "Server"], words("HTTPServer"));
+        assert_eq!(vec!["user", "ID"], words("userID"));
+        assert_eq!(vec!["v2", "Api", "Key"], words("v2ApiKey"));
+        assert_eq!(vec!["content", "type"], words("content-type"));
+    }
+
+    #[test]
+    fn test_convert() {
+        assert_eq!("user_id", Case::Snake.convert("userID"));
+        assert_eq!("userId", Case::Camel.convert("user_id"));
+        assert_eq!("ParseXmlFile", Case::Pascal.convert("parseXMLFile"));
+        assert_eq!("MAX_SIZE", Case::ScreamingSnake.convert("max-size"));
+        assert_eq!("http-server", Case::Kebab.convert("HTTPServer"));
+    }
+}
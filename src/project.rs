@@ -0,0 +1,796 @@
+//! Generate a set of files into a directory.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+#[cfg(feature = "sync")]
+use std::{panic, thread};
+use {Custom, Error, FormatterConfig, IoFmt, Regions, Result, Tokens, WriteTokens};
+
+/// A single file which is part of a project.
+pub struct ProjectFile<'el, C: Custom> {
+    /// Path of the file, relative to the root of the project.
+    pub path: PathBuf,
+    /// Tokens making up the file.
+    pub tokens: Tokens<'el, C>,
+    /// Extra data used when formatting the file.
+    pub extra: C::Extra,
+}
+
+/// A set of files which are written to a root directory together.
+///
+/// All files are rendered before anything is written, so a failure to render one file leaves the
+/// root directory untouched.
+pub struct Project<'el, C: Custom> {
+    /// Files that are part of the project.
+    pub files: Vec<ProjectFile<'el, C>>,
+    /// Configuration used when formatting files.
+    pub config: FormatterConfig,
+}
+
+impl<'el, C: Custom> Project<'el, C> {
+    /// Create a new, empty project using the default configuration of the language.
+    pub fn new() -> Project<'el, C> {
+        Project {
+            files: Vec::new(),
+            config: C::default_config(),
+        }
+    }
+
+    /// Use the given formatter configuration for all files.
+    pub fn config(self, config: FormatterConfig) -> Project<'el, C> {
+        Project { config, ..self }
+    }
+
+    /// Add a file at the given path, relative to the root of the project.
+    ///
+    /// Fails if the path is absolute, or refers to a parent directory. Components referring to
+    /// the current directory are removed, so `./a.txt` and `a.txt` are the same file.
+    pub fn file<P>(&mut self, path: P, tokens: Tokens<'el, C>, extra: C::Extra) -> Result<()>
+    where
+        P: Into<PathBuf>,
+    {
+        let path = path.into();
+
+        if !is_relative(&path) {
+            return Err(Error::invalid_path(path.display().to_string()));
+        }
+
+        let path = normalize(&path);
+
+        self.files.push(ProjectFile {
+            path,
+            tokens,
+            extra,
+        });
+
+        Ok(())
+    }
+
+    /// Add a file declaring the given name.
+    ///
+    /// The path of the file is derived from the conventions of the language, see
+    /// [`Custom::file_path`].
+    pub fn named(&mut self, name: &str, tokens: Tokens<'el, C>, extra: C::Extra) -> Result<()> {
+        let path = C::file_path(name, &extra);
+        self.file(path, tokens, extra)
+    }
+
+    /// List the files that would be written to the given root directory, without writing
+    /// anything.
+    pub fn dry_run<P: AsRef<Path>>(&self, root: P) -> Vec<PathBuf> {
+        let root = root.as_ref();
+        self.files.iter().map(|f| root.join(&f.path)).collect()
+    }
+
+    /// Render all files, returning their paths and contents.
+    pub fn render(self) -> Result<Vec<(PathBuf, Vec<u8>)>> {
+        Ok(self
+            .render_in(None)?
+            .into_iter()
+            .map(|file| (file.path, file.content))
+            .collect())
+    }
+
+    /// Render all files, carrying over user regions from existing files in the given root
+    /// directory.
+    fn render_in(self, root: Option<&Path>) -> Result<Vec<Rendered>> {
+        check_paths(&self.files)?;
+
+        let config = self.config;
+
+        self.files
+            .into_iter()
+            .map(|file| render_file(file, root, &config))
+            .collect()
+    }
+
+    /// Render all files and write them to the given root directory.
+    ///
+    /// Files are only written if their content changed. Each file is first written to a temporary
+    /// file next to its destination, and only once every file has been written are they moved
+    /// into place. If moving a file fails, the files which were already moved are restored.
+    ///
+    /// Generated files are recorded in a manifest in the root directory, named by [`MANIFEST`].
+    /// Files recorded by a previous run which are no longer generated are deleted.
+    ///
+    /// The content of user regions in existing files is carried over. Regions which no longer
+    /// exist in the generated file are reported as orphaned.
+    pub fn write<P: AsRef<Path>>(self, root: P) -> Result<WriteReport> {
+        let root = root.as_ref();
+        let rendered = self.render_in(Some(root))?;
+        write_rendered(root, rendered)
+    }
+}
+
+#[cfg(feature = "sync")]
+impl<'el, C: Custom> Project<'el, C>
+where
+    C: Send + Sync,
+    C::Extra: Send,
+{
+    /// Render all files in parallel, returning their paths and contents.
+    ///
+    /// Files are split between one thread per available core, and are returned in the order
+    /// they were added, like with [`Project::render`].
+    pub fn render_parallel(self) -> Result<Vec<(PathBuf, Vec<u8>)>> {
+        Ok(self
+            .render_parallel_in(None)?
+            .into_iter()
+            .map(|file| (file.path, file.content))
+            .collect())
+    }
+
+    /// Render all files in parallel and write them to the given root directory.
+    ///
+    /// Only rendering happens in parallel, files are written like with [`Project::write`].
+    pub fn write_parallel<P: AsRef<Path>>(self, root: P) -> Result<WriteReport> {
+        let root = root.as_ref();
+        let rendered = self.render_parallel_in(Some(root))?;
+        write_rendered(root, rendered)
+    }
+
+    /// Render all files in parallel, carrying over user regions from existing files in the
+    /// given root directory.
+    fn render_parallel_in(self, root: Option<&Path>) -> Result<Vec<Rendered>> {
+        check_paths(&self.files)?;
+
+        let Project { mut files, config } = self;
+
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        let size = files.len().div_ceil(threads).max(1);
+
+        let mut chunks = Vec::new();
+
+        while !files.is_empty() {
+            let rest = files.split_off(size.min(files.len()));
+            chunks.push(files);
+            files = rest;
+        }
+
+        let config = &config;
+
+        thread::scope(|scope| {
+            let handles = chunks
+                .into_iter()
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .into_iter()
+                            .map(|file| render_file(file, root, config))
+                            .collect::<Result<Vec<_>>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            let mut rendered = Vec::new();
+
+            for handle in handles {
+                match handle.join() {
+                    Ok(chunk) => rendered.extend(chunk?),
+                    Err(panic) => panic::resume_unwind(panic),
+                }
+            }
+
+            Ok(rendered)
+        })
+    }
+}
+
+/// Check that no file has been added more than once.
+fn check_paths<C: Custom>(files: &[ProjectFile<C>]) -> Result<()> {
+    let mut seen = HashSet::new();
+
+    for file in files {
+        if !seen.insert(normalize(&file.path)) {
+            return Err(Error::custom("file added more than once")
+                .with_context(file.path.display().to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that the given path is relative to the root of a project, without leaving it.
+fn is_relative(path: &Path) -> bool {
+    path.components().any(|c| matches!(c, Component::Normal(_)))
+        && path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+/// Keep only the normal components of the given path, dropping references to the current
+/// directory.
+fn normalize(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|c| matches!(c, Component::Normal(_)))
+        .collect()
+}
+
+/// Render a single file, carrying over user regions from an existing file in the given root
+/// directory.
+fn render_file<C: Custom>(
+    mut file: ProjectFile<C>,
+    root: Option<&Path>,
+    config: &FormatterConfig,
+) -> Result<Rendered> {
+    let context = file.path.display().to_string();
+
+    let existing = match root {
+        Some(root) => read_existing(&root.join(&file.path))?,
+        None => None,
+    };
+
+    let mut regions = match existing {
+        Some(ref existing) => {
+            let existing = String::from_utf8_lossy(existing);
+            Regions::parse(&existing, C::line_comment())
+                .map_err(|e| e.with_context(context.clone()))?
+        }
+        None => Regions::new(),
+    };
+
+    let mut content = Vec::new();
+
+    IoFmt(&mut content)
+        .write_file_with_regions(&file.tokens, &mut file.extra, config.clone(), &mut regions)
+        .map_err(|e| e.with_context(context))?;
+
+    let orphaned = regions
+        .orphaned()
+        .into_iter()
+        .map(|(name, content)| (name.to_string(), content))
+        .collect();
+
+    Ok(Rendered {
+        path: file.path,
+        content,
+        existing,
+        orphaned,
+    })
+}
+
+/// Write rendered files to the given root directory, and update the manifest.
+fn write_rendered(root: &Path, rendered: Vec<Rendered>) -> Result<WriteReport> {
+    let manifest_path = root.join(MANIFEST);
+    let previous = read_manifest(&manifest_path)?;
+
+    let mut report = WriteReport::default();
+    let mut manifest = String::new();
+    let mut staged = Vec::new();
+
+    for file in &rendered {
+        manifest.push_str(&manifest_entry(&file.path));
+        manifest.push('\n');
+
+        let path = root.join(&file.path);
+
+        for (name, content) in &file.orphaned {
+            report.orphaned.push(OrphanedRegion {
+                path: path.clone(),
+                name: name.clone(),
+                content: content.clone(),
+            });
+        }
+
+        match file.existing {
+            Some(ref existing) if *existing == file.content => {
+                report.unchanged.push(path);
+                continue;
+            }
+            Some(_) => report.updated.push(path.clone()),
+            None => report.created.push(path.clone()),
+        }
+
+        staged.push((path, file.content.as_slice()));
+    }
+
+    if fs::read(&manifest_path).ok().as_deref() != Some(manifest.as_bytes()) {
+        staged.push((manifest_path, manifest.as_bytes()));
+    }
+
+    let mut temps = Vec::with_capacity(staged.len());
+
+    for (path, content) in staged {
+        match stage(&path, content) {
+            Ok(temp) => temps.push((temp, path)),
+            Err(e) => {
+                for (temp, _) in temps {
+                    let _ = fs::remove_file(temp);
+                }
+
+                return Err(e);
+            }
+        }
+    }
+
+    let mut moved = Vec::with_capacity(temps.len());
+    let mut temps = temps.into_iter();
+
+    while let Some((temp, path)) = temps.next() {
+        match replace(&temp, &path) {
+            Ok(backup) => moved.push((path, backup)),
+            Err(e) => {
+                let _ = fs::remove_file(temp);
+
+                for (temp, _) in temps {
+                    let _ = fs::remove_file(temp);
+                }
+
+                restore(moved);
+                return Err(with_path(e.into(), &path));
+            }
+        }
+    }
+
+    for (_, backup) in moved {
+        if let Some(backup) = backup {
+            let _ = fs::remove_file(backup);
+        }
+    }
+
+    let current = rendered
+        .iter()
+        .map(|file| normalize(&file.path))
+        .collect::<HashSet<_>>();
+
+    for relative in previous {
+        if current.contains(&relative) {
+            continue;
+        }
+
+        let path = root.join(relative);
+
+        match fs::remove_file(&path) {
+            Ok(()) => report.deleted.push(path),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(with_path(e.into(), &path)),
+        }
+    }
+
+    Ok(report)
+}
+
+/// Name of the manifest recording generated files, stored in the root directory of a project.
+pub const MANIFEST: &str = ".rstgen-manifest";
+
+/// Report of which files were affected when writing a project.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct WriteReport {
+    /// Files which did not exist and were created.
+    pub created: Vec<PathBuf>,
+    /// Files which existed with different content and were rewritten.
+    pub updated: Vec<PathBuf>,
+    /// Files which already had the generated content and were left alone.
+    pub unchanged: Vec<PathBuf>,
+    /// Files which were generated by a previous run, but are no longer generated.
+    pub deleted: Vec<PathBuf>,
+    /// User regions in existing files which have no counterpart in the generated files.
+    pub orphaned: Vec<OrphanedRegion>,
+}
+
+/// A user region which was present in an existing file, but not in the generated file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrphanedRegion {
+    /// Path of the file which contained the region.
+    pub path: PathBuf,
+    /// Name of the region.
+    pub name: String,
+    /// Content of the region, which was not carried over.
+    pub content: String,
+}
+
+/// A rendered file.
+struct Rendered {
+    /// Path of the file, relative to the root of the project.
+    path: PathBuf,
+    /// Rendered content of the file.
+    content: Vec<u8>,
+    /// Content of the existing file, if any.
+    existing: Option<Vec<u8>>,
+    /// User regions of the existing file which were not carried over.
+    orphaned: Vec<(String, String)>,
+}
+
+/// Read the existing file at the given path, if it exists.
+fn read_existing(path: &Path) -> Result<Option<Vec<u8>>> {
+    match fs::read(path) {
+        Ok(existing) => Ok(Some(existing)),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(with_path(e.into(), path)),
+    }
+}
+
+impl<'el, C: Custom> Default for Project<'el, C> {
+    fn default() -> Self {
+        Project::new()
+    }
+}
+
+/// Read the paths recorded in the manifest, if it exists.
+fn read_manifest(path: &Path) -> Result<Vec<PathBuf>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(with_path(e.into(), path)),
+    };
+
+    let mut paths = Vec::new();
+
+    for line in content.lines().filter(|line| !line.is_empty()) {
+        let relative = PathBuf::from(line);
+
+        // never delete anything outside of the root directory.
+        if !is_relative(&relative) {
+            return Err(with_path(Error::invalid_path(line), path));
+        }
+
+        paths.push(normalize(&relative));
+    }
+
+    Ok(paths)
+}
+
+/// Manifest entry for the given relative path, using `/` as separator on all platforms.
+fn manifest_entry(path: &Path) -> String {
+    normalize(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Write the content to a temporary file next to the given path.
+fn stage(path: &Path, content: &[u8]) -> Result<PathBuf> {
+    let name = match path.file_name() {
+        Some(name) => name.to_string_lossy(),
+        None => return Err(with_path(Error::custom("missing file name"), path)),
+    };
+
+    let temp = path.with_file_name(format!(".{}.tmp", name));
+
+    let result = (|| {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut f = fs::File::create(&temp)?;
+        f.write_all(content)?;
+        f.sync_all()
+    })();
+
+    match result {
+        Ok(()) => Ok(temp),
+        Err(e) => {
+            let _ = fs::remove_file(&temp);
+            Err(with_path(e.into(), path))
+        }
+    }
+}
+
+/// Move the temporary file into place, keeping a backup of the file it replaces.
+fn replace(temp: &Path, path: &Path) -> io::Result<Option<PathBuf>> {
+    let backup = match path.file_name() {
+        Some(name) if path.exists() => {
+            let backup = path.with_file_name(format!(".{}.bak", name.to_string_lossy()));
+            fs::rename(path, &backup)?;
+            Some(backup)
+        }
+        _ => None,
+    };
+
+    if let Err(e) = fs::rename(temp, path) {
+        if let Some(ref backup) = backup {
+            let _ = fs::rename(backup, path);
+        }
+
+        return Err(e);
+    }
+
+    Ok(backup)
+}
+
+/// Restore the files which were replaced, in reverse order, removing files which didn't exist.
+fn restore(moved: Vec<(PathBuf, Option<PathBuf>)>) {
+    for (path, backup) in moved.into_iter().rev() {
+        let _ = match backup {
+            Some(backup) => fs::rename(backup, &path),
+            None => fs::remove_file(&path),
+        };
+    }
+}
+
+fn with_path(error: Error, path: &Path) -> Error {
+    error.with_context(path.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OrphanedRegion, Project, WriteReport, MANIFEST};
+    use java::{self, Java};
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use {Error, ErrorKind, Formatter, Result, Tokens};
+
+    fn temp_root(name: &str) -> PathBuf {
+        let root = env::temp_dir().join(format!("rstgen-{}-{}", name, ::std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        root
+    }
+
+    #[test]
+    fn test_java_paths() {
+        let mut project: Project<Java> = Project::new();
+        project
+            .named(
+                "Foo",
+                toks!("class Foo {}"),
+                java::Extra::new("com.example"),
+            )
+            .unwrap();
+        project
+            .named("Bar", toks!("class Bar {}"), java::Extra::default())
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                PathBuf::from("root/com/example/Foo.java"),
+                PathBuf::from("root/Bar.java"),
+            ],
+            project.dry_run("root")
+        );
+    }
+
+    #[test]
+    fn test_invalid_paths() {
+        let mut project: Project<()> = Project::new();
+
+        for path in &["/a.txt", "../a.txt", "a/../../b.txt", "", "."] {
+            let error = project.file(*path, toks!("a"), ()).unwrap_err();
+            assert_eq!(&ErrorKind::InvalidPath(path.to_string()), error.kind());
+        }
+
+        project.file("./a/b.txt", toks!("a"), ()).unwrap();
+        assert_eq!(vec![PathBuf::from("root/a/b.txt")], project.dry_run("root"));
+    }
+
+    #[test]
+    fn test_duplicate_paths() {
+        let mut project: Project<()> = Project::new();
+        project.file("a.txt", toks!("a"), ()).unwrap();
+        project.file("./a.txt", toks!("b"), ()).unwrap();
+
+        let error = project.render().unwrap_err();
+        assert_eq!("a.txt: file added more than once", error.to_string());
+    }
+
+    #[test]
+    fn test_write() {
+        let root = temp_root("write");
+
+        let mut project: Project<Java> = Project::new();
+        project
+            .named(
+                "Foo",
+                toks!("class Foo {}"),
+                java::Extra::new("com.example"),
+            )
+            .unwrap();
+        let report = project.write(&root).unwrap();
+
+        let path = root.join("com").join("example").join("Foo.java");
+        assert_eq!(vec![path.clone()], report.created);
+        assert_eq!(
+            "package com.example;\n\nclass Foo {}\n",
+            fs::read_to_string(&path).unwrap()
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_incremental() {
+        let root = temp_root("incremental");
+
+        let project = |files: &[(&'static str, &'static str)]| {
+            let mut project: Project<()> = Project::new();
+
+            for &(path, content) in files {
+                project.file(path, toks!(content), ()).unwrap();
+            }
+
+            project.write(&root).unwrap()
+        };
+
+        let report = project(&[("a.txt", "a"), ("b.txt", "b")]);
+        assert_eq!(vec![root.join("a.txt"), root.join("b.txt")], report.created);
+
+        let report = project(&[("a.txt", "a"), ("c.txt", "c")]);
+        assert_eq!(
+            WriteReport {
+                created: vec![root.join("c.txt")],
+                updated: vec![],
+                unchanged: vec![root.join("a.txt")],
+                deleted: vec![root.join("b.txt")],
+                orphaned: vec![],
+            },
+            report
+        );
+        assert!(!root.join("b.txt").exists());
+
+        let report = project(&[("a.txt", "changed"), ("c.txt", "c")]);
+        assert_eq!(vec![root.join("a.txt")], report.updated);
+        assert_eq!(vec![root.join("c.txt")], report.unchanged);
+        assert_eq!("changed\n", fs::read_to_string(root.join("a.txt")).unwrap());
+        assert_eq!(
+            "a.txt\nc.txt\n",
+            fs::read_to_string(root.join(MANIFEST)).unwrap()
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_incremental_normalized() {
+        let root = temp_root("normalized");
+
+        let project = |path: &'static str| {
+            let mut project: Project<()> = Project::new();
+            project.file(path, toks!("a"), ()).unwrap();
+            project.write(&root).unwrap()
+        };
+
+        project("./a.txt");
+        let report = project("a.txt");
+        assert_eq!(vec![root.join("a.txt")], report.unchanged);
+        assert!(report.deleted.is_empty());
+
+        // manifests recording paths through the current directory are still matched.
+        fs::write(root.join(MANIFEST), "./a.txt\n").unwrap();
+        let report = project("a.txt");
+        assert!(report.deleted.is_empty());
+        assert!(root.join("a.txt").exists());
+        assert_eq!("a.txt\n", fs::read_to_string(root.join(MANIFEST)).unwrap());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_manifest_outside_root() {
+        let root = temp_root("manifest");
+        let outside = temp_root("manifest-outside");
+
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(outside.join("a.txt"), "a").unwrap();
+
+        let escape = format!(
+            "../{}/a.txt",
+            outside.file_name().unwrap().to_string_lossy()
+        );
+        fs::write(root.join(MANIFEST), format!("{}\n", escape)).unwrap();
+
+        let mut project: Project<()> = Project::new();
+        project.file("b.txt", toks!("b"), ()).unwrap();
+        let error = project.write(&root).unwrap_err();
+
+        assert_eq!(&ErrorKind::InvalidPath(escape), error.kind());
+        assert!(outside.join("a.txt").exists());
+        assert!(!root.join("b.txt").exists());
+
+        fs::remove_dir_all(&root).unwrap();
+        fs::remove_dir_all(&outside).unwrap();
+    }
+
+    #[test]
+    fn test_regions() {
+        let root = temp_root("regions");
+
+        let project = |regions: &[&'static str]| {
+            let mut project: Project<()> = Project::new();
+            let mut toks = Tokens::new();
+
+            for region in regions {
+                toks.region(*region, toks!("todo"));
+            }
+
+            project.file("a.txt", toks, ()).unwrap();
+            project.write(&root).unwrap()
+        };
+
+        project(&["first", "second"]);
+        let path = root.join("a.txt");
+
+        let edited = fs::read_to_string(&path).unwrap().replace("todo", "edited");
+        fs::write(&path, edited).unwrap();
+
+        let report = project(&["first"]);
+        assert_eq!(
+            "// rstgen:begin first\nedited\n// rstgen:end first\n",
+            fs::read_to_string(&path).unwrap()
+        );
+        assert_eq!(
+            vec![OrphanedRegion {
+                path: path.clone(),
+                name: String::from("second"),
+                content: String::from("edited"),
+            }],
+            report.orphaned
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_failure_writes_nothing() {
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        struct Failing;
+
+        impl ::Custom for Failing {
+            type Extra = ();
+
+            fn format(&self, _: &mut Formatter, _: &mut (), _: usize) -> Result<()> {
+                Err(Error::custom("not supported"))
+            }
+        }
+
+        let root = temp_root("failure");
+
+        let mut project: Project<Failing> = Project::new();
+        project.file("a.txt", toks!("a"), ()).unwrap();
+        project.file("b.txt", toks!(Failing), ()).unwrap();
+        let error = project.write(&root).unwrap_err();
+
+        assert_eq!("b.txt: not supported", error.to_string());
+        assert!(!root.exists());
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn test_render_parallel() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Tokens<Java>>();
+
+        let project = || {
+            let mut project: Project<Java> = Project::new();
+
+            for n in 0..32 {
+                let name = format!("Foo{}", n);
+                let list = java::imported("java.util", "List");
+                let tokens = toks!("class ", name.clone(), " extends ", list, " {}");
+                project
+                    .named(&name, tokens, java::Extra::new("com.example"))
+                    .unwrap();
+            }
+
+            project
+        };
+
+        assert_eq!(
+            project().render().unwrap(),
+            project().render_parallel().unwrap()
+        );
+    }
+}
@@ -1,13 +1,18 @@
 //! Specialization for Python code generation.
 
+use naming::{Case, Naming};
 use std::collections::BTreeSet;
 use std::fmt::{self, Write};
-use {Cons, Custom, Formatter, IntoTokens, Tokens};
+use std::path::PathBuf;
+use syntax::{Blocks, Construct, Declaration, Exceptions, ForEach, Lambda, Syntax};
+use value::{self, Value};
+use {CommentStyle, Cons, Custom, Formatter, IntoOwned, IntoTokens, Result, Tokens};
 
 static SEP: &'static str = ".";
 
 /// Python token specialization.
 #[derive(Debug, Clone, Hash, PartialOrd, Ord, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Python<'el> {
     /// Module of the imported name.
     module: Option<Cons<'el>>,
@@ -54,8 +59,17 @@ impl<'el> fmt::Display for Python<'el> {
 into_tokens_impl_from!(Python<'el>, Python<'el>);
 into_tokens_impl_from!(&'el Python<'el>, Python<'el>);
 
+/// Extra data for Python formatting.
+#[derive(Debug, Clone, Default)]
+pub struct Extra {
+    /// Interpreter written in a shebang line at the top of the file, like `/usr/bin/env python3`.
+    pub shebang: Option<String>,
+    /// Source encoding declared at the top of the file, like `utf-8`.
+    pub encoding: Option<String>,
+}
+
 impl<'el> Python<'el> {
-    fn imports<'a>(tokens: &'a Tokens<'a, Self>) -> Option<Tokens<'a, Self>> {
+    fn imports<'a>(tokens: &'a Tokens<Self>) -> Option<Tokens<'a, Self>> {
         let mut modules = BTreeSet::new();
 
         for custom in tokens.walk_custom() {
@@ -110,14 +124,35 @@ impl<'el> Python<'el> {
     }
 }
 
+/// Reserved keywords.
+static KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+impl<'el> IntoOwned for Python<'el> {
+    type Owned = Python<'static>;
+
+    fn into_owned(self) -> Python<'static> {
+        Python {
+            module: self.module.into_owned(),
+            alias: self.alias.into_owned(),
+            name: self.name.into_owned(),
+        }
+    }
+}
+
 impl<'el> Custom for Python<'el> {
-    type Extra = ();
+    type Extra = Extra;
 
-    fn format(&self, out: &mut Formatter, _extra: &mut Self::Extra, _level: usize) -> fmt::Result {
-        write!(out, "{}", self)
+    fn format(&self, out: &mut Formatter, _extra: &mut Self::Extra, _level: usize) -> Result<()> {
+        write!(out, "{}", self)?;
+        Ok(())
     }
 
-    fn quote_string(out: &mut Formatter, input: &str) -> fmt::Result {
+    fn quote_string(out: &mut Formatter, input: &str) -> Result<()> {
         out.write_char('"')?;
 
         for c in input.chars() {
@@ -139,20 +174,110 @@ impl<'el> Custom for Python<'el> {
         Ok(())
     }
 
+    fn block_comment() -> CommentStyle {
+        CommentStyle::Line("#")
+    }
+
+    fn doc_comment() -> CommentStyle {
+        CommentStyle::Block {
+            open: "\"\"\"",
+            prefix: "",
+            close: "\"\"\"",
+        }
+    }
+
+    fn literal<'a>(value: Value<'a, Self>) -> Tokens<'a, Self>
+    where
+        Self: 'a,
+    {
+        match value {
+            Value::Null => value::raw("None"),
+            Value::Bool(true) => value::raw("True"),
+            Value::Bool(false) => value::raw("False"),
+            Value::Char(value) => value::literal(Value::String(value.to_string().into())),
+            Value::Float(value) if !value.is_finite() => value::non_finite(
+                value.into(),
+                "float(\"nan\")",
+                "float(\"inf\")",
+                "float(\"-inf\")",
+            ),
+            Value::Double(value) if !value.is_finite() => {
+                value::non_finite(value, "float(\"nan\")", "float(\"inf\")", "float(\"-inf\")")
+            }
+            Value::Tuple(values) => value::tuple(values),
+            value => value::literal(value),
+        }
+    }
+
+    fn syntax() -> Syntax {
+        Syntax {
+            semicolons: false,
+            blocks: Blocks::Indentation,
+            parenthesized: false,
+            declaration: Declaration::Operator("="),
+            and: "and",
+            or: "or",
+            not: "not ",
+            loose_not: true,
+            else_if: "elif",
+            for_each: ForEach {
+                keyword: "for",
+                binding: "",
+                separator: " in ",
+            },
+            lambda: Lambda::Keyword,
+            construct: Construct::Call,
+            exceptions: Some(Exceptions::Except),
+            ..Syntax::default()
+        }
+    }
+
+    fn naming() -> Naming {
+        Naming {
+            type_name: Case::Pascal,
+            field: Case::Snake,
+            method: Case::Snake,
+            constant: Case::ScreamingSnake,
+        }
+    }
+
+    fn keywords() -> &'static [&'static str] {
+        KEYWORDS
+    }
+
+    fn file_path(name: &str, _extra: &Self::Extra) -> PathBuf {
+        PathBuf::from(format!("{}.py", name))
+    }
+
+    fn line_comment() -> &'static str {
+        "#"
+    }
+
     fn write_file<'a>(
-        tokens: Tokens<'a, Self>,
+        tokens: &Tokens<'a, Self>,
         out: &mut Formatter,
         extra: &mut Self::Extra,
         level: usize,
-    ) -> fmt::Result {
+    ) -> Result<()> {
+        if let Some(ref shebang) = extra.shebang {
+            write!(out, "#!{}", shebang)?;
+            out.new_line()?;
+        }
+
+        if let Some(ref encoding) = extra.encoding {
+            write!(out, "# -*- coding: {} -*-", encoding)?;
+            out.new_line()?;
+        }
+
+        out.write_header::<Self>()?;
+
         let mut toks: Tokens<Self> = Tokens::new();
 
-        if let Some(imports) = Self::imports(&tokens) {
+        if let Some(imports) = Self::imports(tokens) {
             toks.push(imports);
         }
 
-        toks.push_ref(&tokens);
-        toks.join_line_spacing().format(out, extra, level)
+        tokens.format_with_preamble(&toks, out, extra, level)
     }
 }
 
@@ -182,9 +307,56 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::{imported, local, Python};
+    use super::{imported, local, Extra, Python};
     use quoted::Quoted;
     use tokens::Tokens;
+    use Custom;
+    use {Element, Value};
+
+    #[test]
+    fn test_comments() {
+        let mut toks: Tokens<Python> = Tokens::new();
+        toks.push(Element::DocComment(vec!["Say \"\"\"hello\"\"\"".into()]));
+        toks.push(Element::LineComment(vec!["todo".into()]));
+        toks.push("pass");
+
+        assert_eq!(
+            "\"\"\"\nSay \"\"\\\"hello\"\"\\\"\n\"\"\"\n# todo\npass",
+            toks.to_string().unwrap().as_str()
+        );
+    }
+
+    #[test]
+    fn test_header() {
+        let mut toks: Tokens<Python> = Tokens::new();
+        toks.push(toks![imported("os")]);
+
+        let extra = Extra {
+            shebang: Some(String::from("/usr/bin/env python3")),
+            encoding: Some(String::from("utf-8")),
+        };
+        let config = Python::default_config().header("Code generated. DO NOT EDIT.");
+
+        assert_eq!(
+            "#!/usr/bin/env python3\n# -*- coding: utf-8 -*-\n# Code generated. DO NOT EDIT.\n\nimport os\n\nos\n",
+            toks.to_file_with_config(extra, config).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_literals() {
+        let toks: Tokens<Python> = toks![Value::Tuple(vec![
+            Value::Bool(true),
+            Value::Char('a'),
+            Value::Map(vec![("a".into(), Value::Null)]),
+            Value::Double(f64::INFINITY),
+        ])];
+
+        assert_eq!(
+            "(True, \"a\", {\"a\": None}, float(\"inf\"))",
+            toks.to_string().unwrap().as_str()
+        );
+    }
 
     #[test]
     fn test_string() {
@@ -2,7 +2,7 @@
 
 use super::cons::Cons;
 use super::element::Element;
-use std::rc::Rc;
+use shared::Rc;
 
 /// Trait to convert types to quoted elements.
 pub trait Quoted<'el> {
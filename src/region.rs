@@ -0,0 +1,174 @@
+//! User regions, which carry hand-written code over when a file is regenerated.
+
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use {Error, Result};
+
+const BEGIN: &str = "rstgen:begin";
+const END: &str = "rstgen:end";
+
+/// Marker comment which starts the named region.
+pub fn begin_marker(comment: &str, name: &str) -> String {
+    format!("{} {} {}", comment, BEGIN, name)
+}
+
+/// Marker comment which ends the named region.
+pub fn end_marker(comment: &str, name: &str) -> String {
+    format!("{} {} {}", comment, END, name)
+}
+
+/// Contents of user regions, carried over from a previously generated file.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Regions {
+    /// Lines of each region, with the indentation of its begin marker stripped.
+    contents: BTreeMap<String, Vec<String>>,
+    /// Regions which have been used while formatting.
+    used: HashSet<String>,
+}
+
+impl Regions {
+    /// Create an empty set of regions.
+    pub fn new() -> Regions {
+        Regions::default()
+    }
+
+    /// Parse the regions of a previously generated file, delimited by marker comments starting
+    /// with the given line comment.
+    pub fn parse(input: &str, comment: &str) -> Result<Regions> {
+        let mut regions = Regions::new();
+        let mut current: Option<(String, &str, Vec<String>)> = None;
+
+        for line in input.lines() {
+            let trimmed = line.trim_start();
+
+            if let Some((name, indent, mut lines)) = current.take() {
+                match marker(trimmed, comment, END) {
+                    Some(end) if end == name => {
+                        regions.insert(name, lines);
+                    }
+                    Some(end) => {
+                        return Err(Error::custom(format!(
+                            "user region `{}` closed by `{}`",
+                            name, end
+                        )));
+                    }
+                    None => {
+                        lines.push(line.strip_prefix(indent).unwrap_or(trimmed).to_string());
+                        current = Some((name, indent, lines));
+                    }
+                }
+
+                continue;
+            }
+
+            if let Some(name) = marker(trimmed, comment, BEGIN) {
+                if regions.contents.contains_key(name) {
+                    return Err(Error::custom(format!("duplicate user region `{}`", name)));
+                }
+
+                let indent = &line[..line.len() - trimmed.len()];
+                current = Some((name.to_string(), indent, Vec::new()));
+            }
+        }
+
+        if let Some((name, _, _)) = current {
+            return Err(Error::custom(format!(
+                "unterminated user region `{}`",
+                name
+            )));
+        }
+
+        Ok(regions)
+    }
+
+    /// Insert the lines of the named region.
+    pub fn insert<N: Into<String>>(&mut self, name: N, lines: Vec<String>) {
+        self.contents.insert(name.into(), lines);
+    }
+
+    /// Take the lines of the named region, marking it as used.
+    pub fn take(&mut self, name: &str) -> Option<Vec<String>> {
+        let lines = self.contents.get(name)?.clone();
+        self.used.insert(name.to_string());
+        Some(lines)
+    }
+
+    /// Regions which have not been used while formatting, together with their content.
+    pub fn orphaned(&self) -> Vec<(&str, String)> {
+        self.contents
+            .iter()
+            .filter(|&(name, _)| !self.used.contains(name))
+            .map(|(name, lines)| (name.as_str(), lines.join("\n")))
+            .collect()
+    }
+}
+
+/// Parse a marker of the given kind, returning the name of the region.
+fn marker<'a>(line: &'a str, comment: &str, kind: &str) -> Option<&'a str> {
+    let rest = line.strip_prefix(comment)?.trim_start();
+    let name = rest.strip_prefix(kind)?;
+
+    if !name.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    Some(name.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Regions;
+    use {FormatterConfig, Tokens, WriteTokens};
+
+    fn class(body: Tokens<'static, ()>) -> Tokens<'static, ()> {
+        let mut toks = Tokens::new();
+        toks.push("class Foo {");
+        toks.nested(body);
+        toks.push("}");
+        toks
+    }
+
+    #[test]
+    fn test_default() {
+        let mut body = Tokens::new();
+        body.region("body", toks!("// todo"));
+
+        assert_eq!(
+            Ok("class Foo {\n  // rstgen:begin body\n  // todo\n  // rstgen:end body\n}"),
+            class(body).to_string().as_ref().map(|s| s.as_str())
+        );
+    }
+
+    #[test]
+    fn test_preserve() {
+        let existing = "class Foo {\n    // rstgen:begin body\n    int a;\n\n      int b;\n    // rstgen:end body\n    // rstgen:begin gone\n    int c;\n    // rstgen:end gone\n}\n";
+        let mut regions = Regions::parse(existing, "//").unwrap();
+
+        let mut body = Tokens::new();
+        body.region("body", toks!("// todo"));
+
+        let mut out = String::new();
+        out.write_file_with_regions(
+            &class(body),
+            &mut (),
+            FormatterConfig::default(),
+            &mut regions,
+        )
+        .unwrap();
+
+        assert_eq!(
+            "class Foo {\n  // rstgen:begin body\n  int a;\n\n    int b;\n  // rstgen:end body\n}\n",
+            out
+        );
+        assert_eq!(vec![("gone", String::from("int c;"))], regions.orphaned());
+    }
+
+    #[test]
+    fn test_unterminated() {
+        let error = Regions::parse("// rstgen:begin body\nfoo\n", "//").unwrap_err();
+        assert_eq!("unterminated user region `body`", error.to_string());
+
+        let error = Regions::parse("# rstgen:begin a\n# rstgen:end b\n", "#").unwrap_err();
+        assert_eq!("user region `a` closed by `b`", error.to_string());
+    }
+}
@@ -1,9 +1,13 @@
 //! Specialization for Rust code generation.
 
+use naming::{Case, Naming};
+use shared::Rc;
 use std::collections::BTreeSet;
-use std::fmt::{self, Write};
-use std::rc::Rc;
-use {Cons, Custom, Formatter, IntoTokens, Tokens};
+use std::fmt::Write;
+use std::path::PathBuf;
+use syntax::{Construct, Declaration, ForEach, Lambda, Syntax};
+use value::{self, Value};
+use {CommentStyle, Cons, Custom, Formatter, IntoOwned, IntoTokens, Result, Tokens};
 
 static SEP: &'static str = "::";
 
@@ -17,6 +21,7 @@ pub struct StaticRef;
 
 /// Reference information about a name.
 #[derive(Debug, Clone, Hash, PartialOrd, Ord, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Reference<'el> {
     /// An anonymous reference.
     Ref,
@@ -52,6 +57,7 @@ impl<'el> From<&'el str> for Reference<'el> {
 
 /// A name.
 #[derive(Debug, Clone, Hash, PartialOrd, Ord, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Name<'el> {
     reference: Option<Reference<'el>>,
     /// Name  of class.
@@ -62,7 +68,7 @@ pub struct Name<'el> {
 
 impl<'el> Name<'el> {
     /// Format the name.
-    fn format(&self, out: &mut Formatter, extra: &mut (), level: usize) -> fmt::Result {
+    fn format(&self, out: &mut Formatter, extra: &mut (), level: usize) -> Result<()> {
         if let Some(reference) = self.reference.as_ref() {
             match *reference {
                 Reference::StaticRef => {
@@ -129,6 +135,7 @@ impl<'el> From<Cons<'el>> for Name<'el> {
 
 /// Rust token specialization.
 #[derive(Debug, Clone, Hash, PartialOrd, Ord, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Rust<'el> {
     /// Module of the imported name.
     module: Option<Cons<'el>>,
@@ -162,7 +169,7 @@ impl<'el> Rust<'el> {
         }
     }
 
-    fn imports<'a>(tokens: &'a Tokens<'a, Self>) -> Option<Tokens<'a, Self>> {
+    fn imports<'a>(tokens: &'a Tokens<Self>) -> Option<Tokens<'a, Self>> {
         let mut modules = BTreeSet::new();
 
         for custom in tokens.walk_custom() {
@@ -229,10 +236,56 @@ impl<'el> Rust<'el> {
     }
 }
 
+/// Reserved keywords.
+static KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "try", "typeof", "unsized", "virtual", "yield",
+];
+
+impl<'el> IntoOwned for Reference<'el> {
+    type Owned = Reference<'static>;
+
+    fn into_owned(self) -> Reference<'static> {
+        match self {
+            Reference::Ref => Reference::Ref,
+            Reference::StaticRef => Reference::StaticRef,
+            Reference::Named(name) => Reference::Named(name.into_owned()),
+        }
+    }
+}
+
+impl<'el> IntoOwned for Name<'el> {
+    type Owned = Name<'static>;
+
+    fn into_owned(self) -> Name<'static> {
+        Name {
+            reference: self.reference.into_owned(),
+            name: self.name.into_owned(),
+            arguments: self.arguments.into_owned(),
+        }
+    }
+}
+
+impl<'el> IntoOwned for Rust<'el> {
+    type Owned = Rust<'static>;
+
+    fn into_owned(self) -> Rust<'static> {
+        Rust {
+            module: self.module.into_owned(),
+            alias: self.alias.into_owned(),
+            name: self.name.into_owned(),
+            qualified: self.qualified,
+        }
+    }
+}
+
 impl<'el> Custom for Rust<'el> {
     type Extra = ();
 
-    fn format(&self, out: &mut Formatter, extra: &mut Self::Extra, level: usize) -> fmt::Result {
+    fn format(&self, out: &mut Formatter, extra: &mut Self::Extra, level: usize) -> Result<()> {
         if let Some(alias) = self.alias.as_ref() {
             out.write_str(alias)?;
             out.write_str(SEP)?;
@@ -244,7 +297,7 @@ impl<'el> Custom for Rust<'el> {
         self.name.format(out, extra, level)
     }
 
-    fn quote_string(out: &mut Formatter, input: &str) -> fmt::Result {
+    fn quote_string(out: &mut Formatter, input: &str) -> Result<()> {
         out.write_char('"')?;
 
         for c in input.chars() {
@@ -263,20 +316,103 @@ impl<'el> Custom for Rust<'el> {
         Ok(())
     }
 
+    fn doc_comment() -> CommentStyle {
+        CommentStyle::Line("///")
+    }
+
+    fn literal<'a>(value: Value<'a, Self>) -> Tokens<'a, Self>
+    where
+        Self: 'a,
+    {
+        match value {
+            Value::Null => value::raw("None"),
+            Value::Long(value) => value::raw(format!("{}i64", value)),
+            Value::Float(value) if !value.is_finite() => value::non_finite(
+                value.into(),
+                "f32::NAN",
+                "f32::INFINITY",
+                "f32::NEG_INFINITY",
+            ),
+            Value::Double(value) if !value.is_finite() => {
+                value::non_finite(value, "f64::NAN", "f64::INFINITY", "f64::NEG_INFINITY")
+            }
+            Value::Float(value) => value::raw(format!("{}f32", value::float(value))),
+            Value::List(values) => value::sequence("vec![", values, "]"),
+            Value::Map(entries) => {
+                let values = entries
+                    .into_iter()
+                    .map(|(key, value)| Value::Tuple(vec![key, value]))
+                    .collect();
+
+                toks![
+                    imported("std::collections", "HashMap"),
+                    value::sequence("::from([", values, "])"),
+                ]
+            }
+            Value::Tuple(values) => value::tuple(values),
+            value => value::literal(value),
+        }
+    }
+
+    fn syntax() -> Syntax {
+        Syntax {
+            parenthesized: false,
+            declaration: Declaration::Keyword {
+                immutable: "let",
+                mutable: "let mut",
+            },
+            for_each: ForEach {
+                keyword: "for",
+                binding: "",
+                separator: " in ",
+            },
+            lambda: Lambda::Pipes,
+            construct: Construct::Associated("::new"),
+            exceptions: None,
+            ..Syntax::default()
+        }
+    }
+
+    fn naming() -> Naming {
+        Naming {
+            type_name: Case::Pascal,
+            field: Case::Snake,
+            method: Case::Snake,
+            constant: Case::ScreamingSnake,
+        }
+    }
+
+    fn keywords() -> &'static [&'static str] {
+        KEYWORDS
+    }
+
+    fn escape_keyword(ident: &str) -> String {
+        match ident {
+            // keywords which can't be used as raw identifiers.
+            "crate" | "self" | "Self" | "super" => format!("{}_", ident),
+            _ => format!("r#{}", ident),
+        }
+    }
+
+    fn file_path(name: &str, _extra: &Self::Extra) -> PathBuf {
+        PathBuf::from(format!("{}.rs", name))
+    }
+
     fn write_file<'a>(
-        tokens: Tokens<'a, Self>,
+        tokens: &Tokens<'a, Self>,
         out: &mut Formatter,
         extra: &mut Self::Extra,
         level: usize,
-    ) -> fmt::Result {
+    ) -> Result<()> {
+        out.write_header::<Self>()?;
+
         let mut toks: Tokens<Self> = Tokens::new();
 
-        if let Some(imports) = Self::imports(&tokens) {
+        if let Some(imports) = Self::imports(tokens) {
             toks.push(imports);
         }
 
-        toks.push_ref(&tokens);
-        toks.join_line_spacing().format(out, extra, level)
+        tokens.format_with_preamble(&toks, out, extra, level)
     }
 }
 
@@ -313,6 +449,19 @@ mod tests {
     use quoted::Quoted;
     use rust::Rust;
     use tokens::Tokens;
+    use Value;
+
+    #[test]
+    fn test_literals() {
+        let mut toks: Tokens<Rust> = Tokens::new();
+        toks.push(Value::Tuple(vec![Value::Null]));
+        toks.push(Value::Map(vec![(1.into(), Value::List(vec![1.5.into()]))]));
+
+        assert_eq!(
+            Ok("use std::collections;\n\n(None,)\ncollections::HashMap::from([(1, vec![1.5])])\n"),
+            toks.to_file().as_ref().map(|s| s.as_str())
+        );
+    }
 
     #[test]
     fn test_string() {
@@ -0,0 +1,80 @@
+//! Shared ownership used by tokens.
+//!
+//! By default, shared values are reference counted using [`std::rc::Rc`]. With the `sync`
+//! feature enabled, [`std::sync::Arc`] is used instead, which makes tokens `Send` and `Sync` if
+//! the language specialization is, so that files can be rendered in parallel.
+//!
+//! Enabling the `sync` feature changes the type of [`Element::Rc`](::Element::Rc) and
+//! [`Cons::Rc`](::Cons::Rc). Since any crate in the dependency graph can enable it, code building
+//! these variants must use the [`rstgen::Rc`](Rc) alias rather than naming [`std::rc::Rc`] or
+//! [`std::sync::Arc`] directly, which fails to compile once the feature is enabled elsewhere.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rstgen::{Cons, Element, Java, Rc};
+//!
+//! let element: Element<Java> = Element::Rc(Rc::new(Element::Literal(Cons::from("a"))));
+//! let name = Cons::Rc(Rc::new(String::from("b")));
+//! ```
+
+#[cfg(not(feature = "sync"))]
+use std::cell::{RefCell, RefMut};
+#[cfg(feature = "sync")]
+use std::sync::{Mutex, MutexGuard};
+
+/// Reference counted pointer used by tokens, like in [`Cons::Rc`](::Cons::Rc).
+#[cfg(not(feature = "sync"))]
+pub use std::rc::Rc;
+
+/// Reference counted pointer used by tokens, like in [`Cons::Rc`](::Cons::Rc).
+#[cfg(feature = "sync")]
+pub use std::sync::Arc as Rc;
+
+/// Bound on closures stored in tokens, which is `Send + Sync` with the `sync` feature enabled.
+#[cfg(not(feature = "sync"))]
+pub trait MaybeSync {}
+
+#[cfg(not(feature = "sync"))]
+impl<T: ?Sized> MaybeSync for T {}
+
+/// Bound on closures stored in tokens, which is `Send + Sync` with the `sync` feature enabled.
+#[cfg(feature = "sync")]
+pub trait MaybeSync: Send + Sync {}
+
+#[cfg(feature = "sync")]
+impl<T: ?Sized + Send + Sync> MaybeSync for T {}
+
+/// Mutable cell, which is locked when shared between threads with the `sync` feature.
+#[derive(Debug)]
+pub(crate) struct Lock<T> {
+    #[cfg(not(feature = "sync"))]
+    inner: RefCell<T>,
+    #[cfg(feature = "sync")]
+    inner: Mutex<T>,
+}
+
+impl<T> Lock<T> {
+    /// Create a new cell with the given value.
+    pub fn new(value: T) -> Lock<T> {
+        Lock {
+            #[cfg(not(feature = "sync"))]
+            inner: RefCell::new(value),
+            #[cfg(feature = "sync")]
+            inner: Mutex::new(value),
+        }
+    }
+
+    /// Access the value of the cell mutably.
+    #[cfg(not(feature = "sync"))]
+    pub fn lock(&self) -> RefMut<'_, T> {
+        self.inner.borrow_mut()
+    }
+
+    /// Access the value of the cell mutably.
+    #[cfg(feature = "sync")]
+    pub fn lock(&self) -> MutexGuard<'_, T> {
+        // The value is only replaced while locked, so it can't be left in an invalid state.
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,539 @@
+//! Named slots, which are filled with tokens after they have been placed.
+//!
+//! A slot is placed in a set of tokens like any other element, and can be filled through any
+//! handle to it until it is rendered, or converted into owned tokens. This makes it possible to
+//! contribute to a part of a file which has already been generated, like helper functions at the
+//! end of the file.
+//!
+//! Tokens only hold a [`SlotRef`] to the slot, which can't fill it. This keeps tokens covariant
+//! over the lifetime and language of their elements, like `Tokens<'static, Java<'static>>` being
+//! usable as `Tokens<'a, Java<'a>>`.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rstgen::{Rust, Slot, Tokens};
+//!
+//! let helpers = Slot::new("helpers");
+//!
+//! let mut file: Tokens<Rust> = Tokens::new();
+//! file.push("fn main() {}");
+//! file.slot(&helpers);
+//!
+//! helpers.push_keyed("helper", "fn helper() {}").unwrap();
+//! helpers.push_keyed("helper", "fn helper() {}").unwrap();
+//!
+//! assert_eq!("fn main() {}\nfn helper() {}", file.to_string().unwrap());
+//! ```
+
+#[cfg(feature = "serde")]
+use serde::ser::SerializeStruct;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use shared::{Lock, Rc};
+use std::collections::HashSet;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+use {Custom, Element, Error, Formatter, IntoTokens, Result, Tokens};
+
+/// A handle to a named slot.
+///
+/// Cloning the handle gives another handle to the same slot. Slots own their contents, since
+/// they outlive the tokens they are placed in.
+pub struct Slot<'el, C: 'el> {
+    inner: Rc<SlotInner<'el, C>>,
+}
+
+/// A reference to a slot placed in a set of tokens, which can only read it.
+///
+/// Unlike a [`Slot`], references are covariant over the lifetime and language of the tokens
+/// in the slot, since they can't add tokens to it.
+pub struct SlotRef<'el, C: 'el> {
+    /// The `SlotInner<'el, C>` of the slot, from a strong reference owned by this reference.
+    ptr: NonNull<()>,
+    marker: PhantomData<Tokens<'el, C>>,
+}
+
+struct SlotInner<'el, C: 'el> {
+    /// Name of the slot.
+    name: String,
+    /// If entries are separated by empty lines.
+    spaced: bool,
+    /// Entries and keys added to the slot.
+    contents: Lock<Contents<'el, C>>,
+}
+
+struct Contents<'el, C: 'el> {
+    /// Entries added to the slot, which are never removed or modified.
+    entries: Vec<Rc<Tokens<'el, C>>>,
+    keys: HashSet<String>,
+    /// If the slot has been rendered or copied, after which it can't be filled.
+    frozen: bool,
+}
+
+impl<'el, C: 'el> SlotInner<'el, C> {
+    /// Entries of the slot.
+    fn entries(&self) -> Vec<&Tokens<'el, C>> {
+        self.contents
+            .lock()
+            .entries
+            .iter()
+            // Entries are shared and never removed, so they live as long as the slot.
+            .map(|entry| unsafe { &*Rc::as_ptr(entry) })
+            .collect()
+    }
+
+    fn is_frozen(&self) -> bool {
+        self.contents.lock().frozen
+    }
+}
+
+impl<'el, C: 'el> Slot<'el, C> {
+    /// Create a new slot with the given name, where every entry is put on a line of its own.
+    pub fn new<N: Into<String>>(name: N) -> Slot<'el, C> {
+        Slot::build(name.into(), false)
+    }
+
+    /// Create a new slot with the given name, where entries are separated by empty lines.
+    pub fn spaced<N: Into<String>>(name: N) -> Slot<'el, C> {
+        Slot::build(name.into(), true)
+    }
+
+    fn build(name: String, spaced: bool) -> Slot<'el, C> {
+        Slot {
+            inner: Rc::new(SlotInner {
+                name,
+                spaced,
+                contents: Lock::new(Contents {
+                    entries: Vec::new(),
+                    keys: HashSet::new(),
+                    frozen: false,
+                }),
+            }),
+        }
+    }
+
+    /// Name of the slot.
+    pub fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    /// Add the given tokens to the slot.
+    ///
+    /// Fails if the slot has already been rendered, or converted into owned tokens.
+    #[track_caller]
+    pub fn push<T>(&self, tokens: T) -> Result<()>
+    where
+        T: IntoTokens<'el, C>,
+    {
+        let mut contents = self.inner.contents.lock();
+        self.check(&contents)?;
+        contents
+            .entries
+            .push(Rc::new(tokens.into_tokens().tracked()));
+        Ok(())
+    }
+
+    /// Add the given tokens to the slot, unless tokens have already been added with the same
+    /// key.
+    ///
+    /// Returns `true` if the tokens were added. Fails if the slot has already been rendered, or
+    /// converted into owned tokens.
+    #[track_caller]
+    pub fn push_keyed<K, T>(&self, key: K, tokens: T) -> Result<bool>
+    where
+        K: Into<String>,
+        T: IntoTokens<'el, C>,
+    {
+        let mut contents = self.inner.contents.lock();
+        self.check(&contents)?;
+
+        if !contents.keys.insert(key.into()) {
+            return Ok(false);
+        }
+
+        contents
+            .entries
+            .push(Rc::new(tokens.into_tokens().tracked()));
+        Ok(true)
+    }
+
+    /// Check if tokens have been added to the slot with the given key.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.inner.contents.lock().keys.contains(key)
+    }
+
+    /// Check if the slot has been rendered, after which it can't be filled.
+    pub fn is_frozen(&self) -> bool {
+        self.inner.is_frozen()
+    }
+
+    /// Get a reference to the slot, to place in a set of tokens.
+    pub fn reference(&self) -> SlotRef<'el, C> {
+        let inner = Rc::into_raw(self.inner.clone());
+
+        SlotRef {
+            // Pointers to reference counted values are never null.
+            ptr: unsafe { NonNull::new_unchecked(inner as *mut ()) },
+            marker: PhantomData,
+        }
+    }
+
+    fn check(&self, contents: &Contents<'el, C>) -> Result<()> {
+        if contents.frozen {
+            return Err(Error::slot_frozen(self.name()));
+        }
+
+        Ok(())
+    }
+}
+
+impl<'el, C: 'el> SlotRef<'el, C> {
+    fn inner(&self) -> &SlotInner<'el, C> {
+        // The slot is kept alive by the strong reference owned by this reference.
+        unsafe { &*(self.ptr.as_ptr() as *const SlotInner<'el, C>) }
+    }
+
+    /// Name of the slot.
+    pub fn name(&self) -> &str {
+        &self.inner().name
+    }
+
+    /// Check if the slot has been rendered, after which it can't be filled.
+    pub fn is_frozen(&self) -> bool {
+        self.inner().is_frozen()
+    }
+
+    /// Entries of the slot, without freezing it.
+    pub(crate) fn entries(&self) -> Vec<&Tokens<'el, C>> {
+        self.inner().entries()
+    }
+
+    /// Copy the slot into a new slot, converting every entry with the given function.
+    ///
+    /// The slot is frozen, since tokens added to it afterwards would not be part of the copy.
+    pub(crate) fn map<'b, D: 'b, F>(&self, mut f: F) -> SlotRef<'b, D>
+    where
+        F: FnMut(&Tokens<'el, C>) -> Tokens<'b, D>,
+    {
+        let inner = self.inner();
+        let slot = Slot::build(inner.name.clone(), inner.spaced);
+
+        let (keys, frozen) = {
+            let mut contents = inner.contents.lock();
+            let frozen = ::std::mem::replace(&mut contents.frozen, true);
+            (contents.keys.clone(), frozen)
+        };
+
+        let entries = inner.entries().into_iter().map(|entry| Rc::new(f(entry)));
+
+        {
+            let mut contents = slot.inner.contents.lock();
+            contents.keys = keys;
+            contents.frozen = frozen;
+            contents.entries = entries.collect();
+        }
+
+        slot.reference()
+    }
+
+    /// Freeze the slot and format its entries.
+    pub(crate) fn format(
+        &self,
+        out: &mut Formatter,
+        extra: &mut C::Extra,
+        level: usize,
+    ) -> Result<()>
+    where
+        C: Custom,
+    {
+        let inner = self.inner();
+        inner.contents.lock().frozen = true;
+
+        for (n, entry) in inner.entries().into_iter().enumerate() {
+            if n > 0 && inner.spaced {
+                out.new_line_unless_empty()?;
+                out.new_line()?;
+            }
+
+            out.new_line_unless_empty()?;
+            entry.format(out, extra, level)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'el, C> Clone for Slot<'el, C> {
+    fn clone(&self) -> Self {
+        Slot {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<'el, C> Clone for SlotRef<'el, C> {
+    fn clone(&self) -> Self {
+        unsafe { Rc::increment_strong_count(self.ptr.as_ptr() as *const SlotInner<'el, C>) };
+
+        SlotRef {
+            ptr: self.ptr,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'el, C> Drop for SlotRef<'el, C> {
+    fn drop(&mut self) {
+        unsafe { drop(Rc::from_raw(self.ptr.as_ptr() as *const SlotInner<'el, C>)) };
+    }
+}
+
+/// References share the slot like handles do, which can only be shared between threads with the
+/// `sync` feature.
+#[cfg(feature = "sync")]
+unsafe impl<'el, C: Send + Sync> Send for SlotRef<'el, C> {}
+
+#[cfg(feature = "sync")]
+unsafe impl<'el, C: Send + Sync> Sync for SlotRef<'el, C> {}
+
+impl<'el, C> fmt::Debug for Slot<'el, C> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Slot")
+            .field("name", &self.inner.name)
+            .finish()
+    }
+}
+
+impl<'el, C> fmt::Debug for SlotRef<'el, C> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("SlotRef")
+            .field("name", &self.inner().name)
+            .finish()
+    }
+}
+
+/// Slots are equal if they are handles to the same slot.
+impl<'el, C> PartialEq for Slot<'el, C> {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl<'el, C> Eq for Slot<'el, C> {}
+
+/// References are equal if they refer to the same slot.
+impl<'el, C> PartialEq for SlotRef<'el, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr == other.ptr
+    }
+}
+
+impl<'el, C> Eq for SlotRef<'el, C> {}
+
+impl<'el, C> From<SlotRef<'el, C>> for Element<'el, C> {
+    fn from(value: SlotRef<'el, C>) -> Self {
+        Element::Slot(value)
+    }
+}
+
+impl<'el, C> From<Slot<'el, C>> for Element<'el, C> {
+    fn from(value: Slot<'el, C>) -> Self {
+        Element::Slot(value.reference())
+    }
+}
+
+impl<'a, 'el, C> From<&'a Slot<'el, C>> for Element<'el, C> {
+    fn from(value: &'a Slot<'el, C>) -> Self {
+        Element::Slot(value.reference())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'el, C> SlotInner<'el, C>
+where
+    C: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let (mut keys, frozen) = {
+            let contents = self.contents.lock();
+            (
+                contents.keys.iter().cloned().collect::<Vec<_>>(),
+                contents.frozen,
+            )
+        };
+
+        keys.sort();
+
+        let mut state = serializer.serialize_struct("Slot", 5)?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("spaced", &self.spaced)?;
+        state.serialize_field("keys", &keys)?;
+        state.serialize_field("entries", &self.entries())?;
+        state.serialize_field("frozen", &frozen)?;
+        state.end()
+    }
+}
+
+/// Slots are serialized with their entries, and deserialized into a new slot.
+#[cfg(feature = "serde")]
+impl<'el, C> Serialize for Slot<'el, C>
+where
+    C: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.inner.serialize(serializer)
+    }
+}
+
+/// References are serialized like slots, and deserialized into a reference to a new slot.
+#[cfg(feature = "serde")]
+impl<'el, C> Serialize for SlotRef<'el, C>
+where
+    C: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.inner().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, 'el, C: 'el> Deserialize<'de> for Slot<'el, C>
+where
+    C: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(bound = "C: Deserialize<'de>")]
+        struct Data<'el, C: 'el> {
+            name: String,
+            spaced: bool,
+            keys: Vec<String>,
+            entries: Vec<Tokens<'el, C>>,
+            frozen: bool,
+        }
+
+        let data = Data::deserialize(deserializer)?;
+        let slot = Slot::build(data.name, data.spaced);
+
+        {
+            let mut contents = slot.inner.contents.lock();
+            contents.keys.extend(data.keys);
+            contents.entries = data.entries.into_iter().map(Rc::new).collect();
+            contents.frozen = data.frozen;
+        }
+
+        Ok(slot)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, 'el, C: 'el> Deserialize<'de> for SlotRef<'el, C>
+where
+    C: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Slot::deserialize(deserializer)?.reference())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Slot, SlotRef};
+    use java::imported;
+    use {ErrorKind, Java, Tokens};
+
+    #[test]
+    fn test_slot() {
+        let helpers = Slot::spaced("helpers");
+
+        let mut file: Tokens<Java> = Tokens::new();
+        file.push("class Foo {");
+        file.nested("int a;");
+        file.push("}");
+        file.slot(&helpers);
+
+        let handle = helpers.clone();
+        let list = imported("java.util", "ArrayList");
+
+        assert!(handle
+            .push_keyed("list", toks!["class Bar extends ", list, " {}"])
+            .unwrap());
+        assert!(!helpers.push_keyed("list", "class Baz {}").unwrap());
+        handle.push("class Baz {}").unwrap();
+
+        assert_eq!(
+            "import java.util.ArrayList;\n\nclass Foo {\n  int a;\n}\nclass Bar extends ArrayList {}\n\nclass Baz {}\n",
+            file.to_file().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_frozen() {
+        let helpers = Slot::new("helpers");
+
+        let mut toks: Tokens<Java> = Tokens::new();
+        toks.slot(&helpers);
+        toks.to_string().unwrap();
+
+        let error = helpers.push("int a;").unwrap_err();
+        assert_eq!(&ErrorKind::SlotFrozen("helpers".into()), error.kind());
+    }
+
+    #[test]
+    fn test_covariant() {
+        fn shorten<'a>(
+            toks: Tokens<'static, Java<'static>>,
+            slot: SlotRef<'static, Java<'static>>,
+        ) -> (Tokens<'a, Java<'a>>, SlotRef<'a, Java<'a>>) {
+            (toks, slot)
+        }
+
+        let helpers = Slot::new("helpers");
+        helpers.push("int a;").unwrap();
+
+        let mut toks: Tokens<Java> = Tokens::new();
+        toks.slot(&helpers);
+
+        let (toks, slot) = shorten(toks, helpers.reference());
+        assert_eq!("int a;", toks.to_string().unwrap());
+        assert_eq!("helpers", slot.name());
+        assert!(slot.is_frozen());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use serde_json;
+
+        let helpers: Slot<Java> = Slot::spaced("helpers");
+        helpers.push_keyed("a", "int a;").unwrap();
+        helpers.push("int b;").unwrap();
+
+        let json = serde_json::to_string(&helpers).unwrap();
+        let slot: Slot<Java> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!("helpers", slot.name());
+        assert!(slot.contains_key("a"));
+
+        let mut toks: Tokens<Java> = Tokens::new();
+        toks.slot(&slot);
+        assert_eq!("int a;\n\nint b;", toks.to_string().unwrap());
+    }
+}
@@ -0,0 +1,509 @@
+//! Source maps from generated output back to the generator.
+//!
+//! Tokens can carry an [`Origin`], which is either a label supplied by the caller, or the
+//! location of the call to [`Tokens::push`](::Tokens::push),
+//! [`Tokens::nested`](::Tokens::nested), or [`Tokens::append`](::Tokens::append) which added them.
+//! Rendering with [`Tokens::to_file_mapped`](::Tokens::to_file_mapped) records the span written for every origin, and
+//! returns a [`SourceMap`] which can be serialized as JSON in the [source map v3] format.
+//!
+//! Output is mapped to the innermost origin enclosing it. Locations inside of this crate, like the
+//! ones captured while converting the models of a language into tokens, are skipped in favor of
+//! the closest enclosing location outside of it.
+//!
+//! [source map v3]: https://sourcemaps.info/spec.html
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rstgen::{Java, Tokens};
+//!
+//! let mut class: Tokens<Java> = Tokens::new();
+//! class.append("class Foo {}");
+//!
+//! let mut toks: Tokens<Java> = Tokens::new();
+//! toks.push(class.with_origin("class"));
+//! toks.push("int a;");
+//!
+//! let (output, map) = toks.to_file_mapped().unwrap();
+//! assert_eq!("class Foo {}\nint a;\n", output);
+//!
+//! let mapping = map.lookup(0, 0).unwrap();
+//! assert_eq!("class", map.sources[mapping.source]);
+//!
+//! let mapping = map.lookup(1, 0).unwrap();
+//! assert!(map.sources[mapping.source].ends_with(".rs"));
+//! ```
+
+use std::fmt;
+use std::panic::Location;
+use std::path::Path;
+use Cons;
+
+/// Where a set of tokens was created.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin<'el> {
+    /// A location in the generator, as captured by `#[track_caller]`.
+    Location(&'static Location<'static>),
+    /// A label supplied by the caller.
+    Label(Cons<'el>),
+}
+
+impl<'el> Origin<'el> {
+    /// Capture the location of the caller.
+    #[track_caller]
+    pub fn caller() -> Origin<'el> {
+        Origin::Location(Location::caller())
+    }
+
+    /// Check if the origin is a location inside of this crate.
+    fn is_internal(&self) -> bool {
+        match *self {
+            Origin::Location(location) => is_crate_source(location.file()),
+            Origin::Label(_) => false,
+        }
+    }
+}
+
+/// Check if the given file, as reported by the compiler, is a source of this crate.
+///
+/// Files of workspace members are reported relative to the workspace root, which is found by
+/// removing the path this file is reported with from its full path.
+fn is_crate_source(file: &str) -> bool {
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let file = Path::new(file);
+
+    let file = if file.is_absolute() {
+        file.to_path_buf()
+    } else {
+        let this = Path::new(file!());
+
+        if this.is_absolute() {
+            return false;
+        }
+
+        // skip `src/source_map.rs` and the directories of the crate in the workspace.
+        let depth = this.components().count() - 2;
+
+        match manifest.ancestors().nth(depth) {
+            Some(root) => root.join(file),
+            None => return false,
+        }
+    };
+
+    file.starts_with(manifest.join("src"))
+}
+
+impl<'el> fmt::Display for Origin<'el> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Origin::Location(location) => location.fmt(f),
+            Origin::Label(ref label) => f.write_str(label.as_ref()),
+        }
+    }
+}
+
+impl<'el> From<&'static Location<'static>> for Origin<'el> {
+    fn from(value: &'static Location<'static>) -> Self {
+        Origin::Location(value)
+    }
+}
+
+impl<'el> From<&'el str> for Origin<'el> {
+    fn from(value: &'el str) -> Self {
+        Origin::Label(value.into())
+    }
+}
+
+impl<'el> From<String> for Origin<'el> {
+    fn from(value: String) -> Self {
+        Origin::Label(value.into())
+    }
+}
+
+impl<'el> From<Cons<'el>> for Origin<'el> {
+    fn from(value: Cons<'el>) -> Self {
+        Origin::Label(value)
+    }
+}
+
+/// A single mapping from a position in the generated output to its origin.
+///
+/// All lines and columns start at zero. Generated columns count UTF-16 code units, like in the
+/// source map v3 format. Original columns are taken from the compiler and count characters, which
+/// only differ on lines with characters outside of the basic multilingual plane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Mapping {
+    /// Line in the generated output.
+    pub generated_line: usize,
+    /// Column in the generated output.
+    pub generated_column: usize,
+    /// Index of the source in [`SourceMap::sources`].
+    pub source: usize,
+    /// Line in the source.
+    pub original_line: usize,
+    /// Column in the source.
+    pub original_column: usize,
+}
+
+/// Mappings from generated output back to the origins of its tokens.
+///
+/// Sources are the files of captured locations, or the labels of tokens.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    /// Name of the generated file.
+    pub file: Option<String>,
+    /// Sources which are mapped to.
+    pub sources: Vec<String>,
+    /// Mappings, ordered by their position in the generated output.
+    pub mappings: Vec<Mapping>,
+}
+
+impl SourceMap {
+    /// Create a new, empty source map.
+    pub fn new() -> SourceMap {
+        SourceMap::default()
+    }
+
+    /// Change the name of the generated file.
+    pub fn file<F: Into<String>>(self, file: F) -> SourceMap {
+        SourceMap {
+            file: Some(file.into()),
+            ..self
+        }
+    }
+
+    /// Find the mapping covering the given line and column of the generated output.
+    pub fn lookup(&self, line: usize, column: usize) -> Option<&Mapping> {
+        self.mappings
+            .iter()
+            .take_while(|m| (m.generated_line, m.generated_column) <= (line, column))
+            .filter(|m| m.generated_line == line)
+            .last()
+    }
+
+    /// Encode the mappings as base64 VLQ segments, like in the `mappings` field of the JSON
+    /// format.
+    pub fn encode_mappings(&self) -> String {
+        let mut out = String::new();
+        let mut line = 0;
+        let mut previous = [0i64; 4];
+
+        for (n, m) in self.mappings.iter().enumerate() {
+            if m.generated_line > line {
+                for _ in line..m.generated_line {
+                    out.push(';');
+                }
+
+                line = m.generated_line;
+                previous[0] = 0;
+            } else if n > 0 {
+                out.push(',');
+            }
+
+            let current = [
+                m.generated_column as i64,
+                m.source as i64,
+                m.original_line as i64,
+                m.original_column as i64,
+            ];
+
+            for (current, previous) in current.iter().zip(previous.iter_mut()) {
+                encode_vlq(&mut out, *current - *previous);
+                *previous = *current;
+            }
+        }
+
+        out
+    }
+
+    /// Serialize the source map as JSON, in the source map v3 format.
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{\"version\":3,");
+
+        if let Some(ref file) = self.file {
+            out.push_str("\"file\":");
+            json_string(&mut out, file);
+            out.push(',');
+        }
+
+        out.push_str("\"sources\":[");
+
+        for (n, source) in self.sources.iter().enumerate() {
+            if n > 0 {
+                out.push(',');
+            }
+
+            json_string(&mut out, source);
+        }
+
+        out.push_str("],\"names\":[],\"mappings\":");
+        json_string(&mut out, &self.encode_mappings());
+        out.push('}');
+        out
+    }
+}
+
+/// A position in a source.
+#[derive(Debug, Clone, Copy)]
+struct Position {
+    source: usize,
+    line: usize,
+    column: usize,
+    internal: bool,
+}
+
+/// Records a source map while formatting.
+#[derive(Debug, Default)]
+pub(crate) struct Recorder {
+    map: SourceMap,
+    /// Positions of the origins being formatted, innermost last.
+    stack: Vec<Position>,
+    /// Current column in the output, in UTF-16 code units.
+    column: usize,
+}
+
+impl Recorder {
+    /// Enter tokens with the given origin.
+    pub fn enter(&mut self, origin: &Origin) {
+        let internal = origin.is_internal();
+
+        if let Some(top) = self.stack.last().cloned() {
+            if internal && !top.internal {
+                self.stack.push(top);
+                return;
+            }
+        }
+
+        let name = match *origin {
+            Origin::Location(location) => location.file(),
+            Origin::Label(ref label) => label.as_ref(),
+        };
+
+        let source = match self.map.sources.iter().position(|s| s == name) {
+            Some(source) => source,
+            None => {
+                self.map.sources.push(name.to_string());
+                self.map.sources.len() - 1
+            }
+        };
+
+        let (line, column) = match *origin {
+            Origin::Location(location) => {
+                (location.line() as usize - 1, location.column() as usize - 1)
+            }
+            Origin::Label(_) => (0, 0),
+        };
+
+        self.stack.push(Position {
+            source,
+            line,
+            column,
+            internal,
+        });
+    }
+
+    /// Exit the innermost origin.
+    pub fn exit(&mut self) {
+        self.stack.pop();
+    }
+
+    /// Track the column of the output, which the given string is written to.
+    pub fn write(&mut self, s: &str) {
+        match s.rfind('\n') {
+            Some(n) => self.column = s[n + 1..].encode_utf16().count(),
+            None => self.column += s.encode_utf16().count(),
+        }
+    }
+
+    /// Record that output is written at the given line and the current column.
+    pub fn record(&mut self, line: usize) {
+        let column = self.column;
+
+        let top = match self.stack.last() {
+            Some(top) => *top,
+            None => return,
+        };
+
+        if let Some(last) = self.map.mappings.last() {
+            if last.generated_line == line
+                && (last.source, last.original_line, last.original_column)
+                    == (top.source, top.line, top.column)
+            {
+                return;
+            }
+        }
+
+        self.map.mappings.push(Mapping {
+            generated_line: line,
+            generated_column: column,
+            source: top.source,
+            original_line: top.line,
+            original_column: top.column,
+        });
+    }
+
+    /// Finish recording.
+    pub fn finish(self) -> SourceMap {
+        self.map
+    }
+}
+
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encode a single value as base64 VLQ.
+fn encode_vlq(out: &mut String, value: i64) {
+    let mut value = if value < 0 {
+        ((-value) << 1) | 1
+    } else {
+        value << 1
+    };
+
+    loop {
+        let mut digit = value & 0b1_1111;
+        value >>= 5;
+
+        if value > 0 {
+            digit |= 0b10_0000;
+        }
+
+        out.push(BASE64[digit as usize] as char);
+
+        if value == 0 {
+            break;
+        }
+    }
+}
+
+/// Write the given string as a JSON string.
+fn json_string(out: &mut String, s: &str) {
+    out.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_crate_source, Mapping, Origin, SourceMap};
+    use java::{imported, Java};
+    use Tokens;
+
+    #[test]
+    fn test_encode_mappings() {
+        let map = SourceMap {
+            file: Some("Foo.java".into()),
+            sources: vec!["a.rs".into(), "b.rs".into()],
+            mappings: vec![
+                Mapping {
+                    generated_line: 0,
+                    generated_column: 0,
+                    source: 0,
+                    original_line: 9,
+                    original_column: 4,
+                },
+                Mapping {
+                    generated_line: 0,
+                    generated_column: 6,
+                    source: 1,
+                    original_line: 2,
+                    original_column: 0,
+                },
+                Mapping {
+                    generated_line: 2,
+                    generated_column: 2,
+                    source: 0,
+                    original_line: 10,
+                    original_column: 4,
+                },
+            ],
+        };
+
+        assert_eq!("AASI,MCPJ;;EDQI", map.encode_mappings());
+        assert_eq!(
+            "{\"version\":3,\"file\":\"Foo.java\",\"sources\":[\"a.rs\",\"b.rs\"],\"names\":[],\"mappings\":\"AASI,MCPJ;;EDQI\"}",
+            map.to_json()
+        );
+    }
+
+    #[test]
+    fn test_mapped() {
+        let mut class: Tokens<Java> = Tokens::new();
+        class.push("class Foo {");
+        class.nested(toks![imported("java.util", "List"), " a;"]);
+        class.push("}");
+
+        let mut toks: Tokens<Java> = Tokens::new();
+        toks.push(class.with_origin("Foo"));
+        let line = line!() + 1;
+        toks.push("int b;");
+
+        let (output, map) = toks.to_file_mapped().unwrap();
+
+        assert_eq!(
+            "import java.util.List;\n\nclass Foo {\n  List a;\n}\nint b;\n",
+            output
+        );
+
+        // Labels are not overridden by the location they were pushed from.
+        let foo = map.lookup(2, 0).unwrap();
+        assert_eq!("Foo", map.sources[foo.source]);
+
+        // Locations inside of this crate are skipped in favor of the enclosing label.
+        let field = map.lookup(3, 4).unwrap();
+        assert_eq!(3, field.generated_line);
+        assert_eq!(2, field.generated_column);
+        assert_eq!("Foo", map.sources[field.source]);
+
+        let b = map.lookup(5, 0).unwrap();
+        assert_eq!(file!(), map.sources[b.source]);
+        assert_eq!(line as usize - 1, b.original_line);
+        assert_eq!(13, b.original_column);
+
+        // Imports are written by the language, outside of the origins of the tokens.
+        let import = map.lookup(0, 0).unwrap();
+        assert!(map.sources[import.source].ends_with("mod.rs"));
+    }
+
+    #[test]
+    fn test_crate_source() {
+        let manifest = env!("CARGO_MANIFEST_DIR");
+
+        assert!(is_crate_source(file!()));
+        assert!(is_crate_source(&format!("{}/src/lib.rs", manifest)));
+        assert!(!is_crate_source(&format!("{}/tests/lib.rs", manifest)));
+        assert!(!is_crate_source(&format!("{}/other/src/lib.rs", manifest)));
+        assert!(!is_crate_source("/other/src/lib.rs"));
+    }
+
+    #[test]
+    fn test_utf16_columns() {
+        let mut toks: Tokens<Java> = Tokens::new();
+        toks.append("\"\u{1f600}\" + ");
+        toks.append(toks!["a"].with_origin("a"));
+
+        let (_, map) = toks.to_file_mapped().unwrap();
+        // the emoji is a single character, but two UTF-16 code units.
+        let a = map.lookup(0, 7).unwrap();
+        assert_eq!("a", map.sources[a.source]);
+        assert_eq!(7, a.generated_column);
+    }
+
+    #[test]
+    fn test_origin() {
+        let line = line!() + 1;
+        let origin = Origin::caller();
+        assert_eq!(format!("{}:{}:22", file!(), line), origin.to_string());
+        assert_eq!("foo", Origin::from("foo").to_string());
+    }
+}
@@ -1,12 +1,16 @@
 use super::modifier::Modifier;
 use cons::Cons;
+use ident;
 use into_tokens::IntoTokens;
+use naming::Identifier;
 use swift::Swift;
 use tokens::Tokens;
+use Custom;
 use Java;
 
 /// Model for Swift Arguments to functions.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Argument<'el> {
     /// Type of argument.
     ty: Swift<'el>,
@@ -21,11 +25,11 @@ impl<'el> Argument<'el> {
     pub fn new<T, N>(ty: T, name: N) -> Argument<'el>
     where
         T: Into<Swift<'el>>,
-        N: Into<Cons<'el>>,
+        N: Into<Identifier<'el>>,
     {
         Argument {
             ty: ty.into(),
-            name: name.into(),
+            name: ident::escape::<Swift>(name.into().convert(Swift::naming().field)),
             initializer: Tokens::new(),
         }
     }
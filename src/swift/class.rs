@@ -10,6 +10,7 @@ use {Element, Tokens};
 
 /// Model for Swift Classs.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Class<'el> {
     /// Class modifiers.
     pub modifiers: Vec<Modifier>,
@@ -136,7 +137,7 @@ impl<'el> IntoTokens<'el, Swift<'el>> for Class<'el> {
 
         s.push("}");
 
-        s
+        s.with_context(format!("class {}", self.name))
     }
 }
 
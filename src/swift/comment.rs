@@ -2,6 +2,7 @@ use swift::Swift;
 use {Cons, Element, IntoTokens, Tokens};
 
 /// Format a block comment, starting with `/**`, and ending in `*/`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BlockComment<'el>(pub Vec<Cons<'el>>);
 
 impl<'el> IntoTokens<'el, Swift<'el>> for BlockComment<'el> {
@@ -12,14 +13,7 @@ impl<'el> IntoTokens<'el, Swift<'el>> for BlockComment<'el> {
             return t;
         }
 
-        t.push("/**");
-
-        for line in self.0 {
-            t.push(" * ");
-            t.append(line);
-        }
-
-        t.push(" */");
+        t.push(Element::DocComment(self.0));
         t.push(Element::PushSpacing);
 
         t
@@ -2,15 +2,14 @@
 
 use super::argument::Argument;
 use super::modifier::Modifier;
-use con_::Con::Owned;
 use cons::Cons;
-use element::Element;
 use into_tokens::IntoTokens;
 use swift::Swift;
 use tokens::Tokens;
 
 /// Model for Java Constructors.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Constructor<'el> {
     /// Constructor modifiers.
     pub modifiers: Vec<Modifier>,
@@ -38,8 +37,6 @@ into_tokens_impl_from!(Constructor<'el>, Swift<'el>);
 
 impl<'el> IntoTokens<'el, Swift<'el>> for Constructor<'el> {
     fn into_tokens(self) -> Tokens<'el, Swift<'el>> {
-        use self::Element::*;
-
         let mut c = self;
 
         let args: Vec<Tokens<Swift>> = c.arguments.into_iter().map(|a| a.into_tokens()).collect();
@@ -50,14 +47,7 @@ impl<'el> IntoTokens<'el, Swift<'el>> for Constructor<'el> {
         c.modifiers.sort();
         sig.extend(c.modifiers.into_iter().map(Into::into));
 
-        if !args.is_empty() {
-            let sep = toks![",", PushSpacing];
-            let args = args.join(sep);
-
-            sig.append(toks!["init", "(", Nested(Owned(args)), ")",]);
-        } else {
-            sig.append(toks!["init", "()"]);
-        }
+        sig.append(toks!["init", args.join_group("(", ",", ")")]);
 
         if c.throws {
             sig.append("throws");
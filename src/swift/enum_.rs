@@ -10,6 +10,7 @@ use {Element, Tokens};
 
 /// Model for Swift Enums.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Enum<'el> {
     /// Variants of the enum.
     pub variants: Tokens<'el, Swift<'el>>,
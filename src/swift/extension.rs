@@ -10,6 +10,7 @@ use {Element, Tokens};
 
 /// Model for Swift Classs.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Extension<'el> {
     /// Class modifiers.
     pub modifiers: Vec<Modifier>,
@@ -2,14 +2,16 @@
 
 use con_::Con;
 use element::Element::Spacing;
+use naming::Identifier;
 use swift::comment::BlockComment;
 use swift::modifier::Modifier;
 use swift::Swift;
-use {Cons, Tokens};
+use {ident, Cons, Custom, Tokens};
 use {Element, IntoTokens};
 
 /// Model for Swift Fields.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Field<'el> {
     /// Modifiers of field.
     pub modifiers: Vec<Modifier>,
@@ -34,7 +36,7 @@ impl<'el> Field<'el> {
     pub fn new<T, N>(ty: T, name: N) -> Field<'el>
     where
         T: Into<Swift<'el>>,
-        N: Into<Cons<'el>>,
+        N: Into<Identifier<'el>>,
     {
         use self::Modifier::*;
 
@@ -42,7 +44,7 @@ impl<'el> Field<'el> {
             modifiers: vec![Private],
             comments: vec![],
             ty: ty.into(),
-            name: name.into(),
+            name: ident::escape::<Swift>(name.into().convert(Swift::naming().field)),
             initializer: None,
             mutable: false,
             getter: None,
@@ -1,13 +1,15 @@
 //! Data structure for methods.
 
+use naming::Identifier;
 use swift::argument::Argument;
 use swift::comment::BlockComment;
 use swift::modifier::Modifier;
 use swift::{Swift, VOID};
-use {Cons, IntoTokens, Tokens};
+use {ident, Cons, Custom, IntoTokens, Tokens};
 
 /// Model for Swift Methods.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Method<'el> {
     /// Method modifiers.
     pub modifiers: Vec<Modifier>,
@@ -33,7 +35,7 @@ impl<'el> Method<'el> {
     /// Build a new empty constructor.
     pub fn new<N>(name: N) -> Method<'el>
     where
-        N: Into<Cons<'el>>,
+        N: Into<Identifier<'el>>,
     {
         use self::Modifier::*;
 
@@ -46,7 +48,7 @@ impl<'el> Method<'el> {
             comments: Vec::new(),
             throws: false,
             attributes: Tokens::new(),
-            name: name.into(),
+            name: ident::escape::<Swift>(name.into().convert(Swift::naming().method)),
         }
     }
 
@@ -72,6 +74,8 @@ into_tokens_impl_from!(Method<'el>, Swift<'el>);
 
 impl<'el> IntoTokens<'el, Swift<'el>> for Method<'el> {
     fn into_tokens(self) -> Tokens<'el, Swift<'el>> {
+        let context = format!("method {}", self.name);
+
         let mut sig = Tokens::new();
 
         sig.extend(self.modifiers.into_tokens());
@@ -94,7 +98,7 @@ impl<'el> IntoTokens<'el, Swift<'el>> for Method<'el> {
 
             let args: Tokens<Swift> = args.into_tokens();
 
-            n.append(toks!["(", args.join(", "), ")"]);
+            n.append(args.join_group("(", ",", ")"));
 
             n
         });
@@ -125,7 +129,7 @@ impl<'el> IntoTokens<'el, Swift<'el>> for Method<'el> {
             s.push("}");
         }
 
-        s
+        s.with_context(context)
     }
 }
 
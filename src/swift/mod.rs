@@ -1,8 +1,13 @@
 //! Specialization for Swift code generation.
 
+use imports::{Conflict, Imports, Resolved};
+use naming::{Case, Naming};
 use std::collections::BTreeSet;
-use std::fmt::{self, Write};
-use {Cons, Custom, Formatter, Tokens};
+use std::fmt::Write;
+use std::path::PathBuf;
+use syntax::{Construct, Declaration, Exceptions, ForEach, Lambda, Syntax};
+use value::{self, Value};
+use {Cons, Custom, Error, Formatter, IntoOwned, Result, Tokens};
 
 mod argument;
 mod class;
@@ -61,6 +66,7 @@ pub const VOID: Swift<'static> = Swift::Primitive { primitive: "Void" };
 
 /// Name of an imported type.
 #[derive(Debug, Clone, Hash, PartialOrd, Ord, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Name<'el> {
     /// Module of the imported name.
     module: Option<Cons<'el>>,
@@ -70,6 +76,7 @@ pub struct Name<'el> {
 
 /// Swift token specialization.
 #[derive(Debug, Clone, Hash, PartialOrd, Ord, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub enum Swift<'el> {
     /// Primitive type.
     Primitive {
@@ -97,44 +104,77 @@ pub enum Swift<'el> {
 }
 
 impl<'el> Swift<'el> {
-    fn type_imports<'a, 'b: 'a>(swift: &'b Swift<'b>, modules: &'a mut BTreeSet<&'b str>) {
+    fn type_imports<'a>(
+        swift: &'a Swift<'a>,
+        modules: &mut BTreeSet<(&'a str, &'a str)>,
+        locals: &mut BTreeSet<&'a str>,
+    ) {
         use self::Swift::*;
 
         match *swift {
-            Type { ref name, .. } => {
-                if let Some(module) = name.module.as_ref() {
-                    modules.insert(module);
+            Type { ref name, .. } => match name.module.as_ref() {
+                Some(module) => {
+                    modules.insert((module.as_ref(), name.name.as_ref()));
                 }
-            }
+                None => {
+                    locals.insert(name.name.as_ref());
+                }
+            },
             Map {
                 ref key, ref value, ..
             } => {
-                Self::type_imports(key, modules);
-                Self::type_imports(value, modules);
+                Self::type_imports(key, modules, locals);
+                Self::type_imports(value, modules, locals);
             }
             Array { ref inner, .. } => {
-                Self::type_imports(inner, modules);
-            }
-            Primitive { primitive } => {
-                // do nothing
+                Self::type_imports(inner, modules, locals);
             }
+            Primitive { .. } => {}
         };
     }
 
-    fn imports<'a>(tokens: &'a Tokens<'a, Self>) -> Option<Tokens<'a, Self>> {
+    fn imports<'a>(
+        tokens: &'a Tokens<Self>,
+        extra: &mut Extra,
+    ) -> Result<Option<Tokens<'a, Self>>> {
         let mut modules = BTreeSet::new();
+        let mut locals = BTreeSet::new();
 
         for custom in tokens.walk_custom() {
-            Self::type_imports(custom, &mut modules);
+            Self::type_imports(custom, &mut modules, &mut locals);
         }
 
         if modules.is_empty() {
-            return None;
+            return Ok(None);
+        }
+
+        // Local names take precedence over imported names.
+        for name in locals {
+            extra.imports.bind("", name);
         }
 
         let mut out = Tokens::new();
+        let mut imported = BTreeSet::new();
+        let mut names = BTreeSet::new();
+
+        for &(module, name) in &modules {
+            if module.is_empty() {
+                return Err(Error::unresolved_import(name));
+            }
+
+            // Every module referenced is imported, so a name from more than one module is
+            // ambiguous, and qualified everywhere.
+            if !names.insert(name) {
+                extra.imports.reserve(name);
+            }
+        }
+
+        for (module, name) in modules {
+            extra.imports.bind(module, name);
+            imported.insert(module);
+        }
 
-        for module in modules {
+        for module in imported {
             let mut s = Tokens::new();
 
             s.append("import ");
@@ -143,21 +183,196 @@ impl<'el> Swift<'el> {
             out.push(s);
         }
 
-        Some(out)
+        Ok(Some(out))
+    }
+}
+
+/// Extra data for Swift formatting.
+///
+/// Swift used to have no extra data, so code rendering with `to_file_with(())` has to pass
+/// `Extra::default()` instead, or use `to_file()`.
+#[derive(Debug, Clone)]
+pub struct Extra {
+    /// Types which have been bound in the file.
+    imports: Imports,
+}
+
+impl Extra {
+    /// Get the module the given simple name refers to, if it has been bound by an import.
+    pub fn imported(&self, name: &str) -> Option<&str> {
+        self.imports.module(name)
+    }
+}
+
+impl Default for Extra {
+    fn default() -> Self {
+        Extra {
+            imports: Imports::new(Conflict::Qualify),
+        }
+    }
+}
+
+/// Reserved keywords.
+static KEYWORDS: &[&str] = &[
+    "associatedtype",
+    "class",
+    "deinit",
+    "enum",
+    "extension",
+    "fileprivate",
+    "func",
+    "import",
+    "init",
+    "inout",
+    "internal",
+    "let",
+    "open",
+    "operator",
+    "private",
+    "protocol",
+    "public",
+    "rethrows",
+    "static",
+    "struct",
+    "subscript",
+    "typealias",
+    "var",
+    "break",
+    "case",
+    "continue",
+    "default",
+    "defer",
+    "do",
+    "else",
+    "fallthrough",
+    "for",
+    "guard",
+    "if",
+    "in",
+    "repeat",
+    "return",
+    "switch",
+    "where",
+    "while",
+    "as",
+    "Any",
+    "catch",
+    "false",
+    "is",
+    "nil",
+    "super",
+    "self",
+    "Self",
+    "throw",
+    "throws",
+    "true",
+    "try",
+];
+
+impl<'el> IntoOwned for Name<'el> {
+    type Owned = Name<'static>;
+
+    fn into_owned(self) -> Name<'static> {
+        Name {
+            module: self.module.into_owned(),
+            name: self.name.into_owned(),
+        }
+    }
+}
+
+impl<'el> IntoOwned for Swift<'el> {
+    type Owned = Swift<'static>;
+
+    fn into_owned(self) -> Swift<'static> {
+        use self::Swift::*;
+
+        match self {
+            Primitive { primitive } => Primitive { primitive },
+            Type { name } => Type {
+                name: name.into_owned(),
+            },
+            Map { key, value } => Map {
+                key: key.into_owned(),
+                value: value.into_owned(),
+            },
+            Array { inner } => Array {
+                inner: inner.into_owned(),
+            },
+        }
+    }
+}
+
+/// Primitive types are deserialized into one of the known primitive types.
+#[cfg(feature = "serde")]
+impl<'de, 'el> ::serde::Deserialize<'de> for Swift<'el> {
+    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+    where
+        D: ::serde::Deserializer<'de>,
+    {
+        use serde::de::{Error, Unexpected};
+
+        #[derive(Deserialize)]
+        #[serde(rename = "Swift")]
+        enum Data<'el> {
+            Primitive {
+                primitive: String,
+            },
+            Type {
+                name: Name<'el>,
+            },
+            Map {
+                key: Box<Swift<'el>>,
+                value: Box<Swift<'el>>,
+            },
+            Array {
+                inner: Box<Swift<'el>>,
+            },
+        }
+
+        let primitives = [
+            SHORT, INTEGER, LONG, FLOAT, DOUBLE, CHAR, BOOLEAN, BYTE, VOID,
+        ];
+
+        Ok(match Data::deserialize(deserializer)? {
+            Data::Primitive { primitive } => primitives
+                .iter()
+                .find(|p| match **p {
+                    Swift::Primitive { primitive: p } => p == primitive,
+                    _ => false,
+                })
+                .cloned()
+                .ok_or_else(|| {
+                    D::Error::invalid_value(Unexpected::Str(&primitive), &"a primitive type")
+                })?,
+            Data::Type { name } => Swift::Type { name },
+            Data::Map { key, value } => Swift::Map { key, value },
+            Data::Array { inner } => Swift::Array { inner },
+        })
     }
 }
 
 impl<'el> Custom for Swift<'el> {
-    type Extra = ();
+    type Extra = Extra;
 
-    fn format(&self, out: &mut Formatter, extra: &mut Self::Extra, level: usize) -> fmt::Result {
+    fn format(&self, out: &mut Formatter, extra: &mut Self::Extra, level: usize) -> Result<()> {
         use self::Swift::*;
 
         match *self {
             Type {
-                name: Name { ref name, .. },
+                name:
+                    Name {
+                        ref module,
+                        ref name,
+                    },
                 ..
             } => {
+                if let Some(module) = module.as_ref() {
+                    if extra.imports.resolve(module, name) == Some(Resolved::Qualified) {
+                        out.write_str(module)?;
+                        out.write_str(".")?;
+                    }
+                }
+
                 out.write_str(name)?;
             }
             Map {
@@ -182,7 +397,7 @@ impl<'el> Custom for Swift<'el> {
         Ok(())
     }
 
-    fn quote_string(out: &mut Formatter, input: &str) -> fmt::Result {
+    fn quote_string(out: &mut Formatter, input: &str) -> Result<()> {
         out.write_char('"')?;
 
         for c in input.chars() {
@@ -201,20 +416,84 @@ impl<'el> Custom for Swift<'el> {
         Ok(())
     }
 
+    fn literal<'a>(value: Value<'a, Self>) -> Tokens<'a, Self>
+    where
+        Self: 'a,
+    {
+        match value {
+            Value::Null => value::raw("nil"),
+            Value::Float(value) if !value.is_finite() => value::non_finite(
+                value.into(),
+                "Float.nan",
+                "Float.infinity",
+                "-Float.infinity",
+            ),
+            Value::Double(value) if !value.is_finite() => {
+                value::non_finite(value, "Double.nan", "Double.infinity", "-Double.infinity")
+            }
+            Value::Char(value) => value::literal(Value::String(value.to_string().into())),
+            Value::Map(ref entries) if entries.is_empty() => value::raw("[:]"),
+            Value::Map(entries) => value::map("[", entries, ": ", "]"),
+            value => value::literal(value),
+        }
+    }
+
+    fn syntax() -> Syntax {
+        Syntax {
+            semicolons: false,
+            parenthesized: false,
+            declaration: Declaration::Keyword {
+                immutable: "let",
+                mutable: "var",
+            },
+            for_each: ForEach {
+                keyword: "for",
+                binding: "",
+                separator: " in ",
+            },
+            lambda: Lambda::Closure,
+            construct: Construct::Call,
+            exceptions: Some(Exceptions::Do),
+            ..Syntax::default()
+        }
+    }
+
+    fn naming() -> Naming {
+        Naming {
+            type_name: Case::Pascal,
+            field: Case::Camel,
+            method: Case::Camel,
+            constant: Case::Camel,
+        }
+    }
+
+    fn keywords() -> &'static [&'static str] {
+        KEYWORDS
+    }
+
+    fn escape_keyword(ident: &str) -> String {
+        format!("`{}`", ident)
+    }
+
+    fn file_path(name: &str, _extra: &Self::Extra) -> PathBuf {
+        PathBuf::from(format!("{}.swift", name))
+    }
+
     fn write_file<'a>(
-        tokens: Tokens<'a, Self>,
+        tokens: &Tokens<'a, Self>,
         out: &mut Formatter,
         extra: &mut Self::Extra,
         level: usize,
-    ) -> fmt::Result {
+    ) -> Result<()> {
+        out.write_header::<Self>()?;
+
         let mut toks: Tokens<Self> = Tokens::new();
 
-        if let Some(imports) = Self::imports(&tokens) {
+        if let Some(imports) = Self::imports(tokens, extra)? {
             toks.push(imports);
         }
 
-        toks.push_ref(&tokens);
-        toks.join_line_spacing().format(out, extra, level)
+        tokens.format_with_preamble(&toks, out, extra, level)
     }
 }
 
@@ -270,7 +549,21 @@ where
 #[cfg(test)]
 mod tests {
     use super::{array, imported, local, map, Swift};
-    use {Quoted, Tokens};
+    use {Quoted, Tokens, Value};
+
+    #[test]
+    fn test_literals() {
+        let mut toks: Tokens<Swift> = Tokens::new();
+        toks.push(Value::Map(vec![]));
+        toks.push(Value::Map(vec![("a".into(), Value::Null)]));
+        toks.push(Value::Char('a'));
+        toks.push(Value::Double(f64::NEG_INFINITY));
+
+        assert_eq!(
+            Ok("[:]\n[\"a\": nil]\n\"a\"\n-Double.infinity"),
+            toks.to_string().as_ref().map(|s| s.as_str())
+        );
+    }
 
     #[test]
     fn test_string() {
@@ -293,6 +586,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_unresolved_import() {
+        use ErrorKind;
+
+        let toks: Tokens<Swift> = toks!(imported("", "Debug"));
+        let error = toks.to_file().unwrap_err();
+        assert_eq!(&ErrorKind::UnresolvedImport("Debug".into()), error.kind());
+    }
+
+    #[test]
+    fn test_conflicting_imports() {
+        let mut toks: Tokens<Swift> = Tokens::new();
+        toks.push(toks!(imported("Foo", "Debug")));
+        toks.push(toks!(imported("Bar", "Debug")));
+        toks.push(toks!(imported("Bar", "Data")));
+        toks.push(toks!(imported("Foundation", "Data")));
+        toks.push(toks!(local("Data")));
+
+        assert_eq!(
+            Ok("import Bar\nimport Foo\nimport Foundation\n\nFoo.Debug\nBar.Debug\nBar.Data\nFoundation.Data\nData\n"),
+            toks.to_file().as_ref().map(|s| s.as_str())
+        );
+    }
+
     #[test]
     fn test_array() {
         let dbg = array(imported("Foo", "Debug"));
@@ -4,6 +4,7 @@ use {Custom, Element, IntoTokens, Tokens};
 
 /// Model for Enum.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Modifier {
     /// open modifier
     Open,
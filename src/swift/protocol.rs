@@ -9,6 +9,7 @@ use {Cons, Tokens};
 
 /// Model for Swift Protocol.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Protocol<'el> {
     /// Interface modifiers.
     pub modifiers: Vec<Modifier>,
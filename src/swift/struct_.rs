@@ -10,6 +10,7 @@ use {Element, Tokens};
 
 /// Model for Swift Classs.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Struct<'el> {
     /// Class modifiers.
     pub modifiers: Vec<Modifier>,
@@ -129,7 +130,7 @@ impl<'el> IntoTokens<'el, Swift<'el>> for Struct<'el> {
 
         s.push("}");
 
-        s
+        s.with_context(format!("struct {}", self.name))
     }
 }
 
@@ -149,4 +150,22 @@ mod tests {
         let out = s.as_ref().map(|s| s.as_str());
         assert_eq!(Ok("public struct Foo<T> {\n}"), out);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use serde_json;
+        use swift::{imported, Field, INTEGER};
+
+        let mut c = Struct::new("Foo");
+        c.implements.push(imported("Foundation", "Codable"));
+        c.fields.push(Field::new(INTEGER, "count"));
+
+        let json = serde_json::to_string(&c).unwrap();
+        let d: Struct = serde_json::from_str(&json).unwrap();
+
+        let c: Tokens<Swift> = c.into();
+        let d: Tokens<Swift> = d.into();
+        assert_eq!(c.to_file().unwrap(), d.to_file().unwrap());
+    }
 }
@@ -0,0 +1,1098 @@
+//! Language-neutral expressions and statements.
+//!
+//! Expressions and statements are rendered using the [`Syntax`] of the language, which
+//! describes its braces, semicolons, operators, and control flow. Types referenced in them are
+//! rendered as custom elements, so that they are imported like any other type.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rstgen::syntax::{self, var, BinaryOp, Expr, Stmt};
+//! use rstgen::{Java, Python, Tokens};
+//!
+//! fn body<C>() -> Vec<Stmt<'static, C>> {
+//!     vec![Stmt::If(
+//!         var("a").op(BinaryOp::And, var("b")),
+//!         vec![Stmt::Return(Some(var("a")))],
+//!         vec![Stmt::Expr(var("log").call(vec![var("b")]))],
+//!     )]
+//! }
+//!
+//! let java: Tokens<Java> = syntax::render(body()).unwrap();
+//! assert_eq!(
+//!     "if (a && b) {\n  return a;\n} else {\n  log(b);\n}",
+//!     java.to_string().unwrap()
+//! );
+//!
+//! let python: Tokens<Python> = syntax::render(body()).unwrap();
+//! assert_eq!(
+//!     "if a and b:\n  return a\nelse:\n  log(b)\n",
+//!     python.to_string().unwrap()
+//! );
+//! ```
+
+use con_::Con;
+use ident;
+use std::mem;
+use {Cons, Custom, Element, Error, IntoTokens, Result, Tokens, Value};
+
+/// How blocks of statements are delimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Blocks {
+    /// Blocks are surrounded by braces, like `if (a) { .. }`.
+    Braces,
+    /// Blocks are introduced by a colon and indented, like `if a:`.
+    ///
+    /// Empty blocks contain a `pass` statement.
+    Indentation,
+}
+
+/// How variables are declared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Declaration {
+    /// Declarations start with a keyword, like `let a = 1;` or `let mut a = 1;`.
+    Keyword {
+        /// Keyword declaring a variable which is never reassigned.
+        immutable: &'static str,
+        /// Keyword declaring a variable which may be reassigned.
+        mutable: &'static str,
+    },
+    /// Declarations use an assignment operator, like `a := 1`.
+    Operator(&'static str),
+}
+
+/// Syntax of a loop over the items of a collection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ForEach {
+    /// Keyword introducing the loop, like `for` or `foreach`.
+    pub keyword: &'static str,
+    /// Prefix of the name bound to each item, like `var ` or `const `.
+    pub binding: &'static str,
+    /// Separator between the name and the collection, like ` in ` or ` : `.
+    pub separator: &'static str,
+}
+
+/// Syntax of a lambda.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lambda {
+    /// Parenthesized arguments followed by an arrow, like `(a, b) -> a + b`.
+    Arrow(&'static str),
+    /// A closure, like `{ a, b in a + b }`.
+    Closure,
+    /// A lambda keyword, like `lambda a, b: a + b`.
+    Keyword,
+    /// Arguments between pipes, like `|a, b| a + b`.
+    Pipes,
+    /// An anonymous function, like `func(a, b) { return a + b }`.
+    ///
+    /// Languages requiring the types of arguments expect them to be part of the names.
+    Function,
+}
+
+/// Syntax of constructing an instance of a type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Construct {
+    /// The `new` keyword, like `new Foo(a)`.
+    New,
+    /// Calling the type, like `Foo(a)`.
+    Call,
+    /// Calling an associated function, like `Foo::new(a)`.
+    Associated(&'static str),
+    /// A composite literal, like `Foo{a}`.
+    Composite,
+}
+
+/// Syntax of catching exceptions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Exceptions {
+    /// Typed catch clauses, like `catch (Foo e)`.
+    Typed {
+        /// Type caught by a catch clause without a type.
+        any: &'static str,
+    },
+    /// Untyped catch clauses, like `catch (e)`.
+    Untyped,
+    /// Catch clauses optionally prefixed with a type, like `on Foo catch (e)`.
+    On,
+    /// A `do` block followed by catch clauses, like `catch let e as Foo`.
+    ///
+    /// Finally blocks are rendered as a `defer` block. If there are catch clauses, the `defer`
+    /// block and the `do` block are wrapped in an outer `do` block, so that the finally block
+    /// runs after the catch clauses.
+    Do,
+    /// Except clauses, like `except Foo as e:`.
+    Except,
+}
+
+/// Description of the syntax of expressions and statements in a language.
+///
+/// The default describes Java.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Syntax {
+    /// If statements are terminated by semicolons.
+    pub semicolons: bool,
+    /// How blocks are delimited.
+    pub blocks: Blocks,
+    /// If conditions of `if` and `while` statements are parenthesized.
+    pub parenthesized: bool,
+    /// How variables are declared.
+    pub declaration: Declaration,
+    /// Equality operator.
+    pub equals: &'static str,
+    /// Inequality operator.
+    pub not_equals: &'static str,
+    /// Logical and operator.
+    pub and: &'static str,
+    /// Logical or operator.
+    pub or: &'static str,
+    /// Logical negation operator, including any trailing space.
+    pub not: &'static str,
+    /// If logical negation binds less tightly than comparisons, like `not a == b` in Python.
+    pub loose_not: bool,
+    /// Keyword continuing an `if` statement with another condition.
+    pub else_if: &'static str,
+    /// Keyword of a loop with a condition.
+    pub while_loop: &'static str,
+    /// Syntax of a loop over a collection.
+    pub for_each: ForEach,
+    /// Syntax of a lambda.
+    pub lambda: Lambda,
+    /// Syntax of constructing an instance of a type.
+    pub construct: Construct,
+    /// Syntax of catching exceptions, or `None` if the language doesn't have exceptions.
+    pub exceptions: Option<Exceptions>,
+}
+
+impl Default for Syntax {
+    fn default() -> Self {
+        Syntax {
+            semicolons: true,
+            blocks: Blocks::Braces,
+            parenthesized: true,
+            declaration: Declaration::Keyword {
+                immutable: "final var",
+                mutable: "var",
+            },
+            equals: "==",
+            not_equals: "!=",
+            and: "&&",
+            or: "||",
+            not: "!",
+            loose_not: false,
+            else_if: "else if",
+            while_loop: "while",
+            for_each: ForEach {
+                keyword: "for",
+                binding: "var ",
+                separator: " : ",
+            },
+            lambda: Lambda::Arrow("->"),
+            construct: Construct::New,
+            exceptions: Some(Exceptions::Typed { any: "Exception" }),
+        }
+    }
+}
+
+/// A unary operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnaryOp {
+    /// Logical negation.
+    Not,
+    /// Arithmetic negation.
+    Neg,
+}
+
+/// A binary operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinaryOp {
+    /// Addition, `+`.
+    Add,
+    /// Subtraction, `-`.
+    Sub,
+    /// Multiplication, `*`.
+    Mul,
+    /// Division, `/`.
+    Div,
+    /// Remainder, `%`.
+    Rem,
+    /// Equality.
+    Eq,
+    /// Inequality.
+    NotEq,
+    /// Less than, `<`.
+    Lt,
+    /// Less than or equal, `<=`.
+    LtEq,
+    /// Greater than, `>`.
+    Gt,
+    /// Greater than or equal, `>=`.
+    GtEq,
+    /// Logical and.
+    And,
+    /// Logical or.
+    Or,
+}
+
+impl BinaryOp {
+    /// Precedence of the operator, where higher binds tighter.
+    fn precedence(&self) -> u8 {
+        use self::BinaryOp::*;
+
+        match *self {
+            Or => 1,
+            And => 2,
+            Eq | NotEq => 4,
+            Lt | LtEq | Gt | GtEq => 5,
+            Add | Sub => 6,
+            Mul | Div | Rem => 7,
+        }
+    }
+
+    /// If the operator is a comparison, which some languages chain, like `a < b < c` in Python.
+    fn is_comparison(&self) -> bool {
+        use self::BinaryOp::*;
+
+        matches!(*self, Eq | NotEq | Lt | LtEq | Gt | GtEq)
+    }
+
+    fn symbol(&self, syntax: &Syntax) -> &'static str {
+        use self::BinaryOp::*;
+
+        match *self {
+            Add => "+",
+            Sub => "-",
+            Mul => "*",
+            Div => "/",
+            Rem => "%",
+            Eq => syntax.equals,
+            NotEq => syntax.not_equals,
+            Lt => "<",
+            LtEq => "<=",
+            Gt => ">",
+            GtEq => ">=",
+            And => syntax.and,
+            Or => syntax.or,
+        }
+    }
+}
+
+/// Precedence of logical negation in languages where it binds less tightly than comparisons.
+const LOOSE_NOT: u8 = 3;
+/// Precedence of unary operators.
+const UNARY: u8 = 8;
+/// Precedence of calls, field access, and atoms.
+const POSTFIX: u8 = 9;
+
+/// An expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr<'el, C: 'el> {
+    /// A variable, escaped if it is a keyword.
+    Var(Cons<'el>),
+    /// A literal value.
+    Literal(Value<'el, C>),
+    /// A reference to a type.
+    Type(C),
+    /// Tokens which are rendered as-is.
+    Tokens(Tokens<'el, C>),
+    /// Access of a field, like `a.b`.
+    Field(Box<Expr<'el, C>>, Cons<'el>),
+    /// Indexing, like `a[b]`.
+    Index(Box<Expr<'el, C>>, Box<Expr<'el, C>>),
+    /// A call, like `a(b, c)`.
+    Call(Box<Expr<'el, C>>, Vec<Expr<'el, C>>),
+    /// Constructing an instance of a type, like `new A(b, c)`.
+    New(C, Vec<Expr<'el, C>>),
+    /// A unary operation.
+    Unary(UnaryOp, Box<Expr<'el, C>>),
+    /// A binary operation.
+    Binary(Box<Expr<'el, C>>, BinaryOp, Box<Expr<'el, C>>),
+    /// A lambda with the given arguments, returning the given expression.
+    Lambda(Vec<Cons<'el>>, Box<Expr<'el, C>>),
+}
+
+impl<'el, C> Expr<'el, C> {
+    /// Access a field of this expression.
+    pub fn field<N: Into<Cons<'el>>>(self, name: N) -> Expr<'el, C> {
+        Expr::Field(Box::new(self), name.into())
+    }
+
+    /// Index this expression.
+    pub fn index(self, index: Expr<'el, C>) -> Expr<'el, C> {
+        Expr::Index(Box::new(self), Box::new(index))
+    }
+
+    /// Call this expression with the given arguments.
+    pub fn call(self, arguments: Vec<Expr<'el, C>>) -> Expr<'el, C> {
+        Expr::Call(Box::new(self), arguments)
+    }
+
+    /// Call a method of this expression with the given arguments.
+    pub fn method<N: Into<Cons<'el>>>(self, name: N, arguments: Vec<Expr<'el, C>>) -> Expr<'el, C> {
+        self.field(name).call(arguments)
+    }
+
+    /// Apply a binary operator to this expression and the given expression.
+    pub fn op(self, op: BinaryOp, rhs: Expr<'el, C>) -> Expr<'el, C> {
+        Expr::Binary(Box::new(self), op, Box::new(rhs))
+    }
+
+    fn precedence(&self, syntax: &Syntax) -> u8 {
+        match *self {
+            Expr::Lambda(..) => 0,
+            Expr::Binary(_, op, _) => op.precedence(),
+            Expr::Unary(UnaryOp::Not, _) if syntax.loose_not => LOOSE_NOT,
+            Expr::Unary(..) => UNARY,
+            // Negative literals are rendered with a leading `-`, like a negation.
+            ref e if e.is_negative() => UNARY,
+            _ => POSTFIX,
+        }
+    }
+
+    /// If the expression is rendered starting with a `-`.
+    fn is_negative(&self) -> bool {
+        match *self {
+            Expr::Unary(UnaryOp::Neg, _) => true,
+            Expr::Literal(Value::Int(n)) => n < 0,
+            Expr::Literal(Value::Long(n)) => n < 0,
+            Expr::Literal(Value::Float(n)) => n.is_sign_negative(),
+            Expr::Literal(Value::Double(n)) => n.is_sign_negative(),
+            _ => false,
+        }
+    }
+
+    fn is_comparison(&self) -> bool {
+        match *self {
+            Expr::Binary(_, op, _) => op.is_comparison(),
+            _ => false,
+        }
+    }
+}
+
+/// Construct a variable.
+pub fn var<'el, C, N: Into<Cons<'el>>>(name: N) -> Expr<'el, C> {
+    Expr::Var(name.into())
+}
+
+impl<'el, C: Custom + Clone + Eq> From<Expr<'el, C>> for Element<'el, C> {
+    fn from(value: Expr<'el, C>) -> Self {
+        Element::Append(Con::Owned(expr(value, &C::syntax())))
+    }
+}
+
+impl<'el, C: Custom + Clone + Eq> IntoTokens<'el, C> for Expr<'el, C> {
+    fn into_tokens(self) -> Tokens<'el, C> {
+        expr(self, &C::syntax())
+    }
+}
+
+impl<'el, C> From<Value<'el, C>> for Expr<'el, C> {
+    fn from(value: Value<'el, C>) -> Self {
+        Expr::Literal(value)
+    }
+}
+
+/// A clause catching exceptions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Catch<'el, C: 'el> {
+    /// Type of exceptions caught, or `None` to catch any exception.
+    pub ty: Option<C>,
+    /// Name bound to the caught exception.
+    pub name: Cons<'el>,
+    /// Statements handling the exception.
+    pub body: Vec<Stmt<'el, C>>,
+}
+
+impl<'el, C> Catch<'el, C> {
+    /// Catch any exception, binding it to the given name.
+    pub fn new<N: Into<Cons<'el>>>(name: N, body: Vec<Stmt<'el, C>>) -> Catch<'el, C> {
+        Catch {
+            ty: None,
+            name: name.into(),
+            body,
+        }
+    }
+
+    /// Only catch exceptions of the given type.
+    pub fn ty(self, ty: C) -> Catch<'el, C> {
+        Catch {
+            ty: Some(ty),
+            ..self
+        }
+    }
+}
+
+/// A statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stmt<'el, C: 'el> {
+    /// An expression evaluated for its side effects.
+    Expr(Expr<'el, C>),
+    /// Declare a variable which is never reassigned.
+    Let(Cons<'el>, Expr<'el, C>),
+    /// Declare a variable which may be reassigned.
+    Var(Cons<'el>, Expr<'el, C>),
+    /// Assign a value to a variable, field, or index.
+    Assign(Expr<'el, C>, Expr<'el, C>),
+    /// Return from a function, optionally with a value.
+    Return(Option<Expr<'el, C>>),
+    /// A condition with the statements to run if it's true and if it's false.
+    ///
+    /// An else branch consisting of a single `If` is rendered as a chained `else if`.
+    If(Expr<'el, C>, Vec<Stmt<'el, C>>, Vec<Stmt<'el, C>>),
+    /// Loop while the condition is true.
+    While(Expr<'el, C>, Vec<Stmt<'el, C>>),
+    /// Loop over the items of a collection, binding each item to the given name.
+    ForEach(Cons<'el>, Expr<'el, C>, Vec<Stmt<'el, C>>),
+    /// Break out of a loop.
+    Break,
+    /// Continue with the next iteration of a loop.
+    Continue,
+    /// Run statements, handling exceptions with the given catch clauses, followed by the
+    /// statements of the finally block.
+    Try(Vec<Stmt<'el, C>>, Vec<Catch<'el, C>>, Vec<Stmt<'el, C>>),
+}
+
+/// Render the given statements, each on a line of its own.
+///
+/// Rendering fails if a statement is not supported by the language, like a `Try` in a
+/// language without exceptions.
+pub fn render<'el, C>(statements: Vec<Stmt<'el, C>>) -> Result<Tokens<'el, C>>
+where
+    C: Custom + Clone + Eq,
+{
+    block_statements(statements, &C::syntax())
+}
+
+fn expr<'el, C>(e: Expr<'el, C>, syntax: &Syntax) -> Tokens<'el, C>
+where
+    C: Custom + Clone + Eq,
+{
+    let precedence = e.precedence(syntax);
+
+    match e {
+        Expr::Var(name) => toks![ident::escape::<C>(name)],
+        Expr::Literal(value) => toks![value],
+        Expr::Type(ty) => toks![ty],
+        Expr::Tokens(tokens) => tokens,
+        Expr::Field(target, name) => toks![
+            operand(*target, POSTFIX, syntax),
+            ".",
+            ident::escape::<C>(name),
+        ],
+        Expr::Index(target, index) => toks![
+            operand(*target, POSTFIX, syntax),
+            "[",
+            expr(*index, syntax),
+            "]",
+        ],
+        Expr::Call(target, arguments) => toks![
+            operand(*target, POSTFIX, syntax),
+            list(arguments, syntax).join_group("(", ",", ")"),
+        ],
+        Expr::New(ty, arguments) => {
+            let arguments = list(arguments, syntax);
+
+            match syntax.construct {
+                Construct::New => toks!["new ", ty, arguments.join_group("(", ",", ")")],
+                Construct::Call => toks![ty, arguments.join_group("(", ",", ")")],
+                Construct::Associated(function) => {
+                    toks![ty, function, arguments.join_group("(", ",", ")")]
+                }
+                Construct::Composite => toks![ty, arguments.join_group("{", ",", "}")],
+            }
+        }
+        Expr::Unary(op, operand_) => {
+            let symbol = match op {
+                UnaryOp::Not => syntax.not,
+                UnaryOp::Neg => "-",
+            };
+
+            // Keep `-(-a)` from being rendered as the decrement `--a`.
+            if op == UnaryOp::Neg && operand_.is_negative() {
+                return toks![symbol, "(", expr(*operand_, syntax), ")"];
+            }
+
+            toks![symbol, operand(*operand_, precedence, syntax)]
+        }
+        Expr::Binary(lhs, op, rhs) => {
+            // Comparisons of comparisons are parenthesized, since some languages chain them.
+            let chained = |e: &Expr<'el, C>, precedence| {
+                if op.is_comparison() && e.is_comparison() {
+                    POSTFIX
+                } else {
+                    precedence
+                }
+            };
+
+            let lhs_precedence = chained(&lhs, precedence);
+            let rhs_precedence = chained(&rhs, precedence + 1);
+
+            toks![
+                operand(*lhs, lhs_precedence, syntax),
+                " ",
+                op.symbol(syntax),
+                " ",
+                operand(*rhs, rhs_precedence, syntax),
+            ]
+        }
+        Expr::Lambda(arguments, body) => {
+            let arguments = arguments
+                .into_iter()
+                .map(|a| Element::Literal(ident::escape::<C>(a)))
+                .collect::<Tokens<C>>()
+                .join(", ");
+
+            let body = expr(*body, syntax);
+
+            match syntax.lambda {
+                Lambda::Arrow(arrow) => toks!["(", arguments, ") ", arrow, " ", body],
+                Lambda::Closure if arguments.is_empty() => toks!["{ ", body, " }"],
+                Lambda::Closure => toks!["{ ", arguments, " in ", body, " }"],
+                Lambda::Keyword if arguments.is_empty() => toks!["lambda: ", body],
+                Lambda::Keyword => toks!["lambda ", arguments, ": ", body],
+                Lambda::Pipes => toks!["|", arguments, "| ", body],
+                Lambda::Function => toks!["func(", arguments, ") { return ", body, " }"],
+            }
+        }
+    }
+}
+
+/// Render an operand, parenthesized if it binds less tightly than required.
+fn operand<'el, C>(e: Expr<'el, C>, precedence: u8, syntax: &Syntax) -> Tokens<'el, C>
+where
+    C: Custom + Clone + Eq,
+{
+    if e.precedence(syntax) < precedence {
+        return toks!["(", expr(e, syntax), ")"];
+    }
+
+    expr(e, syntax)
+}
+
+fn list<'el, C>(values: Vec<Expr<'el, C>>, syntax: &Syntax) -> Tokens<'el, C>
+where
+    C: Custom + Clone + Eq,
+{
+    values
+        .into_iter()
+        .map(|v| Element::Append(Con::Owned(expr(v, syntax))))
+        .collect()
+}
+
+/// Render a condition following the given keyword.
+fn condition<'el, C>(keyword: Tokens<'el, C>, e: Expr<'el, C>, syntax: &Syntax) -> Tokens<'el, C>
+where
+    C: Custom + Clone + Eq,
+{
+    if syntax.parenthesized {
+        return toks![keyword, " (", expr(e, syntax), ")"];
+    }
+
+    toks![keyword, " ", expr(e, syntax)]
+}
+
+/// Header of a block continuing a previous one, like `} else`.
+fn continuation<'el, C>(header: Tokens<'el, C>, syntax: &Syntax) -> Tokens<'el, C>
+where
+    C: Custom + Clone + Eq,
+{
+    match syntax.blocks {
+        Blocks::Braces => toks!["} ", header],
+        Blocks::Indentation => header,
+    }
+}
+
+/// Render a block with the given header and body, leaving it open.
+fn block<'el, C>(
+    out: &mut Tokens<'el, C>,
+    header: Tokens<'el, C>,
+    mut body: Tokens<'el, C>,
+    syntax: &Syntax,
+) where
+    C: Custom + Clone + Eq,
+{
+    match syntax.blocks {
+        Blocks::Braces => out.push(toks![header, " {"]),
+        Blocks::Indentation => out.push(toks![header, ":"]),
+    }
+
+    if body.is_empty() && syntax.blocks == Blocks::Indentation {
+        body.push("pass");
+    }
+
+    if !body.is_empty() {
+        out.nested(body);
+    }
+}
+
+/// Close the last opened block.
+fn close<'el, C>(out: &mut Tokens<'el, C>, syntax: &Syntax)
+where
+    C: Custom + Clone + Eq,
+{
+    if syntax.blocks == Blocks::Braces {
+        out.push("}");
+    }
+}
+
+fn block_statements<'el, C>(
+    statements: Vec<Stmt<'el, C>>,
+    syntax: &Syntax,
+) -> Result<Tokens<'el, C>>
+where
+    C: Custom + Clone + Eq,
+{
+    let mut out = Tokens::new();
+
+    for s in statements {
+        stmt(&mut out, s, syntax)?;
+    }
+
+    Ok(out)
+}
+
+fn declare<'el, C>(
+    out: &mut Tokens<'el, C>,
+    name: Cons<'el>,
+    value: Expr<'el, C>,
+    mutable: bool,
+    syntax: &Syntax,
+) where
+    C: Custom + Clone + Eq,
+{
+    let end = if syntax.semicolons { ";" } else { "" };
+    let name = ident::escape::<C>(name);
+    let value = expr(value, syntax);
+
+    match syntax.declaration {
+        Declaration::Keyword {
+            immutable,
+            mutable: keyword,
+        } => {
+            let keyword = if mutable { keyword } else { immutable };
+            out.push(toks![keyword, " ", name, " = ", value, end]);
+        }
+        Declaration::Operator(operator) => {
+            out.push(toks![name, " ", operator, " ", value, end]);
+        }
+    }
+}
+
+fn stmt<'el, C>(out: &mut Tokens<'el, C>, s: Stmt<'el, C>, syntax: &Syntax) -> Result<()>
+where
+    C: Custom + Clone + Eq,
+{
+    let end = if syntax.semicolons { ";" } else { "" };
+
+    match s {
+        Stmt::Expr(e) => out.push(toks![expr(e, syntax), end]),
+        Stmt::Let(name, value) => declare(out, name, value, false, syntax),
+        Stmt::Var(name, value) => declare(out, name, value, true, syntax),
+        Stmt::Assign(target, value) => {
+            out.push(toks![expr(target, syntax), " = ", expr(value, syntax), end]);
+        }
+        Stmt::Return(Some(value)) => out.push(toks!["return ", expr(value, syntax), end]),
+        Stmt::Return(None) => out.push(toks!["return", end]),
+        Stmt::If(cond, then, otherwise) => {
+            let header = condition(toks!["if"], cond, syntax);
+            block(out, header, block_statements(then, syntax)?, syntax);
+
+            let mut otherwise = otherwise;
+
+            while !otherwise.is_empty() {
+                if let [Stmt::If(..)] = otherwise[..] {
+                    if let Some(Stmt::If(cond, then, next)) = otherwise.pop() {
+                        let header = continuation(toks![syntax.else_if], syntax);
+                        let header = condition(header, cond, syntax);
+                        block(out, header, block_statements(then, syntax)?, syntax);
+                        otherwise = next;
+                        continue;
+                    }
+                }
+
+                let header = continuation(toks!["else"], syntax);
+                block(out, header, block_statements(otherwise, syntax)?, syntax);
+                break;
+            }
+
+            close(out, syntax);
+        }
+        Stmt::While(cond, body) => {
+            let header = condition(toks![syntax.while_loop], cond, syntax);
+            block(out, header, block_statements(body, syntax)?, syntax);
+            close(out, syntax);
+        }
+        Stmt::ForEach(name, collection, body) => {
+            let ForEach {
+                keyword,
+                binding,
+                separator,
+            } = syntax.for_each;
+
+            let header = toks![
+                binding,
+                ident::escape::<C>(name),
+                separator,
+                expr(collection, syntax),
+            ];
+
+            let header = if syntax.parenthesized {
+                toks![keyword, " (", header, ")"]
+            } else {
+                toks![keyword, " ", header]
+            };
+
+            block(out, header, block_statements(body, syntax)?, syntax);
+            close(out, syntax);
+        }
+        Stmt::Break => out.push(toks!["break", end]),
+        Stmt::Continue => out.push(toks!["continue", end]),
+        Stmt::Try(body, catches, finally) => try_(out, body, catches, finally, syntax)?,
+    }
+
+    Ok(())
+}
+
+fn try_<'el, C>(
+    out: &mut Tokens<'el, C>,
+    body: Vec<Stmt<'el, C>>,
+    catches: Vec<Catch<'el, C>>,
+    mut finally: Vec<Stmt<'el, C>>,
+    syntax: &Syntax,
+) -> Result<()>
+where
+    C: Custom + Clone + Eq,
+{
+    let exceptions = match syntax.exceptions {
+        Some(exceptions) => exceptions,
+        None => return Err(Error::unsupported("try statements")),
+    };
+
+    if let Exceptions::Do = exceptions {
+        let mut nested = Tokens::new();
+
+        // a defer inside of the do block would run before any catch clause.
+        if !finally.is_empty() && !catches.is_empty() {
+            block(
+                &mut nested,
+                toks!["defer"],
+                block_statements(finally, syntax)?,
+                syntax,
+            );
+            close(&mut nested, syntax);
+            try_(&mut nested, body, catches, Vec::new(), syntax)?;
+            block(out, toks!["do"], nested, syntax);
+            close(out, syntax);
+            return Ok(());
+        }
+
+        if !finally.is_empty() {
+            let finally = mem::take(&mut finally);
+            block(
+                &mut nested,
+                toks!["defer"],
+                block_statements(finally, syntax)?,
+                syntax,
+            );
+            close(&mut nested, syntax);
+        }
+
+        nested.extend(block_statements(body, syntax)?);
+        block(out, toks!["do"], nested, syntax);
+    } else {
+        block(out, toks!["try"], block_statements(body, syntax)?, syntax);
+    }
+
+    for catch in catches {
+        let Catch { ty, name, body } = catch;
+        let name = ident::escape::<C>(name);
+
+        let header = match (exceptions, ty) {
+            (Exceptions::Typed { .. }, Some(ty)) => toks!["catch (", ty, " ", name, ")"],
+            (Exceptions::Typed { any }, None) => toks!["catch (", any, " ", name, ")"],
+            (Exceptions::Untyped, None) => toks!["catch (", name, ")"],
+            (Exceptions::Untyped, Some(_)) => {
+                return Err(Error::unsupported("typed catch clauses"));
+            }
+            (Exceptions::On, Some(ty)) => toks!["on ", ty, " catch (", name, ")"],
+            (Exceptions::On, None) => toks!["catch (", name, ")"],
+            (Exceptions::Do, Some(ty)) => toks!["catch let ", name, " as ", ty],
+            (Exceptions::Do, None) => toks!["catch let ", name],
+            (Exceptions::Except, Some(ty)) => toks!["except ", ty, " as ", name],
+            (Exceptions::Except, None) => toks!["except Exception as ", name],
+        };
+
+        let header = continuation(header, syntax);
+        block(out, header, block_statements(body, syntax)?, syntax);
+    }
+
+    if !finally.is_empty() {
+        let header = continuation(toks!["finally"], syntax);
+        block(out, header, block_statements(finally, syntax)?, syntax);
+    }
+
+    close(out, syntax);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render, var, BinaryOp, Catch, Expr, Stmt, UnaryOp};
+    use {dart, go, java, swift, ErrorKind, Go, Java, JavaScript, Python, Rust, Tokens, Value};
+
+    fn body<C>() -> Vec<Stmt<'static, C>> {
+        vec![
+            Stmt::Var("total".into(), Value::Int(0).into()),
+            Stmt::ForEach(
+                "item".into(),
+                var("items"),
+                vec![Stmt::If(
+                    var("item")
+                        .field("price")
+                        .op(BinaryOp::Gt, Value::Int(10).into()),
+                    vec![Stmt::Continue],
+                    vec![Stmt::If(
+                        var("item").method("isFree", vec![]),
+                        vec![],
+                        vec![Stmt::Assign(
+                            var("total"),
+                            var("total").op(BinaryOp::Add, var("item").field("price")),
+                        )],
+                    )],
+                )],
+            ),
+            Stmt::Return(Some(var("total"))),
+        ]
+    }
+
+    #[test]
+    fn test_java() {
+        let toks: Tokens<Java> = render(body()).unwrap();
+
+        assert_eq!(
+            vec![
+                "var total = 0;",
+                "for (var item : items) {",
+                "  if (item.price > 10) {",
+                "    continue;",
+                "  } else if (item.isFree()) {",
+                "  } else {",
+                "    total = total + item.price;",
+                "  }",
+                "}",
+                "return total;",
+            ],
+            toks.to_string().unwrap().lines().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_python() {
+        let toks: Tokens<Python> = render(body()).unwrap();
+
+        assert_eq!(
+            vec![
+                "total = 0",
+                "for item in items:",
+                "  if item.price > 10:",
+                "    continue",
+                "  elif item.isFree():",
+                "    pass",
+                "  else:",
+                "    total = total + item.price",
+                "return total",
+            ],
+            toks.to_string().unwrap().lines().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_go() {
+        let toks: Tokens<Go> = render(body()).unwrap();
+
+        assert_eq!(
+            vec![
+                "total := 0",
+                "for _, item := range items {",
+                "\tif item.price > 10 {",
+                "\t\tcontinue",
+                "\t} else if item.isFree() {",
+                "\t} else {",
+                "\t\ttotal = total + item.price",
+                "\t}",
+                "}",
+                "return total",
+            ],
+            toks.to_string_with(go::Extra::from_package("main"))
+                .unwrap()
+                .lines()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_precedence() {
+        let a = || var("a");
+        let b = || var("b");
+
+        let expr: Expr<Rust> = a()
+            .op(BinaryOp::Sub, b().op(BinaryOp::Sub, a()))
+            .op(BinaryOp::Mul, b())
+            .op(
+                BinaryOp::Eq,
+                Expr::Unary(UnaryOp::Not, Box::new(a().op(BinaryOp::Or, b()))),
+            );
+
+        let toks: Tokens<Rust> = toks![expr];
+        assert_eq!("(a - (b - a)) * b == !(a || b)", toks.to_string().unwrap());
+    }
+
+    #[test]
+    fn test_negation() {
+        let neg = |e| Expr::Unary(UnaryOp::Neg, Box::new(e));
+
+        let toks: Tokens<Java> = toks![
+            neg(neg(var("a"))),
+            " ",
+            neg(Value::Int(-1).into()),
+            " ",
+            Expr::from(Value::Int(-1)).method("abs", vec![]),
+        ];
+
+        assert_eq!("-(-a) -(-1) (-1).abs()", toks.to_string().unwrap());
+    }
+
+    #[test]
+    fn test_comparisons() {
+        fn exprs<C>() -> Vec<Expr<'static, C>> {
+            let not = |e| Expr::Unary(UnaryOp::Not, Box::new(e));
+
+            vec![
+                not(var("a")).op(BinaryOp::Eq, var("b")),
+                not(var("a").op(BinaryOp::Eq, var("b"))),
+                not(var("a")).op(BinaryOp::And, var("b")),
+                var("a")
+                    .op(BinaryOp::Lt, var("b"))
+                    .op(BinaryOp::Eq, var("c").op(BinaryOp::Lt, var("d"))),
+            ]
+        }
+
+        let mut java: Tokens<Java> = Tokens::new();
+        let mut python: Tokens<Python> = Tokens::new();
+
+        for (a, b) in exprs().into_iter().zip(exprs()) {
+            java.push(a);
+            python.push(b);
+        }
+
+        assert_eq!(
+            vec!["!a == b", "!(a == b)", "!a && b", "(a < b) == (c < d)"],
+            java.to_string().unwrap().lines().collect::<Vec<_>>()
+        );
+
+        assert_eq!(
+            vec![
+                "(not a) == b",
+                "not a == b",
+                "not a and b",
+                "(a < b) == (c < d)",
+            ],
+            python.to_string().unwrap().lines().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_lambda() {
+        fn lambda<C>() -> Expr<'static, C> {
+            Expr::Lambda(vec!["a".into(), "in".into()], Box::new(var("a")))
+        }
+
+        let js: Tokens<JavaScript> = toks![lambda::<JavaScript>()];
+        let python: Tokens<Python> = toks![lambda::<Python>()];
+        let rust: Tokens<Rust> = toks![lambda::<Rust>()];
+        let swift: Tokens<swift::Swift> = toks![lambda::<swift::Swift>()];
+
+        assert_eq!("(a, in_) => a", js.to_string().unwrap());
+        assert_eq!("lambda a, in_: a", python.to_string().unwrap());
+        assert_eq!("|a, r#in| a", rust.to_string().unwrap());
+        assert_eq!("{ a, `in` in a }", swift.to_string().unwrap());
+    }
+
+    #[test]
+    fn test_imports() {
+        let list = java::imported("java.util", "ArrayList");
+        let body: Vec<Stmt<Java>> = vec![Stmt::Let("list".into(), Expr::New(list, vec![]))];
+
+        let mut toks = Tokens::new();
+        toks.push(render(body).unwrap());
+
+        assert_eq!(
+            "import java.util.ArrayList;\n\nfinal var list = new ArrayList();\n",
+            toks.to_file().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_try() {
+        fn body<C>(ty: C) -> Vec<Stmt<'static, C>> {
+            vec![Stmt::Try(
+                vec![Stmt::Expr(var("run").call(vec![]))],
+                vec![Catch::new("e", vec![Stmt::Return(None)]).ty(ty)],
+                vec![Stmt::Expr(var("close").call(vec![]))],
+            )]
+        }
+
+        let dart: Tokens<dart::Dart> = render(body(dart::local("IOException"))).unwrap();
+        let swift: Tokens<swift::Swift> = render(body(swift::local("IOError"))).unwrap();
+
+        assert_eq!(
+            vec![
+                "try {",
+                "  run();",
+                "} on IOException catch (e) {",
+                "  return;",
+                "} finally {",
+                "  close();",
+                "}",
+            ],
+            dart.to_string().unwrap().lines().collect::<Vec<_>>()
+        );
+
+        assert_eq!(
+            vec![
+                "do {",
+                "  defer {",
+                "    close()",
+                "  }",
+                "  do {",
+                "    run()",
+                "  } catch let e as IOError {",
+                "    return",
+                "  }",
+                "}",
+            ],
+            swift.to_string().unwrap().lines().collect::<Vec<_>>()
+        );
+
+        let finally_only: Vec<Stmt<swift::Swift>> = vec![Stmt::Try(
+            vec![Stmt::Expr(var("run").call(vec![]))],
+            vec![],
+            vec![Stmt::Expr(var("close").call(vec![]))],
+        )];
+
+        assert_eq!(
+            vec!["do {", "  defer {", "    close()", "  }", "  run()", "}"],
+            render(finally_only)
+                .unwrap()
+                .to_string()
+                .unwrap()
+                .lines()
+                .collect::<Vec<_>>()
+        );
+
+        let error = render(body(go::local("error"))).unwrap_err();
+        assert_eq!(
+            &ErrorKind::Unsupported("try statements".into()),
+            error.kind()
+        );
+    }
+}
@@ -0,0 +1,579 @@
+//! Templates with placeholders, rendered into tokens.
+//!
+//! A template is plain text of the target language with the following tags:
+//!
+//! * `{{name}}` - A placeholder, substituted with the value bound to `name`. Lines broken inside
+//!   of the value are aligned with the column of the placeholder.
+//! * `{{#name}}...{{/name}}` - A section, rendered once if `name` is a flag which is set or a
+//!   value which is not empty, and once for every scope if `name` is a list.
+//! * `{{^name}}...{{/name}}` - An inverted section, rendered if `name` is a flag which is not set,
+//!   an empty value, or an empty list.
+//! * `{{! comment }}` - A comment, which is not rendered.
+//! * `{{{{` - A literal `{{`.
+//!
+//! Lines containing nothing but a section or a comment are removed from the output. Leading tabs
+//! are rendered as levels of indentation, using the indentation of the formatter, while other
+//! leading whitespace is kept as is.
+//!
+//! Since values are tokens, they can be custom elements of the language, which are imported like
+//! anywhere else.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rstgen::template::{Scope, Template};
+//! use rstgen::{java, Java, Tokens};
+//!
+//! let template = Template::parse(concat!(
+//!     "class {{name}} {\n",
+//!     "{{#fields}}\n",
+//!     "  private {{type}} {{name}} = {{default}};\n",
+//!     "{{/fields}}\n",
+//!     "}\n",
+//! ))
+//! .unwrap();
+//!
+//! let scope = Scope::new().value("name", "Foo").list(
+//!     "fields",
+//!     vec![Scope::new()
+//!         .value("type", java::imported("java.util", "List"))
+//!         .value("name", "names")
+//!         .value("default", "null")],
+//! );
+//!
+//! let toks: Tokens<Java> = template.render(&scope).unwrap();
+//!
+//! assert_eq!(
+//!     "import java.util.List;\n\nclass Foo {\n  private List names = null;\n}\n",
+//!     toks.to_file().unwrap()
+//! );
+//! ```
+
+use con_::Con;
+use std::collections::HashMap;
+use {Element, Error, IntoTokens, Result, Tokens};
+
+/// A parsed template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+/// A node of a parsed template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    /// Literal text, without any line breaks.
+    Text(String),
+    /// A line break.
+    Line,
+    /// A placeholder, at the given line.
+    Placeholder(String, usize),
+    /// A section, which is inverted or not, at the given line.
+    Section(String, bool, Vec<Node>, usize),
+}
+
+/// A tag of a template.
+enum Tag {
+    Placeholder(String),
+    Open(String, bool),
+    Close(String),
+    Comment,
+}
+
+/// A piece of a line of a template.
+enum Piece {
+    Text(String),
+    Tag(Tag),
+}
+
+impl Template {
+    /// Parse the given template.
+    pub fn parse(source: &str) -> Result<Template> {
+        let mut lines = source.split('\n').collect::<Vec<_>>();
+
+        if source.ends_with('\n') {
+            lines.pop();
+        }
+
+        // Sections which are open, with the nodes before them and the line they were opened at.
+        let mut stack = Vec::new();
+        let mut nodes = Vec::new();
+
+        for (n, line) in lines.iter().enumerate() {
+            let number = n + 1;
+            let pieces = pieces(line, number)?;
+
+            let standalone = {
+                let mut tags = pieces.iter().filter(|p| match **p {
+                    Piece::Text(ref text) => !text.trim().is_empty(),
+                    Piece::Tag(_) => true,
+                });
+
+                match (tags.next(), tags.next()) {
+                    (Some(Piece::Tag(tag)), None) => !matches!(*tag, Tag::Placeholder(_)),
+                    _ => false,
+                }
+            };
+
+            for piece in pieces {
+                match piece {
+                    Piece::Text(text) => {
+                        if !standalone {
+                            nodes.push(Node::Text(text));
+                        }
+                    }
+                    Piece::Tag(Tag::Placeholder(name)) => {
+                        nodes.push(Node::Placeholder(name, number));
+                    }
+                    Piece::Tag(Tag::Open(name, inverted)) => {
+                        let outer = ::std::mem::take(&mut nodes);
+                        stack.push((name, inverted, outer, number));
+                    }
+                    Piece::Tag(Tag::Close(name)) => match stack.pop() {
+                        Some((open, inverted, outer, line)) if open == name => {
+                            let inner = ::std::mem::replace(&mut nodes, outer);
+                            nodes.push(Node::Section(name, inverted, inner, line));
+                        }
+                        _ => {
+                            return Err(Error::template(
+                                number,
+                                format!("unexpected end of section `{}`", name),
+                            ))
+                        }
+                    },
+                    Piece::Tag(Tag::Comment) => {}
+                }
+            }
+
+            if !standalone && number < lines.len() {
+                nodes.push(Node::Line);
+            }
+        }
+
+        if let Some((name, _, _, line)) = stack.pop() {
+            return Err(Error::template(
+                line,
+                format!("section `{}` is not closed", name),
+            ));
+        }
+
+        Ok(Template { nodes })
+    }
+
+    /// Render the template with the names bound in the given scope.
+    ///
+    /// Fails if the template references a name which is not bound.
+    pub fn render<'el, C>(&self, scope: &Scope<'el, C>) -> Result<Tokens<'el, C>>
+    where
+        C: Clone + PartialEq + Eq,
+    {
+        let mut segments = Vec::new();
+        render(&self.nodes, &mut vec![scope], &mut segments)?;
+
+        let mut out = Tokens::new();
+
+        for (n, line) in segments.split(|s| *s == Segment::Line).enumerate() {
+            if n > 0 {
+                out.append(Element::PushSpacing);
+            }
+
+            let blank = line.iter().all(|s| match *s {
+                Segment::Text(ref text) => text.trim().is_empty(),
+                _ => false,
+            });
+
+            if blank {
+                out.append(Element::Line);
+                continue;
+            }
+
+            let mut indent = (0, 0);
+            let mut content = Tokens::new();
+
+            for segment in line {
+                match *segment {
+                    Segment::Text(ref text) => {
+                        let mut text = text.as_str();
+
+                        if content.is_empty() {
+                            let trimmed = text.trim_start();
+                            let (levels, columns) =
+                                indentation(&text[..text.len() - trimmed.len()]);
+                            indent = (indent.0 + levels, indent.1 + columns);
+                            text = trimmed;
+                        }
+
+                        if !text.is_empty() {
+                            content.append(text.to_string());
+                        }
+                    }
+                    Segment::Value(ref value) => {
+                        content.append(Element::Align(0, Con::Owned(value.clone())));
+                    }
+                    Segment::Line => {}
+                }
+            }
+
+            let (levels, columns) = indent;
+            let mut line = toks![Element::Align(columns, Con::Owned(content))];
+
+            for _ in 0..levels {
+                line = toks![Element::Indent(Con::Owned(line))];
+            }
+
+            out.append(line);
+        }
+
+        Ok(out)
+    }
+}
+
+/// Names bound to values, flags, and lists of nested scopes, used to render a template.
+///
+/// Names which are not bound in a nested scope are looked up in the scopes enclosing it.
+#[derive(Debug, Clone)]
+pub struct Scope<'el, C: 'el> {
+    bindings: HashMap<String, Binding<'el, C>>,
+}
+
+#[derive(Debug, Clone)]
+enum Binding<'el, C: 'el> {
+    Value(Tokens<'el, C>),
+    Flag(bool),
+    List(Vec<Scope<'el, C>>),
+}
+
+impl<'el, C: 'el> Scope<'el, C> {
+    /// Create a new, empty scope.
+    pub fn new() -> Scope<'el, C> {
+        Scope {
+            bindings: HashMap::new(),
+        }
+    }
+
+    /// Bind the given name to a value.
+    pub fn value<N, T>(mut self, name: N, value: T) -> Scope<'el, C>
+    where
+        N: Into<String>,
+        T: IntoTokens<'el, C>,
+    {
+        self.bindings
+            .insert(name.into(), Binding::Value(value.into_tokens()));
+        self
+    }
+
+    /// Bind the given name to a flag.
+    pub fn flag<N>(mut self, name: N, flag: bool) -> Scope<'el, C>
+    where
+        N: Into<String>,
+    {
+        self.bindings.insert(name.into(), Binding::Flag(flag));
+        self
+    }
+
+    /// Bind the given name to a list of nested scopes.
+    pub fn list<N, I>(mut self, name: N, scopes: I) -> Scope<'el, C>
+    where
+        N: Into<String>,
+        I: IntoIterator<Item = Scope<'el, C>>,
+    {
+        self.bindings
+            .insert(name.into(), Binding::List(scopes.into_iter().collect()));
+        self
+    }
+}
+
+impl<'el, C: 'el> Default for Scope<'el, C> {
+    fn default() -> Self {
+        Scope::new()
+    }
+}
+
+/// A rendered segment of a template.
+#[derive(Debug, PartialEq, Eq)]
+enum Segment<'el, C: 'el> {
+    Text(String),
+    Value(Tokens<'el, C>),
+    Line,
+}
+
+/// Render the given nodes into segments.
+fn render<'el, C>(
+    nodes: &[Node],
+    scopes: &mut Vec<&Scope<'el, C>>,
+    out: &mut Vec<Segment<'el, C>>,
+) -> Result<()>
+where
+    C: Clone + PartialEq + Eq,
+{
+    for node in nodes {
+        match *node {
+            Node::Text(ref text) => out.push(Segment::Text(text.clone())),
+            Node::Line => out.push(Segment::Line),
+            Node::Placeholder(ref name, line) => match *lookup(scopes, name, line)? {
+                Binding::Value(ref value) => out.push(Segment::Value(value.clone())),
+                _ => return Err(Error::template(line, format!("`{}` is not a value", name))),
+            },
+            Node::Section(ref name, inverted, ref nodes, line) => {
+                let binding = lookup(scopes, name, line)?;
+
+                let set = match *binding {
+                    Binding::Value(ref value) => !value.is_empty(),
+                    Binding::Flag(flag) => flag,
+                    Binding::List(ref list) => !list.is_empty(),
+                };
+
+                if inverted {
+                    if !set {
+                        render(nodes, scopes, out)?;
+                    }
+
+                    continue;
+                }
+
+                match *binding {
+                    Binding::List(ref list) => {
+                        for scope in list {
+                            scopes.push(scope);
+                            let result = render(nodes, scopes, out);
+                            scopes.pop();
+                            result?;
+                        }
+                    }
+                    _ if set => render(nodes, scopes, out)?,
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Look up the given name, from the innermost to the outermost scope.
+fn lookup<'a, 'el, C>(
+    scopes: &[&'a Scope<'el, C>],
+    name: &str,
+    line: usize,
+) -> Result<&'a Binding<'el, C>> {
+    scopes
+        .iter()
+        .rev()
+        .filter_map(|scope| scope.bindings.get(name))
+        .next()
+        .ok_or_else(|| Error::template(line, format!("`{}` is not bound", name)))
+}
+
+/// Indentation of the given leading whitespace, as the number of tabs and other characters.
+///
+/// Every tab is an indentation level, rendered with the indentation of the formatter, while other
+/// whitespace is kept as alignment.
+fn indentation(whitespace: &str) -> (usize, usize) {
+    let levels = whitespace.chars().filter(|c| *c == '\t').count();
+    (levels, whitespace.chars().count() - levels)
+}
+
+/// Split a single line of a template into pieces.
+fn pieces(mut line: &str, number: usize) -> Result<Vec<Piece>> {
+    let mut out = Vec::new();
+    let mut text = String::new();
+
+    while let Some(start) = line.find("{{") {
+        text.push_str(&line[..start]);
+        line = &line[start + 2..];
+
+        if line.starts_with("{{") {
+            text.push_str("{{");
+            line = &line[2..];
+            continue;
+        }
+
+        let end = match line.find("}}") {
+            Some(end) => end,
+            None => return Err(Error::template(number, "tag is not closed")),
+        };
+
+        let tag = line[..end].trim();
+        line = &line[end + 2..];
+
+        let tag = match tag.chars().next() {
+            Some('#') => Tag::Open(name(&tag[1..], number)?, false),
+            Some('^') => Tag::Open(name(&tag[1..], number)?, true),
+            Some('/') => Tag::Close(name(&tag[1..], number)?),
+            Some('!') => Tag::Comment,
+            _ => Tag::Placeholder(name(tag, number)?),
+        };
+
+        if !text.is_empty() {
+            out.push(Piece::Text(::std::mem::take(&mut text)));
+        }
+
+        out.push(Piece::Tag(tag));
+    }
+
+    text.push_str(line);
+
+    if !text.is_empty() {
+        out.push(Piece::Text(text));
+    }
+
+    Ok(out)
+}
+
+/// Parse the name of a tag.
+fn name(name: &str, number: usize) -> Result<String> {
+    let name = name.trim();
+
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err(Error::template(number, format!("invalid name `{}`", name)));
+    }
+
+    Ok(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Scope, Template};
+    use java::{imported, Java};
+    use {ErrorKind, Quoted, Tokens};
+
+    #[test]
+    fn test_render() {
+        let template = Template::parse(concat!(
+            "class Foo {\n",
+            "  {{! the body of the method is generated }}\n",
+            "  public void run() {\n",
+            "    {{body}}\n",
+            "  }\n",
+            "\n",
+            "  String name = {{name}};\n",
+            "}\n",
+        ))
+        .unwrap();
+
+        let mut body: Tokens<Java> = Tokens::new();
+        body.push(toks![imported("java.util", "List"), " names;"]);
+        body.push("if (names == null) {");
+        body.nested("return;");
+        body.push("}");
+
+        let scope = Scope::new()
+            .value("body", body)
+            .value("name", "foo".quoted());
+        let toks = template.render(&scope).unwrap();
+
+        assert_eq!(
+            concat!(
+                "import java.util.List;\n",
+                "\n",
+                "class Foo {\n",
+                "  public void run() {\n",
+                "    List names;\n",
+                "    if (names == null) {\n",
+                "      return;\n",
+                "    }\n",
+                "  }\n",
+                "\n",
+                "  String name = \"foo\";\n",
+                "}\n",
+            ),
+            toks.to_file().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_sections() {
+        let template = Template::parse(concat!(
+            "enum {{name}} {\n",
+            "  {{#variants}}\n",
+            "  {{name}}{{#deprecated}} /* deprecated */{{/deprecated}},\n",
+            "  {{/variants}}\n",
+            "  {{^variants}}\n",
+            "  // no variants\n",
+            "  {{/variants}}\n",
+            "}",
+        ))
+        .unwrap();
+
+        let scope: Scope<Java> = Scope::new().value("name", "Color").list(
+            "variants",
+            vec![
+                Scope::new().value("name", "RED").flag("deprecated", false),
+                Scope::new().value("name", "GREEN").flag("deprecated", true),
+            ],
+        );
+
+        assert_eq!(
+            "enum Color {\n  RED,\n  GREEN /* deprecated */,\n}",
+            template.render(&scope).unwrap().to_string().unwrap()
+        );
+
+        let scope: Scope<Java> = Scope::new().value("name", "Empty").list("variants", vec![]);
+
+        assert_eq!(
+            "enum Empty {\n  // no variants\n}",
+            template.render(&scope).unwrap().to_string().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_align() {
+        let template = Template::parse("return foo({{args}});").unwrap();
+
+        let mut args: Tokens<Java> = Tokens::new();
+        args.append("a,");
+        args.push("b");
+
+        let scope = Scope::new().value("args", args);
+
+        assert_eq!(
+            "return foo(a,\n           b);",
+            template.render(&scope).unwrap().to_string().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_tabs() {
+        use go::{Extra, Go};
+
+        let template = Template::parse("func main() {\n\tfmt.Println({{args}})\n}").unwrap();
+
+        let mut args: Tokens<Go> = Tokens::new();
+        args.append("a,");
+        args.push("b");
+
+        let scope = Scope::new().value("args", args);
+
+        assert_eq!(
+            "func main() {\n\tfmt.Println(a,\n\t            b)\n}",
+            template
+                .render(&scope)
+                .unwrap()
+                .to_string_with(Extra::from_package("main"))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = Template::parse("{{#a}}\n{{/b}}").unwrap_err();
+        assert_eq!(
+            &ErrorKind::Template(2, "unexpected end of section `b`".into()),
+            error.kind()
+        );
+
+        let error = Template::parse("{{#a}}\n").unwrap_err();
+        assert_eq!(
+            &ErrorKind::Template(1, "section `a` is not closed".into()),
+            error.kind()
+        );
+
+        let template = Template::parse("a\n{{b}}").unwrap();
+        let error = template.render(&Scope::<Java>::new()).unwrap_err();
+        assert_eq!("template line 2: `b` is not bound", error.to_string());
+
+        let template = Template::parse("{{{{b}}").unwrap();
+        let toks: Tokens<Java> = template.render(&Scope::new()).unwrap();
+        assert_eq!("{{b}}", toks.to_string().unwrap());
+    }
+}
@@ -0,0 +1,497 @@
+//! Helpers to test generators against snapshots of their output.
+//!
+//! Output is compared against golden files in a snapshot directory, which defaults to
+//! `tests/snapshots` in the crate being tested. On a mismatch, a diff from the snapshot to the
+//! output is reported.
+//!
+//! Set the environment variable [`UPDATE`] to `1` to write snapshots with the current output
+//! instead of comparing against them. Snapshots which do not exist fail the comparison otherwise,
+//! so that they aren't silently written where output is only checked, like in CI.
+//!
+//! This module is only available with the `testing` feature.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use rstgen::testing::{assert_snapshot, Snapshots};
+//! use rstgen::{Java, Tokens};
+//!
+//! let mut toks: Tokens<Java> = Tokens::new();
+//! toks.push("class Foo {");
+//! toks.push("}");
+//!
+//! assert_snapshot("Foo.java", &toks);
+//!
+//! // Only compare the words of the output, ignoring how they are spaced.
+//! Snapshots::default()
+//!     .ignore_whitespace()
+//!     .assert("Foo.java", &toks);
+//! ```
+
+use std::borrow::Cow;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use {Custom, Error, Result, Tokens};
+
+/// Environment variable which, when set to anything but `0`, updates snapshots instead of
+/// comparing against them.
+pub const UPDATE: &str = "RSTGEN_UPDATE_SNAPSHOTS";
+
+/// Number of unchanged lines shown around every change in a diff.
+const CONTEXT: usize = 2;
+
+/// How output is compared against a snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compare {
+    /// Output has to match the snapshot exactly.
+    Exact,
+    /// Output has to match the snapshot, ignoring indentation, blank lines, and how words are
+    /// spaced and broken into lines.
+    IgnoreWhitespace,
+}
+
+/// A directory of snapshots to compare output against.
+#[derive(Debug, Clone)]
+pub struct Snapshots {
+    /// Directory the snapshots are stored in.
+    pub dir: PathBuf,
+    /// How output is compared against snapshots.
+    pub compare: Compare,
+    /// If snapshots are overwritten with the output, instead of compared against.
+    pub update: bool,
+}
+
+impl Snapshots {
+    /// Create snapshots stored in the given directory.
+    ///
+    /// Snapshots are updated if the [`UPDATE`] environment variable is set.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Snapshots {
+        let update = match env::var(UPDATE) {
+            Ok(value) => !value.is_empty() && value != "0",
+            Err(_) => false,
+        };
+
+        Snapshots {
+            dir: dir.into(),
+            compare: Compare::Exact,
+            update,
+        }
+    }
+
+    /// Compare output against snapshots, ignoring whitespace.
+    pub fn ignore_whitespace(self) -> Snapshots {
+        Snapshots {
+            compare: Compare::IgnoreWhitespace,
+            ..self
+        }
+    }
+
+    /// Change if snapshots are overwritten with the output, instead of compared against.
+    pub fn update(self, update: bool) -> Snapshots {
+        Snapshots { update, ..self }
+    }
+
+    /// Render the tokens as a file with the given extra, and compare against the snapshot with the
+    /// given name.
+    pub fn check_with<'el, C: Custom>(
+        &self,
+        name: &str,
+        tokens: &Tokens<'el, C>,
+        extra: C::Extra,
+    ) -> Result<()> {
+        self.check_str(name, &tokens.to_file_with(extra)?)
+    }
+
+    /// Compare the given output against the snapshot with the given name.
+    ///
+    /// Fails with [`ErrorKind::Snapshot`](::ErrorKind::Snapshot) if they don't match, and with
+    /// [`ErrorKind::MissingSnapshot`](::ErrorKind::MissingSnapshot) if the snapshot does not
+    /// exist and is not being updated.
+    pub fn check_str(&self, name: &str, output: &str) -> Result<()> {
+        let path = self.dir.join(name);
+
+        if self.update {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            fs::write(&path, output)?;
+            return Ok(());
+        }
+
+        let expected = match fs::read_to_string(&path) {
+            Ok(expected) => expected,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
+                return Err(Error::missing_snapshot(name));
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let matches = match self.compare {
+            Compare::Exact => expected == output,
+            Compare::IgnoreWhitespace => expected.split_whitespace().eq(output.split_whitespace()),
+        };
+
+        if matches {
+            return Ok(());
+        }
+
+        let expected = lines(&expected, self.compare);
+        let output = lines(output, self.compare);
+        Err(Error::snapshot(name, diff(&expected, &output)))
+    }
+
+    /// Render the tokens as a file, and compare against the snapshot with the given name.
+    pub fn check<'el, C>(&self, name: &str, tokens: &Tokens<'el, C>) -> Result<()>
+    where
+        C: Custom,
+        C::Extra: Default,
+    {
+        self.check_with(name, tokens, C::Extra::default())
+    }
+
+    /// Render the tokens as a file, and assert that they match the snapshot with the given name.
+    ///
+    /// # Panics
+    ///
+    /// Panics with a diff if they don't match.
+    #[track_caller]
+    pub fn assert<'el, C>(&self, name: &str, tokens: &Tokens<'el, C>)
+    where
+        C: Custom,
+        C::Extra: Default,
+    {
+        if let Err(e) = self.check(name, tokens) {
+            panic!("{}", e);
+        }
+    }
+}
+
+impl Default for Snapshots {
+    /// Snapshots stored in `tests/snapshots`, relative to the manifest of the crate being tested.
+    fn default() -> Snapshots {
+        let dir = env::var_os("CARGO_MANIFEST_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_default();
+
+        Snapshots::new(dir.join("tests").join("snapshots"))
+    }
+}
+
+/// Assert that the tokens rendered as a file match the snapshot with the given name, in the
+/// default snapshot directory.
+///
+/// # Panics
+///
+/// Panics with a diff if they don't match.
+#[track_caller]
+pub fn assert_snapshot<'el, C>(name: &str, tokens: &Tokens<'el, C>)
+where
+    C: Custom,
+    C::Extra: Default,
+{
+    Snapshots::default().assert(name, tokens);
+}
+
+/// A line of output, compared by its key.
+#[derive(Debug)]
+struct Line<'a> {
+    text: &'a str,
+    key: Cow<'a, str>,
+}
+
+impl<'a> PartialEq for Line<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<'a> AsRef<str> for Line<'a> {
+    fn as_ref(&self) -> &str {
+        self.text
+    }
+}
+
+/// Split output into the lines to diff.
+///
+/// Compared exactly, lines keep any carriage returns, and a trailing line break doesn't start
+/// another line. Ignoring whitespace, blank lines are skipped and lines are compared by their words, so that
+/// only lines with different words show up as changed.
+fn lines(output: &str, compare: Compare) -> Vec<Line<'_>> {
+    match compare {
+        Compare::Exact => output
+            .split_terminator('\n')
+            .map(|text| Line {
+                text,
+                key: Cow::Borrowed(text),
+            })
+            .collect(),
+        Compare::IgnoreWhitespace => output
+            .lines()
+            .filter(|text| !text.trim().is_empty())
+            .map(|text| Line {
+                text,
+                key: Cow::Owned(text.split_whitespace().collect::<Vec<_>>().join(" ")),
+            })
+            .collect(),
+    }
+}
+
+/// Line diff from the expected to the actual lines, with some context around every change.
+///
+/// Removed lines are prefixed with `-`, added lines with `+`, and skipped lines are shown as
+/// `...`.
+fn diff<T>(expected: &[T], actual: &[T]) -> String
+where
+    T: PartialEq + AsRef<str>,
+{
+    let mut lines = Vec::new();
+    edits(expected, actual, &mut lines);
+
+    let changed = lines
+        .iter()
+        .enumerate()
+        .filter(|&(_, &(op, _))| op != ' ')
+        .map(|(n, _)| n)
+        .collect::<Vec<_>>();
+
+    let mut out = String::new();
+    let mut last = None;
+
+    for (n, &(op, line)) in lines.iter().enumerate() {
+        let shown = changed
+            .iter()
+            .any(|&c| n + CONTEXT >= c && n <= c + CONTEXT);
+
+        if !shown {
+            continue;
+        }
+
+        if last.is_some_and(|last| last + 1 < n) || (last.is_none() && n > 0) {
+            out.push_str("...\n");
+        }
+
+        out.push(op);
+        out.push(' ');
+        out.push_str(line.as_ref());
+        out.push('\n');
+        last = Some(n);
+    }
+
+    if last.is_some_and(|last| last + 1 < lines.len()) {
+        out.push_str("...\n");
+    }
+
+    out
+}
+
+/// Edits turning the expected into the actual lines, along a longest common subsequence.
+///
+/// Uses the divide and conquer algorithm by Hirschberg, which only needs space linear in the
+/// number of lines.
+fn edits<'a, T>(expected: &'a [T], actual: &'a [T], out: &mut Vec<(char, &'a T)>)
+where
+    T: PartialEq,
+{
+    let prefix = expected
+        .iter()
+        .zip(actual)
+        .take_while(|&(e, a)| e == a)
+        .count();
+
+    let suffix = expected[prefix..]
+        .iter()
+        .rev()
+        .zip(actual[prefix..].iter().rev())
+        .take_while(|&(e, a)| e == a)
+        .count();
+
+    out.extend(expected[..prefix].iter().map(|line| (' ', line)));
+
+    let e = &expected[prefix..expected.len() - suffix];
+    let a = &actual[prefix..actual.len() - suffix];
+
+    if e.is_empty() {
+        out.extend(a.iter().map(|line| ('+', line)));
+    } else if a.is_empty() {
+        out.extend(e.iter().map(|line| ('-', line)));
+    } else if e.len() == 1 {
+        match a.iter().position(|line| *line == e[0]) {
+            Some(n) => {
+                out.extend(a[..n].iter().map(|line| ('+', line)));
+                out.push((' ', &e[0]));
+                out.extend(a[n + 1..].iter().map(|line| ('+', line)));
+            }
+            None => {
+                out.push(('-', &e[0]));
+                out.extend(a.iter().map(|line| ('+', line)));
+            }
+        }
+    } else {
+        let mid = e.len() / 2;
+        let head = lcs_lengths(e[..mid].iter(), a.iter());
+        let tail = lcs_lengths(e[mid..].iter().rev(), a.iter().rev());
+
+        // split the actual lines where the subsequences of both halves are the longest.
+        let split = (0..=a.len())
+            .max_by_key(|&n| (head[n] + tail[a.len() - n], a.len() - n))
+            .unwrap_or(0);
+
+        edits(&e[..mid], &a[..split], out);
+        edits(&e[mid..], &a[split..], out);
+    }
+
+    out.extend(
+        expected[expected.len() - suffix..]
+            .iter()
+            .map(|line| (' ', line)),
+    );
+}
+
+/// Lengths of the longest common subsequences of the expected lines and every prefix of the
+/// actual lines.
+fn lcs_lengths<'a, T, E, A>(expected: E, actual: A) -> Vec<usize>
+where
+    T: 'a + PartialEq,
+    E: Iterator<Item = &'a T>,
+    A: Iterator<Item = &'a T> + Clone,
+{
+    let mut row = vec![0; actual.clone().count() + 1];
+
+    for e in expected {
+        let mut diagonal = 0;
+
+        for (j, a) in actual.clone().enumerate() {
+            let above = row[j + 1];
+
+            row[j + 1] = if e == a {
+                diagonal + 1
+            } else {
+                above.max(row[j])
+            };
+
+            diagonal = above;
+        }
+    }
+
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff, Snapshots};
+    use java::Java;
+    use std::env;
+    use std::fs;
+    use {ErrorKind, Tokens};
+
+    fn class(field: &'static str) -> Tokens<'static, Java<'static>> {
+        let mut toks = Tokens::new();
+        toks.push("class Foo {");
+        toks.nested(field);
+        toks.push("}");
+        toks
+    }
+
+    #[test]
+    fn test_snapshots() {
+        let dir = env::temp_dir().join(format!("rstgen-snapshots-{}", ::std::process::id()));
+        let snapshots = Snapshots::new(&dir).update(false);
+
+        // missing snapshots are only written when updating.
+        let error = snapshots.check("Foo.java", &class("int a;")).unwrap_err();
+        assert_eq!(&ErrorKind::MissingSnapshot("Foo.java".into()), error.kind());
+        assert!(!dir.join("Foo.java").exists());
+
+        snapshots
+            .clone()
+            .update(true)
+            .check("Foo.java", &class("int a;"))
+            .unwrap();
+        assert_eq!(
+            "class Foo {\n  int a;\n}\n",
+            fs::read_to_string(dir.join("Foo.java")).unwrap()
+        );
+
+        snapshots.check("Foo.java", &class("int a;")).unwrap();
+
+        let error = snapshots.check("Foo.java", &class("int b;")).unwrap_err();
+        assert_eq!(
+            &ErrorKind::Snapshot(
+                "Foo.java".into(),
+                "  class Foo {\n-   int a;\n+   int b;\n  }\n".into()
+            ),
+            error.kind()
+        );
+
+        let mut toks: Tokens<Java> = Tokens::new();
+        toks.push("class Foo");
+        toks.push("{");
+        toks.push("int   a;");
+        toks.push("}");
+
+        assert!(snapshots.check("Foo.java", &toks).is_err());
+        snapshots
+            .clone()
+            .ignore_whitespace()
+            .check("Foo.java", &toks)
+            .unwrap();
+
+        snapshots
+            .update(true)
+            .check("Foo.java", &class("int b;"))
+            .unwrap();
+        assert_eq!(
+            "class Foo {\n  int b;\n}\n",
+            fs::read_to_string(dir.join("Foo.java")).unwrap()
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_ignore_whitespace_diff() {
+        let dir = env::temp_dir().join(format!("rstgen-whitespace-{}", ::std::process::id()));
+        let snapshots = Snapshots::new(&dir).update(true);
+        snapshots
+            .check_str("Foo.java", "class Foo {\n  int a;\n\n  int b;\n}\n")
+            .unwrap();
+
+        let error = snapshots
+            .update(false)
+            .ignore_whitespace()
+            .check_str("Foo.java", "class Foo {\n    int   a;\n    int c;\n}\n")
+            .unwrap_err();
+
+        // lines are shown as written, and only lines with different words are changed.
+        assert_eq!(
+            &ErrorKind::Snapshot(
+                "Foo.java".into(),
+                "  class Foo {\n    int a;\n-   int b;\n+     int c;\n  }\n".into()
+            ),
+            error.kind()
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_diff() {
+        let expected = ["a", "b", "c", "d", "e", "f", "g", "h"];
+        let actual = ["a", "b", "c", "d", "x", "f", "g", "h"];
+
+        assert_eq!(
+            "...\n  c\n  d\n- e\n+ x\n  f\n  g\n...\n",
+            diff(&expected, &actual)
+        );
+
+        assert_eq!("  a\n+ b\n", diff(&["a"], &["a", "b"]));
+        assert_eq!("- a\n  b\n+ c\n", diff(&["a", "b"], &["b", "c"]));
+        assert_eq!(
+            "- a\n+ x\n  b\n- c\n+ y\n  d\n",
+            diff(&["a", "b", "c", "d"], &["x", "b", "y", "d"])
+        );
+    }
+}
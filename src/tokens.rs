@@ -9,22 +9,40 @@
 //! ```
 
 use con_::Con::{self, Borrowed, Owned};
+use debug;
 use element::Element::{Append, Nested, Push};
+use shared::{MaybeSync, Rc};
+use source_map::{Origin, SourceMap};
 use std::collections::LinkedList;
 use std::fmt;
 use std::fmt::Display;
 use std::iter::FromIterator;
-use std::rc::Rc;
 use std::result;
 use std::vec;
-use {Custom, Element, Formatter, IntoTokens, WriteTokens};
+use visit::{self, VisitorMut};
+use {
+    Cons, Custom, Element, Error, Formatter, FormatterConfig, IntoTokens, Lazy, Slot, WriteTokens,
+};
 
 /// A set of tokens.
-#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
 pub struct Tokens<'el, C: 'el> {
     elements: Vec<Element<'el, C>>,
+    /// Where the tokens were created, see [`source_map`](::source_map).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    origin: Option<Origin<'el>>,
 }
 
+/// Origins are not compared, since they don't affect the output.
+impl<'el, C: PartialEq> PartialEq for Tokens<'el, C> {
+    fn eq(&self, other: &Tokens<'el, C>) -> bool {
+        self.elements == other.elements
+    }
+}
+
+impl<'el, C: Eq> Eq for Tokens<'el, C> {}
+
 /// Generic methods.
 impl<'el, C: 'el> Tokens<'el, C>
 where
@@ -34,18 +52,22 @@ where
     pub fn new() -> Tokens<'el, C> {
         Tokens {
             elements: Vec::new(),
+            origin: None,
         }
     }
 
     /// Push a nested definition.
+    #[track_caller]
     pub fn nested<T>(&mut self, tokens: T)
     where
         T: IntoTokens<'el, C>,
     {
-        self.elements.push(Nested(Owned(tokens.into_tokens())));
+        self.elements
+            .push(Nested(Owned(tokens.into_tokens().tracked())));
     }
 
     /// Push a nested definition.
+    #[track_caller]
     pub fn nested_into<B>(&mut self, builder: B) -> ()
     where
         B: FnOnce(&mut Tokens<'el, C>) -> (),
@@ -58,6 +80,7 @@ where
     /// Push a nested definition.
     ///
     /// This is a fallible version that expected the builder to return a result.
+    #[track_caller]
     pub fn try_nested_into<E, B>(&mut self, builder: B) -> Result<(), E>
     where
         B: FnOnce(&mut Tokens<'el, C>) -> Result<(), E>,
@@ -74,14 +97,17 @@ where
     }
 
     /// Push a definition, guaranteed to be preceded with one newline.
+    #[track_caller]
     pub fn push<T>(&mut self, tokens: T)
     where
         T: IntoTokens<'el, C>,
     {
-        self.elements.push(Push(Owned(tokens.into_tokens())));
+        self.elements
+            .push(Push(Owned(tokens.into_tokens().tracked())));
     }
 
     /// Push a new created definition, guaranteed to be preceded with one newline.
+    #[track_caller]
     pub fn push_into<B>(&mut self, builder: B) -> ()
     where
         B: FnOnce(&mut Tokens<'el, C>) -> (),
@@ -94,6 +120,7 @@ where
     /// Push a new created definition, guaranteed to be preceded with one newline.
     ///
     /// This is a fallible version that expected the builder to return a result.
+    #[track_caller]
     pub fn try_push_into<E, B>(&mut self, builder: B) -> Result<(), E>
     where
         B: FnOnce(&mut Tokens<'el, C>) -> Result<(), E>,
@@ -107,6 +134,7 @@ where
     /// Push the given set of tokens, unless it is empty.
     ///
     /// This is useful when you wish to preserve the structure of nested and joined tokens.
+    #[track_caller]
     pub fn push_unless_empty<T>(&mut self, tokens: T)
     where
         T: IntoTokens<'el, C>,
@@ -117,7 +145,73 @@ where
             return;
         }
 
-        self.elements.push(Push(Owned(tokens)));
+        self.elements.push(Push(Owned(tokens.tracked())));
+    }
+
+    /// Push a user region with the given name, guaranteed to be preceded with one newline.
+    ///
+    /// The region is surrounded by marker comments, and renders the given tokens unless the
+    /// formatter carries over the content of the region from a previously generated file.
+    #[track_caller]
+    pub fn region<N, T>(&mut self, name: N, tokens: T)
+    where
+        N: Into<Cons<'el>>,
+        T: IntoTokens<'el, C>,
+    {
+        self.elements.push(Element::Region(
+            name.into(),
+            Owned(tokens.into_tokens().tracked()),
+        ));
+    }
+
+    /// Append a group, whose soft line breaks are laid out flat if it fits on the current line
+    /// together with what follows it, see [`Element::Group`].
+    #[track_caller]
+    pub fn group<T>(&mut self, tokens: T)
+    where
+        T: IntoTokens<'el, C>,
+    {
+        self.elements
+            .push(Element::Group(Owned(tokens.into_tokens().tracked())));
+    }
+
+    /// Append tokens whose lines are indented one more level.
+    #[track_caller]
+    pub fn indent<T>(&mut self, tokens: T)
+    where
+        T: IntoTokens<'el, C>,
+    {
+        self.elements
+            .push(Element::Indent(Owned(tokens.into_tokens().tracked())));
+    }
+
+    /// Append tokens whose lines are aligned with the current column, plus the given number of
+    /// columns.
+    #[track_caller]
+    pub fn align<T>(&mut self, columns: usize, tokens: T)
+    where
+        T: IntoTokens<'el, C>,
+    {
+        self.elements.push(Element::Align(
+            columns,
+            Owned(tokens.into_tokens().tracked()),
+        ));
+    }
+
+    /// Push a slot, which renders the tokens it is filled with through any of its handles.
+    pub fn slot(&mut self, slot: &Slot<'el, C>) {
+        self.elements.push(Push(Owned(toks![slot])));
+    }
+
+    /// Append a lazy element, which produces the tokens to render while being formatted.
+    pub fn lazy<F>(&mut self, lazy: F)
+    where
+        C: Custom,
+        F: Fn(&mut Formatter, &mut C::Extra, usize) -> Result<Tokens<'el, C>, Error>
+            + MaybeSync
+            + 'el,
+    {
+        self.elements.push(Element::Lazy(Lazy::new(lazy)));
     }
 
     /// Push a reference to a definition.
@@ -134,11 +228,23 @@ where
     }
 
     /// Append the given element.
+    #[track_caller]
     pub fn append<E>(&mut self, element: E)
     where
         E: Into<Element<'el, C>>,
     {
-        let element = element.into();
+        let mut element = element.into();
+
+        match element {
+            Append(Owned(ref mut tokens))
+            | Push(Owned(ref mut tokens))
+            | Nested(Owned(ref mut tokens))
+                if tokens.origin.is_none() =>
+            {
+                tokens.origin = Some(Origin::caller());
+            }
+            _ => {}
+        }
 
         if Element::None != element {
             self.elements.push(element);
@@ -153,6 +259,7 @@ where
     /// Append the given set of tokens, unless it is empty.
     ///
     /// This is useful when you wish to preserve the structure of nested and joined tokens.
+    #[track_caller]
     pub fn append_unless_empty<T>(&mut self, tokens: T)
     where
         T: IntoTokens<'el, C>,
@@ -163,7 +270,7 @@ where
             return;
         }
 
-        self.elements.push(Append(Owned(tokens)));
+        self.elements.push(Append(Owned(tokens.tracked())));
     }
 
     /// Extend with another set of tokens.
@@ -175,7 +282,7 @@ where
     }
 
     /// Walk over all elements.
-    pub fn walk_custom(&self) -> WalkCustom<C> {
+    pub fn walk_custom<'a>(&'a self) -> WalkCustom<'a, 'el, C> {
         let mut queue = LinkedList::new();
         queue.extend(self.elements.iter());
         WalkCustom { queue: queue }
@@ -187,6 +294,18 @@ where
             .push(Element::Registered(Con::Rc(Rc::new(custom))));
     }
 
+    /// Wrap the tokens in the given context, which is attached to any error raised while
+    /// formatting them.
+    pub fn with_context<L>(self, context: L) -> Tokens<'el, C>
+    where
+        L: Into<Cons<'el>>,
+    {
+        Tokens {
+            elements: vec![Element::Context(context.into(), Owned(self))],
+            origin: None,
+        }
+    }
+
     /// Check if tokens contain no elements.
     pub fn is_empty(&self) -> bool {
         self.elements.is_empty()
@@ -202,46 +321,220 @@ impl<'el, C> IntoIterator for Tokens<'el, C> {
     }
 }
 
+/// Access to elements and origins.
+impl<'el, C> Tokens<'el, C> {
+    /// Elements of the tokens.
+    pub(crate) fn elements(&self) -> &[Element<'el, C>] {
+        &self.elements
+    }
+
+    /// Elements of the tokens, mutably.
+    pub(crate) fn elements_mut(&mut self) -> &mut Vec<Element<'el, C>> {
+        &mut self.elements
+    }
+
+    /// Tag the tokens with the given origin, which is either a label or a location in the
+    /// generator.
+    ///
+    /// Tokens which are pushed or appended are tagged with the location they were added from,
+    /// unless they already have an origin. See [`source_map`](::source_map).
+    pub fn with_origin<O>(self, origin: O) -> Tokens<'el, C>
+    where
+        O: Into<Origin<'el>>,
+    {
+        Tokens {
+            origin: Some(origin.into()),
+            ..self
+        }
+    }
+
+    /// Where the tokens were created, if known.
+    pub fn origin(&self) -> Option<&Origin<'el>> {
+        self.origin.as_ref()
+    }
+
+    /// Tag the tokens with the location of the caller, unless they already have an origin.
+    #[track_caller]
+    pub(crate) fn tracked(mut self) -> Tokens<'el, C> {
+        if self.origin.is_none() {
+            self.origin = Some(Origin::caller());
+        }
+
+        self
+    }
+}
+
 impl<'el, C: Custom> Tokens<'el, C> {
-    /// Format the tokens.
-    pub fn format(&self, out: &mut Formatter, extra: &mut C::Extra, level: usize) -> fmt::Result {
-        for element in &self.elements {
+    /// Format the given preamble of a file, like its package declaration and imports, followed
+    /// by these tokens.
+    ///
+    /// Every element of the preamble is followed by an empty line, like when joined using
+    /// [`Tokens::join_line_spacing`].
+    pub fn format_with_preamble(
+        &self,
+        preamble: &Tokens<C>,
+        out: &mut Formatter,
+        extra: &mut C::Extra,
+        level: usize,
+    ) -> result::Result<(), Error> {
+        for element in &preamble.elements {
+            if let Element::None = *element {
+                continue;
+            }
+
             element.format(out, extra, level)?;
+            out.new_line_unless_empty()?;
+            out.new_line()?;
         }
 
-        Ok(())
+        out.new_line_unless_empty()?;
+        self.format(out, extra, level)
+    }
+
+    /// Format the tokens.
+    pub fn format(
+        &self,
+        out: &mut Formatter,
+        extra: &mut C::Extra,
+        level: usize,
+    ) -> result::Result<(), Error> {
+        if let Some(ref origin) = self.origin {
+            out.enter_origin(origin);
+        }
+
+        let result = self
+            .elements
+            .iter()
+            .enumerate()
+            .try_for_each(|(n, element)| {
+                if out.is_flat() || !element.has_group() {
+                    return element.format(out, extra, level);
+                }
+
+                // groups need to fit together with what follows them on the same line.
+                let rest = &self.elements[n + 1..];
+
+                let (width, broken) = out.measure(|probe| {
+                    let extra = &mut extra.clone();
+                    rest.iter().try_for_each(|e| e.format(probe, extra, level))
+                });
+
+                let trailing = if broken {
+                    width
+                } else {
+                    width + out.trailing()
+                };
+
+                let previous = out.set_trailing(trailing);
+                let result = element.format(out, extra, level);
+                out.set_trailing(previous);
+                result
+            });
+
+        if self.origin.is_some() {
+            out.exit_origin();
+        }
+
+        result
     }
 
     /// Format token as file with the given extra.
-    pub fn to_file_with(self, mut extra: C::Extra) -> result::Result<String, fmt::Error> {
+    pub fn to_file_with(&self, mut extra: C::Extra) -> result::Result<String, Error> {
         let mut output = String::new();
         output.write_file(self, &mut extra)?;
         Ok(output)
     }
 
     /// Format the tokens with the given extra.
-    pub fn to_string_with(self, mut extra: C::Extra) -> result::Result<String, fmt::Error> {
+    pub fn to_string_with(&self, mut extra: C::Extra) -> result::Result<String, Error> {
         let mut output = String::new();
         output.write_tokens(self, &mut extra)?;
         Ok(output)
     }
+
+    /// Format token as file with the given extra and formatter configuration.
+    pub fn to_file_with_config(
+        &self,
+        mut extra: C::Extra,
+        config: FormatterConfig,
+    ) -> result::Result<String, Error> {
+        let mut output = String::new();
+        output.write_file_with_config(self, &mut extra, config)?;
+        Ok(output)
+    }
+
+    /// Format the tokens with the given extra and formatter configuration.
+    pub fn to_string_with_config(
+        &self,
+        mut extra: C::Extra,
+        config: FormatterConfig,
+    ) -> result::Result<String, Error> {
+        let mut output = String::new();
+        output.write_tokens_with_config(self, &mut extra, config)?;
+        Ok(output)
+    }
+
+    /// Format token as file with the given extra, together with a source map from the output to
+    /// the origins of the tokens.
+    pub fn to_file_mapped_with(
+        &self,
+        mut extra: C::Extra,
+    ) -> result::Result<(String, SourceMap), Error> {
+        let mut output = String::new();
+        let source_map =
+            output.write_file_with_source_map(self, &mut extra, C::default_config())?;
+        Ok((output, source_map))
+    }
+
+    /// Show the structure of the tokens with the given extra, with every element on a line of
+    /// its own, indented by depth.
+    ///
+    /// Custom elements are summarized by rendering them on their own.
+    pub fn debug_tree_with(&self, mut extra: C::Extra) -> result::Result<String, Error> {
+        let mut output = String::new();
+        debug::tree(self, &mut output, &mut extra, 0)?;
+        Ok(output)
+    }
+
+    /// Format the tokens with the given extra, with whitespace made visible.
+    ///
+    /// See [`debug::visible`] for how whitespace is shown.
+    pub fn to_visible_string_with(&self, extra: C::Extra) -> result::Result<String, Error> {
+        Ok(debug::visible(&self.to_string_with(extra)?))
+    }
 }
 
 impl<'el, E: Default, C: Custom<Extra = E>> Tokens<'el, C> {
     /// Format token as file.
-    pub fn to_file(self) -> result::Result<String, fmt::Error> {
+    pub fn to_file(&self) -> result::Result<String, Error> {
         self.to_file_with(C::Extra::default())
     }
 
     /// Format the tokens.
-    pub fn to_string(self) -> result::Result<String, fmt::Error> {
+    pub fn to_string(&self) -> result::Result<String, Error> {
         self.to_string_with(C::Extra::default())
     }
+
+    /// Format token as file, together with a source map from the output to the origins of the
+    /// tokens.
+    pub fn to_file_mapped(&self) -> result::Result<(String, SourceMap), Error> {
+        self.to_file_mapped_with(C::Extra::default())
+    }
+
+    /// Show the structure of the tokens.
+    pub fn debug_tree(&self) -> result::Result<String, Error> {
+        self.debug_tree_with(C::Extra::default())
+    }
+
+    /// Format the tokens, with whitespace made visible.
+    pub fn to_visible_string(&self) -> result::Result<String, Error> {
+        self.to_visible_string_with(C::Extra::default())
+    }
 }
 
-impl<'el, E: Default, C: Custom<Extra = E> + Clone> Display for Tokens<'el, C> {
+impl<'el, E: Default, C: Custom<Extra = E>> Display for Tokens<'el, C> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let str = Tokens::to_string(Tokens::clone(&self)).unwrap();
+        let str = self.to_string().map_err(|_| fmt::Error)?;
         f.write_str(&str)
     }
 }
@@ -251,6 +544,28 @@ impl<'el, C> Tokens<'el, C>
 where
     C: Clone + PartialEq + Eq,
 {
+    /// Replace every custom element, including registered ones, with the result of the given
+    /// function.
+    ///
+    /// Borrowed and shared elements on the way are cloned before they are modified.
+    pub fn map_custom<F>(&mut self, f: F)
+    where
+        F: FnMut(C) -> C,
+    {
+        visit::MapCustom(f).visit_tokens_mut(self);
+    }
+
+    /// Only keep the elements for which the given function returns `true`, in these tokens and
+    /// in all tokens nested inside of them.
+    ///
+    /// Borrowed and shared elements on the way are cloned before they are modified.
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&Element<'el, C>) -> bool,
+    {
+        visit::Retain(f).visit_tokens_mut(self);
+    }
+
     /// Join the set of tokens on the given element.
     pub fn join<E>(self, element: E) -> Tokens<'el, C>
     where
@@ -269,7 +584,10 @@ where
         if let Some(first) = it.next() {
             out.push(first);
         } else {
-            return Tokens { elements: out };
+            return Tokens {
+                elements: out,
+                origin: self.origin,
+            };
         }
 
         while let Some(next) = it.next() {
@@ -277,7 +595,10 @@ where
             out.push(next);
         }
 
-        Tokens { elements: out }
+        Tokens {
+            elements: out,
+            origin: self.origin,
+        }
     }
 
     /// Join with spacing.
@@ -289,6 +610,32 @@ where
     pub fn join_line_spacing(self) -> Tokens<'el, C> {
         self.join(Element::LineSpacing)
     }
+
+    /// Join the set of tokens as a delimited list, like an argument list.
+    ///
+    /// The list is laid out on a single line if it fits, otherwise each item is put on a line
+    /// of its own, indented one level deeper than the delimiters.
+    pub fn join_group<O, S, E>(self, open: O, separator: S, close: E) -> Tokens<'el, C>
+    where
+        O: Into<Element<'el, C>>,
+        S: Into<Element<'el, C>>,
+        E: Into<Element<'el, C>>,
+    {
+        if self.is_empty() {
+            return toks![open.into(), close.into()];
+        }
+
+        let items = self.join(toks![separator.into(), Element::SoftSpacing]);
+        let items = toks![Element::SoftLine, items];
+
+        let mut group = Tokens::new();
+        group.append(open.into());
+        group.append(Element::Indent(Owned(items)));
+        group.append(Element::SoftLine);
+        group.append(close.into());
+
+        toks![Element::Group(Owned(group))]
+    }
 }
 
 impl<'el, C> IntoTokens<'el, C> for Tokens<'el, C> {
@@ -302,6 +649,7 @@ impl<'el, C> IntoTokens<'el, C> for Vec<Tokens<'el, C>> {
     fn into_tokens(self) -> Tokens<'el, C> {
         Tokens {
             elements: self.into_iter().map(Into::into).collect(),
+            origin: None,
         }
     }
 }
@@ -313,6 +661,7 @@ impl<'el, C> IntoTokens<'el, C> for Element<'el, C> {
     fn into_tokens(self) -> Tokens<'el, C> {
         Tokens {
             elements: vec![self],
+            origin: None,
         }
     }
 }
@@ -327,6 +676,7 @@ where
     fn into_tokens(self) -> Tokens<'el, C> {
         Tokens {
             elements: vec![self.into()],
+            origin: None,
         }
     }
 }
@@ -339,6 +689,7 @@ where
     fn into_tokens(self) -> Tokens<'el, C> {
         Tokens {
             elements: vec![self.into()],
+            origin: None,
         }
     }
 }
@@ -348,6 +699,7 @@ impl<'el, C> IntoTokens<'el, C> for &'el str {
     fn into_tokens(self) -> Tokens<'el, C> {
         Tokens {
             elements: vec![self.into()],
+            origin: None,
         }
     }
 }
@@ -359,6 +711,7 @@ impl<'el, C> IntoTokens<'el, C> for String {
     fn into_tokens(self) -> Tokens<'el, C> {
         Tokens {
             elements: vec![self.into()],
+            origin: None,
         }
     }
 }
@@ -369,6 +722,7 @@ impl<'el, C> FromIterator<&'el Element<'el, C>> for Tokens<'el, C> {
     fn from_iter<I: IntoIterator<Item = &'el Element<'el, C>>>(iter: I) -> Tokens<'el, C> {
         Tokens {
             elements: iter.into_iter().map(|e| Element::Borrowed(e)).collect(),
+            origin: None,
         }
     }
 }
@@ -377,16 +731,17 @@ impl<'el, C> FromIterator<Element<'el, C>> for Tokens<'el, C> {
     fn from_iter<I: IntoIterator<Item = Element<'el, C>>>(iter: I) -> Tokens<'el, C> {
         Tokens {
             elements: iter.into_iter().collect(),
+            origin: None,
         }
     }
 }
 
-pub struct WalkCustom<'el, C: 'el> {
-    queue: LinkedList<&'el Element<'el, C>>,
+pub struct WalkCustom<'a, 'el: 'a, C: 'el> {
+    queue: LinkedList<&'a Element<'el, C>>,
 }
 
-impl<'el, C: 'el> Iterator for WalkCustom<'el, C> {
-    type Item = &'el C;
+impl<'a, 'el: 'a, C: 'el> Iterator for WalkCustom<'a, 'el, C> {
+    type Item = &'a C;
 
     fn next(&mut self) -> Option<Self::Item> {
         use self::Element::*;
@@ -400,9 +755,21 @@ impl<'el, C: 'el> Iterator for WalkCustom<'el, C> {
                 Borrowed(ref element) => {
                     self.queue.push_back(element);
                 }
-                Push(ref tokens) | Nested(ref tokens) | Append(ref tokens) => {
+                Push(ref tokens)
+                | Nested(ref tokens)
+                | Append(ref tokens)
+                | Group(ref tokens)
+                | Indent(ref tokens)
+                | Align(_, ref tokens)
+                | Context(_, ref tokens)
+                | Region(_, ref tokens) => {
                     self.queue.extend(tokens.as_ref().elements.iter());
                 }
+                Slot(ref slot) => {
+                    for entry in slot.entries() {
+                        self.queue.extend(entry.elements.iter());
+                    }
+                }
                 Custom(ref custom) => return Some(custom.as_ref()),
                 Registered(ref custom) => return Some(custom.as_ref()),
                 _ => {}
@@ -442,6 +809,154 @@ mod tests {
         assert_eq!("foo bar nope", toks.to_string().unwrap().as_str());
     }
 
+    #[test]
+    fn test_join_group() {
+        use {FormatterConfig, WriteTokens};
+
+        let args = || -> Tokens<()> { toks!("first", "second", "third") };
+
+        let mut out = String::new();
+        let config = FormatterConfig::default().max_width(40);
+        out.write_tokens_with_config(
+            &toks!("call", args().join_group("(", ",", ")")),
+            &mut (),
+            config,
+        )
+        .unwrap();
+        assert_eq!("call(first, second, third)", out);
+
+        let mut out = String::new();
+        let config = FormatterConfig::default().max_width(20);
+        out.write_tokens_with_config(
+            &toks!("call", args().join_group("(", ",", ")")),
+            &mut (),
+            config,
+        )
+        .unwrap();
+        assert_eq!("call(\n  first,\n  second,\n  third\n)", out);
+
+        // the group has to fit together with what follows it on the same line, across levels.
+        let call = toks!(toks!("call", args().join_group("(", ",", ")")), " + rest;");
+        let mut out = String::new();
+        let config = FormatterConfig::default().max_width(30);
+        out.write_tokens_with_config(&toks!(call.clone(), toks!()), &mut (), config)
+            .unwrap();
+        assert_eq!("call(\n  first,\n  second,\n  third\n) + rest;", out);
+
+        let mut out = String::new();
+        let config = FormatterConfig::default().max_width(34);
+        out.write_tokens_with_config(&call, &mut (), config)
+            .unwrap();
+        assert_eq!("call(first, second, third) + rest;", out);
+
+        // output after a line break doesn't count.
+        let mut toks = toks!("call", args().join_group("(", ",", ")"));
+        toks.push("more than fits on the line");
+        let mut out = String::new();
+        let config = FormatterConfig::default().max_width(30);
+        out.write_tokens_with_config(&toks, &mut (), config)
+            .unwrap();
+        assert_eq!(
+            "call(first, second, third)\nmore than fits on the line",
+            out
+        );
+
+        let empty: Tokens<()> = Tokens::new();
+        assert_eq!("()", empty.join_group("(", ",", ")").to_string().unwrap());
+    }
+
+    #[test]
+    fn test_group_indent_align() {
+        use {Element, FormatterConfig, WriteTokens};
+
+        let mut body: Tokens<()> = Tokens::new();
+        body.append(Element::SoftLine);
+        body.append("first,");
+        body.append(Element::SoftSpacing);
+        body.append("second");
+
+        let mut call: Tokens<()> = Tokens::new();
+        call.append("call(");
+        call.indent(body);
+        call.append(Element::SoftLine);
+        call.append(")");
+
+        let mut toks: Tokens<()> = Tokens::new();
+        toks.group(call.clone());
+        assert_eq!("call(first, second)", toks.to_string().unwrap());
+
+        let mut out = String::new();
+        let config = FormatterConfig::default().max_width(10);
+        out.write_tokens_with_config(&toks, &mut (), config)
+            .unwrap();
+        assert_eq!("call(\n  first,\n  second\n)", out);
+
+        let mut toks: Tokens<()> = Tokens::new();
+        toks.append("a = ");
+        toks.align(0, toks!("b +", Element::Line, "c"));
+        assert_eq!("a = b +\n    c", toks.to_string().unwrap());
+    }
+
+    #[test]
+    fn test_group_extra() {
+        use con_::Con;
+        use {Element, Formatter, Result, WriteTokens};
+
+        /// Counts how many times it has been formatted.
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        struct Counted;
+
+        impl Custom for Counted {
+            type Extra = usize;
+
+            fn format(&self, out: &mut Formatter, extra: &mut usize, _: usize) -> Result<()> {
+                *extra += 1;
+                out.write_str("counted")?;
+                Ok(())
+            }
+        }
+
+        let mut toks: Tokens<Counted> = Tokens::new();
+        toks.append(Element::Group(Con::Owned(toks!("(", Counted, ")"))));
+
+        let mut count = 0;
+        let mut out = String::new();
+        out.write_tokens(&toks, &mut count).unwrap();
+
+        assert_eq!("(counted)", out);
+        assert_eq!(1, count);
+    }
+
+    #[test]
+    fn test_render_by_reference() {
+        let toks: Tokens<()> = toks!("foo", "bar").join_spacing();
+
+        assert_eq!("foo bar", toks.to_string().unwrap());
+        assert_eq!("foo bar\n", toks.to_file().unwrap());
+        assert_eq!("foo bar", format!("{}", toks));
+    }
+
+    #[test]
+    fn test_display_error() {
+        use std::fmt::Write;
+        use {Error, Formatter, Result};
+
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        struct Failing;
+
+        impl Custom for Failing {
+            type Extra = ();
+
+            fn format(&self, _: &mut Formatter, _: &mut (), _: usize) -> Result<()> {
+                Err(Error::custom("failing"))
+            }
+        }
+
+        let toks: Tokens<Failing> = toks!("foo", Failing);
+        let mut out = String::new();
+        assert!(write!(out, "{}", toks).is_err());
+    }
+
     #[test]
     fn test_walk_custom() {
         let mut toks: Tokens<Lang> = Tokens::new();
@@ -462,4 +977,44 @@ mod tests {
 
         assert_eq!(expected, output);
     }
+
+    #[test]
+    fn test_walk_custom_slot() {
+        use Slot;
+
+        let slot = Slot::new("helpers");
+        slot.push(toks!(Lang(1))).unwrap();
+
+        let mut toks: Tokens<Lang> = Tokens::new();
+        toks.slot(&slot);
+
+        let output: Vec<_> = toks.walk_custom().cloned().collect();
+        assert_eq!(vec![Lang(1)], output);
+
+        // Walking the tokens doesn't freeze the slot.
+        slot.push(toks!(Lang(2))).unwrap();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use java::{self, Java};
+        use serde_json;
+        use {Cons, Element, IntoOwned, Quoted};
+
+        let mut toks: Tokens<Java> = Tokens::new();
+        toks.push(toks!["class Foo {"]);
+        toks.nested(toks![
+            java::imported("java.util", "List"),
+            " a = ",
+            "b".quoted(),
+            ";"
+        ]);
+        toks.push("}");
+        toks.push(Element::LineComment(vec![Cons::from("comment")]));
+
+        let json = serde_json::to_string(&toks).unwrap();
+        // Borrowed strings are deserialized into owned strings.
+        assert_eq!(toks.into_owned(), serde_json::from_str(&json).unwrap());
+    }
 }
@@ -0,0 +1,304 @@
+//! Language-neutral literal values.
+//!
+//! Values are converted into tokens using the literal syntax of the language when they are
+//! turned into an [`Element`], so that any types referenced by a literal are imported.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rstgen::{Java, Python, Tokens, Value};
+//!
+//! let mut java: Tokens<Java> = Tokens::new();
+//! java.append(Value::Long(42));
+//! assert_eq!("42L", java.to_string().unwrap());
+//!
+//! let mut python: Tokens<Python> = Tokens::new();
+//! python.append(Value::List(vec![Value::Null, Value::Bool(true)]));
+//! assert_eq!("[None, True]", python.to_string().unwrap());
+//! ```
+
+use con_::Con;
+use shared::Rc;
+use std::fmt;
+use {Cons, Custom, Element, IntoTokens, Tokens};
+
+/// A literal value.
+#[derive(Debug, Clone)]
+pub enum Value<'el, C: 'el> {
+    /// The null value, like `null`, `nil`, or `None`.
+    Null,
+    /// A boolean.
+    Bool(bool),
+    /// A 32-bit integer.
+    Int(i32),
+    /// A 64-bit integer.
+    Long(i64),
+    /// A 32-bit floating point number.
+    Float(f32),
+    /// A 64-bit floating point number.
+    Double(f64),
+    /// A single character.
+    Char(char),
+    /// A string.
+    String(Cons<'el>),
+    /// A fixed-size array with elements of the given type.
+    Array(C, Vec<Value<'el, C>>),
+    /// A list of values.
+    List(Vec<Value<'el, C>>),
+    /// A map from keys to values.
+    Map(Vec<(Value<'el, C>, Value<'el, C>)>),
+    /// A tuple of values.
+    Tuple(Vec<Value<'el, C>>),
+}
+
+/// Floating point numbers are compared by their bit patterns, so that values are `Eq`.
+impl<'el, C: PartialEq> PartialEq for Value<'el, C> {
+    fn eq(&self, other: &Self) -> bool {
+        use self::Value::*;
+
+        match (self, other) {
+            (Null, Null) => true,
+            (Bool(a), Bool(b)) => a == b,
+            (Int(a), Int(b)) => a == b,
+            (Long(a), Long(b)) => a == b,
+            (Float(a), Float(b)) => a.to_bits() == b.to_bits(),
+            (Double(a), Double(b)) => a.to_bits() == b.to_bits(),
+            (Char(a), Char(b)) => a == b,
+            (String(a), String(b)) => a == b,
+            (Array(at, a), Array(bt, b)) => at == bt && a == b,
+            (List(a), List(b)) => a == b,
+            (Map(a), Map(b)) => a == b,
+            (Tuple(a), Tuple(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl<'el, C: Eq> Eq for Value<'el, C> {}
+
+impl<'el, C: Custom> From<Value<'el, C>> for Element<'el, C> {
+    fn from(value: Value<'el, C>) -> Self {
+        Element::Append(Con::Owned(C::literal(value)))
+    }
+}
+
+impl<'el, C: Custom> IntoTokens<'el, C> for Value<'el, C> {
+    fn into_tokens(self) -> Tokens<'el, C> {
+        C::literal(self)
+    }
+}
+
+impl<'el, C> From<bool> for Value<'el, C> {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl<'el, C> From<i32> for Value<'el, C> {
+    fn from(value: i32) -> Self {
+        Value::Int(value)
+    }
+}
+
+impl<'el, C> From<i64> for Value<'el, C> {
+    fn from(value: i64) -> Self {
+        Value::Long(value)
+    }
+}
+
+impl<'el, C> From<f32> for Value<'el, C> {
+    fn from(value: f32) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl<'el, C> From<f64> for Value<'el, C> {
+    fn from(value: f64) -> Self {
+        Value::Double(value)
+    }
+}
+
+impl<'el, C> From<char> for Value<'el, C> {
+    fn from(value: char) -> Self {
+        Value::Char(value)
+    }
+}
+
+impl<'el, C> From<&'el str> for Value<'el, C> {
+    fn from(value: &'el str) -> Self {
+        Value::String(value.into())
+    }
+}
+
+impl<'el, C> From<String> for Value<'el, C> {
+    fn from(value: String) -> Self {
+        Value::String(value.into())
+    }
+}
+
+impl<'el, C> From<Rc<String>> for Value<'el, C> {
+    fn from(value: Rc<String>) -> Self {
+        Value::String(value.into())
+    }
+}
+
+/// Convert a value into tokens using a C-like literal syntax.
+///
+/// This is the default implementation of [`Custom::literal`], which languages can fall back to
+/// for values which don't need special treatment. Nested values are converted using the literal
+/// syntax of the language.
+pub fn literal<'el, C: Custom>(value: Value<'el, C>) -> Tokens<'el, C> {
+    use self::Value::*;
+
+    match value {
+        Null => raw("null"),
+        Bool(value) => raw(if value { "true" } else { "false" }),
+        Int(value) => raw(value.to_string()),
+        Long(value) => raw(value.to_string()),
+        Float(value) if !value.is_finite() => {
+            non_finite(value.into(), "NaN", "Infinity", "-Infinity")
+        }
+        Double(value) if !value.is_finite() => non_finite(value, "NaN", "Infinity", "-Infinity"),
+        Float(value) => raw(float(value)),
+        Double(value) => raw(float(value)),
+        Char(value) => raw(char_literal(value)),
+        String(value) => vec![Element::Quoted(value)].into_iter().collect(),
+        Array(_, values) | List(values) => sequence("[", values, "]"),
+        Map(entries) => map("{", entries, ": ", "}"),
+        Tuple(values) => sequence("(", values, ")"),
+    }
+}
+
+/// Tokens for a raw literal.
+pub fn raw<'el, C, L: Into<Cons<'el>>>(literal: L) -> Tokens<'el, C> {
+    vec![Element::Literal(literal.into())].into_iter().collect()
+}
+
+/// Tokens for a sequence of values, separated by commas and surrounded by the given delimiters.
+pub fn sequence<'el, C: Custom>(
+    open: &'static str,
+    values: Vec<Value<'el, C>>,
+    close: &'static str,
+) -> Tokens<'el, C> {
+    let mut elements = vec![Element::Literal(open.into())];
+
+    for (n, value) in values.into_iter().enumerate() {
+        if n > 0 {
+            elements.push(Element::Literal(", ".into()));
+        }
+
+        elements.push(value.into());
+    }
+
+    elements.push(Element::Literal(close.into()));
+    elements.into_iter().collect()
+}
+
+/// Tokens for a tuple, where a tuple with a single value is marked with a trailing comma.
+pub fn tuple<'el, C: Custom>(values: Vec<Value<'el, C>>) -> Tokens<'el, C> {
+    if values.len() == 1 {
+        return sequence("(", values, ",)");
+    }
+
+    sequence("(", values, ")")
+}
+
+/// Tokens for a sequence of map entries, separated by commas and surrounded by the given
+/// delimiters. Keys are separated from values by `separator`.
+pub fn map<'el, C: Custom>(
+    open: &'static str,
+    entries: Vec<(Value<'el, C>, Value<'el, C>)>,
+    separator: &'static str,
+    close: &'static str,
+) -> Tokens<'el, C> {
+    let mut elements = vec![Element::Literal(open.into())];
+
+    for (n, (key, value)) in entries.into_iter().enumerate() {
+        if n > 0 {
+            elements.push(Element::Literal(", ".into()));
+        }
+
+        elements.push(key.into());
+        elements.push(Element::Literal(separator.into()));
+        elements.push(value.into());
+    }
+
+    elements.push(Element::Literal(close.into()));
+    elements.into_iter().collect()
+}
+
+/// Format a floating point number, making sure that it is not mistaken for an integer.
+///
+/// Only finite numbers have a literal syntax, see [`non_finite`] for the others.
+pub fn float<F: fmt::Debug>(value: F) -> String {
+    format!("{:?}", value)
+}
+
+/// Tokens for a floating point number which is not finite, picking between the given tokens for
+/// NaN, positive infinity, and negative infinity.
+pub fn non_finite<'el, C, T>(value: f64, nan: T, infinity: T, neg_infinity: T) -> Tokens<'el, C>
+where
+    T: IntoTokens<'el, C>,
+{
+    if value.is_nan() {
+        nan.into_tokens()
+    } else if value > 0.0 {
+        infinity.into_tokens()
+    } else {
+        neg_infinity.into_tokens()
+    }
+}
+
+/// Format a character literal, surrounded by single quotes.
+pub fn char_literal(value: char) -> String {
+    match value {
+        '\'' => String::from("'\\''"),
+        '\\' => String::from("'\\\\'"),
+        '\n' => String::from("'\\n'"),
+        '\r' => String::from("'\\r'"),
+        '\t' => String::from("'\\t'"),
+        c => format!("'{}'", c),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Value;
+    use Tokens;
+
+    #[test]
+    fn test_default() {
+        let value: Value<()> = Value::Map(vec![
+            (Value::from("a"), Value::List(vec![1.into(), 2.5.into()])),
+            (
+                Value::from('\''),
+                Value::Tuple(vec![Value::Null, true.into()]),
+            ),
+        ]);
+
+        let toks: Tokens<()> = toks![value];
+        assert_eq!(
+            "{a: [1, 2.5], '\\'': (null, true)}",
+            toks.to_string().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_non_finite() {
+        let toks: Tokens<()> = toks![
+            Value::Double(f64::NAN),
+            " ",
+            Value::Float(f32::INFINITY),
+            " ",
+            Value::Double(f64::NEG_INFINITY),
+        ];
+        assert_eq!("NaN Infinity -Infinity", toks.to_string().unwrap());
+    }
+
+    #[test]
+    fn test_eq() {
+        let a: Value<()> = Value::Double(0.1);
+        assert_eq!(a, Value::Double(0.1));
+        assert!(a != Value::Float(0.1));
+    }
+}
@@ -0,0 +1,295 @@
+//! Traversal of the whole element tree of a set of tokens.
+//!
+//! [`Visitor`] visits every element, including the elements behind refcounted and borrowed
+//! elements, and the entries of slots. [`VisitorMut`] does the same mutably, cloning borrowed and
+//! shared elements before they are visited, except for slots, which can't be modified through
+//! the tokens they are placed in.
+//!
+//! Tokens produced by lazy elements are not visited, since they only exist while formatting.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use rstgen::visit::VisitorMut;
+//! use rstgen::{Cons, Element, Java, Tokens};
+//!
+//! /// Rename an identifier everywhere.
+//! struct Rename;
+//!
+//! impl<'el> VisitorMut<'el, Java<'el>> for Rename {
+//!     fn visit_element_mut(&mut self, element: &mut Element<'el, Java<'el>>) {
+//!         if let Element::Literal(ref mut literal) = *element {
+//!             if literal.as_ref() == "foo" {
+//!                 *literal = Cons::from("bar");
+//!             }
+//!         }
+//!
+//!         rstgen::visit::walk_element_mut(self, element);
+//!     }
+//! }
+//!
+//! let mut toks: Tokens<Java> = Tokens::new();
+//! toks.append("int");
+//! toks.append(" ");
+//! toks.append("foo");
+//! toks.append(";");
+//!
+//! Rename.visit_tokens_mut(&mut toks);
+//! assert_eq!("int bar;", toks.to_string().unwrap());
+//! ```
+
+use shared;
+use {Element, Tokens};
+
+/// Visitor of the element tree of a set of tokens.
+///
+/// Every method walks into the visited node by default, overriding a method and calling the
+/// corresponding walk function continues the traversal.
+pub trait Visitor<'el, C: 'el> {
+    /// Visit a set of tokens.
+    fn visit_tokens(&mut self, tokens: &Tokens<'el, C>) {
+        walk_tokens(self, tokens);
+    }
+
+    /// Visit a single element.
+    fn visit_element(&mut self, element: &Element<'el, C>) {
+        walk_element(self, element);
+    }
+
+    /// Visit a custom element, which is either rendered or registered.
+    fn visit_custom(&mut self, _custom: &C) {}
+}
+
+/// Visit every element of the given tokens.
+pub fn walk_tokens<'el, C: 'el, V>(visitor: &mut V, tokens: &Tokens<'el, C>)
+where
+    V: Visitor<'el, C> + ?Sized,
+{
+    for element in tokens.elements() {
+        visitor.visit_element(element);
+    }
+}
+
+/// Visit the children of the given element.
+pub fn walk_element<'el, C: 'el, V>(visitor: &mut V, element: &Element<'el, C>)
+where
+    V: Visitor<'el, C> + ?Sized,
+{
+    use self::Element::*;
+
+    match *element {
+        Rc(ref element) => visitor.visit_element(element),
+        Borrowed(element) => visitor.visit_element(element),
+        Append(ref tokens)
+        | Push(ref tokens)
+        | Nested(ref tokens)
+        | Group(ref tokens)
+        | Indent(ref tokens)
+        | Align(_, ref tokens)
+        | Context(_, ref tokens)
+        | Region(_, ref tokens) => visitor.visit_tokens(tokens.as_ref()),
+        Custom(ref custom) | Registered(ref custom) => visitor.visit_custom(custom.as_ref()),
+        Slot(ref slot) => {
+            for entry in slot.entries() {
+                visitor.visit_tokens(entry);
+            }
+        }
+        Literal(_) | Quoted(_) | LineComment(_) | BlockComment(_) | DocComment(_) | Lazy(_)
+        | None | PushSpacing | Line | Spacing | LineSpacing | SoftLine | SoftSpacing => {}
+    }
+}
+
+/// Mutable visitor of the element tree of a set of tokens.
+///
+/// Borrowed and shared elements are cloned before they are visited. The entries of slots are not
+/// visited, since tokens only hold a [`SlotRef`](::SlotRef), which can't modify them.
+pub trait VisitorMut<'el, C: 'el + Clone> {
+    /// Visit a set of tokens.
+    fn visit_tokens_mut(&mut self, tokens: &mut Tokens<'el, C>) {
+        walk_tokens_mut(self, tokens);
+    }
+
+    /// Visit a single element.
+    fn visit_element_mut(&mut self, element: &mut Element<'el, C>) {
+        walk_element_mut(self, element);
+    }
+
+    /// Visit a custom element, which is either rendered or registered.
+    fn visit_custom_mut(&mut self, _custom: &mut C) {}
+}
+
+/// Visit every element of the given tokens mutably.
+pub fn walk_tokens_mut<'el, C: 'el + Clone, V>(visitor: &mut V, tokens: &mut Tokens<'el, C>)
+where
+    V: VisitorMut<'el, C> + ?Sized,
+{
+    for element in tokens.elements_mut() {
+        visitor.visit_element_mut(element);
+    }
+}
+
+/// Visit the children of the given element mutably.
+///
+/// A borrowed element is replaced by a clone of the element it borrows, which is then visited.
+pub fn walk_element_mut<'el, C: 'el + Clone, V>(visitor: &mut V, element: &mut Element<'el, C>)
+where
+    V: VisitorMut<'el, C> + ?Sized,
+{
+    use self::Element::*;
+
+    if let Borrowed(borrowed) = *element {
+        *element = borrowed.clone();
+        visitor.visit_element_mut(element);
+        return;
+    }
+
+    match *element {
+        Rc(ref mut element) => visitor.visit_element_mut(shared::Rc::make_mut(element)),
+        Append(ref mut tokens)
+        | Push(ref mut tokens)
+        | Nested(ref mut tokens)
+        | Group(ref mut tokens)
+        | Indent(ref mut tokens)
+        | Align(_, ref mut tokens)
+        | Context(_, ref mut tokens)
+        | Region(_, ref mut tokens) => visitor.visit_tokens_mut(tokens.make_mut()),
+        Custom(ref mut custom) | Registered(ref mut custom) => {
+            visitor.visit_custom_mut(custom.make_mut())
+        }
+        Borrowed(_) | Slot(_) | Literal(_) | Quoted(_) | LineComment(_) | BlockComment(_)
+        | DocComment(_) | Lazy(_) | None | PushSpacing | Line | Spacing | LineSpacing
+        | SoftLine | SoftSpacing => {}
+    }
+}
+
+/// Visitor replacing every custom element, see [`Tokens::map_custom`].
+pub(crate) struct MapCustom<F>(pub F);
+
+impl<'el, C: 'el + Clone, F> VisitorMut<'el, C> for MapCustom<F>
+where
+    F: FnMut(C) -> C,
+{
+    fn visit_custom_mut(&mut self, custom: &mut C) {
+        *custom = (self.0)(custom.clone());
+    }
+}
+
+/// Visitor retaining elements, see [`Tokens::retain`].
+pub(crate) struct Retain<F>(pub F);
+
+impl<'el, C: 'el + Clone, F> VisitorMut<'el, C> for Retain<F>
+where
+    F: FnMut(&Element<'el, C>) -> bool,
+{
+    fn visit_tokens_mut(&mut self, tokens: &mut Tokens<'el, C>) {
+        let f = &mut self.0;
+        tokens.elements_mut().retain(|element| f(element));
+        walk_tokens_mut(self, tokens);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Visitor, VisitorMut};
+    use java::{imported, Java};
+    use shared::Rc;
+    use {Element, Slot, Tokens};
+
+    /// Count the custom elements in a tree.
+    struct Count(usize);
+
+    impl<'el> Visitor<'el, Java<'el>> for Count {
+        fn visit_custom(&mut self, _: &Java<'el>) {
+            self.0 += 1;
+        }
+    }
+
+    #[test]
+    fn test_visit() {
+        let list = imported("java.util", "List");
+        let borrowed: &'static Element<Java> =
+            Box::leak(Box::new(Element::from(toks![list.clone()])));
+
+        let slot = Slot::new("helpers");
+        slot.push(toks![list.clone()]).unwrap();
+
+        let mut toks: Tokens<Java> = Tokens::new();
+        toks.append(Element::Rc(Rc::new(Element::from(toks![list.clone()]))));
+        toks.append_ref(borrowed);
+        toks.register(list.clone());
+        toks.slot(&slot);
+
+        let mut count = Count(0);
+        count.visit_tokens(&toks);
+        assert_eq!(4, count.0);
+    }
+
+    #[test]
+    fn test_map_custom() {
+        let shared = Rc::new(Element::from(toks![imported("java.util", "List"), " a;"]));
+
+        let mut toks: Tokens<Java> = Tokens::new();
+        toks.push(Element::Rc(shared.clone()));
+        toks.map_custom(|custom| {
+            if custom == imported("java.util", "List") {
+                imported("java.awt", "List")
+            } else {
+                custom
+            }
+        });
+
+        assert_eq!(
+            "import java.awt.List;\n\nList a;\n",
+            toks.to_file().unwrap()
+        );
+        // Shared elements are cloned before they are modified.
+        let mut original: Tokens<Java> = Tokens::new();
+        original.push(Element::Rc(shared));
+        assert_eq!(
+            "import java.util.List;\n\nList a;\n",
+            original.to_file().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut toks: Tokens<Java> = Tokens::new();
+        toks.register(imported("java.util", "List"));
+        toks.push("class Foo {");
+        toks.nested(toks![imported("java.io", "File"), " a;"]);
+        toks.push("}");
+        toks.register(imported("java.io", "Reader"));
+
+        toks.retain(|element| !matches!(*element, Element::Registered(_)));
+
+        assert_eq!(
+            "import java.io.File;\n\nclass Foo {\n  File a;\n}\n",
+            toks.to_file().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_slot_mut() {
+        struct Upper;
+
+        impl<'el> VisitorMut<'el, Java<'el>> for Upper {
+            fn visit_element_mut(&mut self, element: &mut Element<'el, Java<'el>>) {
+                if let Element::Literal(ref mut literal) = *element {
+                    *literal = literal.to_uppercase().into();
+                }
+
+                super::walk_element_mut(self, element);
+            }
+        }
+
+        let slot = Slot::new("helpers");
+        slot.push("int a;").unwrap();
+
+        let mut toks: Tokens<Java> = Tokens::new();
+        toks.slot(&slot);
+
+        // Slots are left as they are, since their entries can't be modified through the tokens.
+        Upper.visit_tokens_mut(&mut toks);
+        assert_eq!("int a;", toks.to_string().unwrap());
+    }
+}
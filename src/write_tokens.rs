@@ -1,44 +1,124 @@
 //! Helper trait to treat different containers as immediate targets for tokens.
 
 use super::custom::Custom;
-use super::formatter::Formatter;
+use super::error::Result;
+use super::formatter::{Formatter, FormatterConfig};
+use super::region::Regions;
+use super::source_map::SourceMap;
 use super::tokens::Tokens;
 use std::fmt;
+use std::mem;
 
 /// Helper trait to write tokens immediately to containers.
 pub trait WriteTokens {
     /// Write the given tokens to the container.
     fn write_tokens<'el, C: Custom>(
         &mut self,
-        tokens: Tokens<'el, C>,
+        tokens: &Tokens<'el, C>,
         extra: &mut C::Extra,
-    ) -> fmt::Result;
+    ) -> Result<()> {
+        self.write_tokens_with_config(tokens, extra, C::default_config())
+    }
 
     /// Write the given tokens to the container as a file.
     fn write_file<'el, C: Custom>(
         &mut self,
-        tokens: Tokens<'el, C>,
+        tokens: &Tokens<'el, C>,
+        extra: &mut C::Extra,
+    ) -> Result<()> {
+        self.write_file_with_config(tokens, extra, C::default_config())
+    }
+
+    /// Write the given tokens to the container, using the given formatter configuration.
+    fn write_tokens_with_config<'el, C: Custom>(
+        &mut self,
+        tokens: &Tokens<'el, C>,
+        extra: &mut C::Extra,
+        config: FormatterConfig,
+    ) -> Result<()>;
+
+    /// Write the given tokens to the container as a file, using the given formatter
+    /// configuration.
+    fn write_file_with_config<'el, C: Custom>(
+        &mut self,
+        tokens: &Tokens<'el, C>,
         extra: &mut C::Extra,
-    ) -> fmt::Result;
+        config: FormatterConfig,
+    ) -> Result<()> {
+        self.write_file_with_regions(tokens, extra, config, &mut Regions::new())
+    }
+
+    /// Write the given tokens to the container as a file, carrying over the content of the given
+    /// user regions.
+    ///
+    /// Regions which were used are marked as such, so that orphaned regions can be reported.
+    fn write_file_with_regions<'el, C: Custom>(
+        &mut self,
+        tokens: &Tokens<'el, C>,
+        extra: &mut C::Extra,
+        config: FormatterConfig,
+        regions: &mut Regions,
+    ) -> Result<()>;
+
+    /// Write the given tokens to the container as a file, returning a source map from the
+    /// output to the origins of the tokens.
+    fn write_file_with_source_map<'el, C: Custom>(
+        &mut self,
+        tokens: &Tokens<'el, C>,
+        extra: &mut C::Extra,
+        config: FormatterConfig,
+    ) -> Result<SourceMap>;
 }
 
 impl<W: fmt::Write> WriteTokens for W {
-    fn write_tokens<'el, C: Custom>(
+    fn write_tokens_with_config<'el, C: Custom>(
         &mut self,
-        tokens: Tokens<'el, C>,
+        tokens: &Tokens<'el, C>,
         extra: &mut C::Extra,
-    ) -> fmt::Result {
-        tokens.format(&mut Formatter::new(self), extra, 0usize)
+        config: FormatterConfig,
+    ) -> Result<()> {
+        tokens.format(&mut Formatter::with_config(self, config), extra, 0usize)
     }
 
-    fn write_file<'el, C: Custom>(
+    fn write_file_with_regions<'el, C: Custom>(
         &mut self,
-        tokens: Tokens<'el, C>,
+        tokens: &Tokens<'el, C>,
         extra: &mut C::Extra,
-    ) -> fmt::Result {
-        let mut formatter = Formatter::new(self);
-        C::write_file(tokens, &mut formatter, extra, 0usize)?;
+        config: FormatterConfig,
+        regions: &mut Regions,
+    ) -> Result<()> {
+        let mut formatter = Formatter::with_config(self, config);
+        mem::swap(formatter.regions_mut(), regions);
+
+        let result = write_file(tokens, &mut formatter, extra);
+        mem::swap(formatter.regions_mut(), regions);
+        result
+    }
+
+    fn write_file_with_source_map<'el, C: Custom>(
+        &mut self,
+        tokens: &Tokens<'el, C>,
+        extra: &mut C::Extra,
+        config: FormatterConfig,
+    ) -> Result<SourceMap> {
+        let mut formatter = Formatter::with_config(self, config);
+        formatter.record_source_map();
+        write_file(tokens, &mut formatter, extra)?;
+        Ok(formatter.take_source_map().unwrap_or_default())
+    }
+}
+
+/// Write the given tokens as a file to the formatter.
+fn write_file<'el, C: Custom>(
+    tokens: &Tokens<'el, C>,
+    formatter: &mut Formatter,
+    extra: &mut C::Extra,
+) -> Result<()> {
+    C::write_file(tokens, formatter, extra, 0usize)?;
+
+    if formatter.config().trailing_newline {
         formatter.new_line_unless_empty()?;
-        Ok(())
     }
+
+    Ok(())
 }